@@ -17,7 +17,8 @@ use std::collections::BTreeMap;
 use anyhow::Result;
 use diffly::{
     presentation::writers::{all_writers, write_to_file, writer_for},
-    AppConfig, Changeset, DbConfig, DiffConfig, ExcludedColumns, OutputConfig, TableConfig,
+    AppConfig, Changeset, CompareRules, DbConfig, DiffConfig, DiffStrategy, ExcludedColumns,
+    OutputConfig, ResolutionPolicy, SqlOutputConfig, TableConfig,
 };
 
 #[tokio::main]
@@ -40,7 +41,7 @@ async fn from_config_file(path: &str) -> Result<()> {
     let changeset = diffly::run(&cfg).await?;
 
     // Write all three output formats (JSON / SQL / HTML)
-    for writer in all_writers() {
+    for writer in all_writers(&cfg.output.sql) {
         write_to_file(&*writer, &changeset, &cfg.output.dir)?;
         println!(
             "Written: {}/{}.{}",
@@ -69,6 +70,7 @@ async fn programmatic_config() -> Result<()> {
         user: "diffly".into(),
         password: "diffly".into(),
         schema: schema.into(),
+        consistent_read: false,
     };
 
     let cfg = AppConfig {
@@ -83,28 +85,39 @@ async fn programmatic_config() -> Result<()> {
                         "created_at".into(),
                         "updated_at".into(),
                     ]),
+                    strategy: DiffStrategy::InMemory,
+                    compare: CompareRules::default(),
+                    on_conflict: ResolutionPolicy::default(),
                 },
                 TableConfig {
                     name: "discount_tiers".into(),
                     primary_key: vec!["id".into()],
                     excluded_columns: ExcludedColumns::default(),
+                    strategy: DiffStrategy::InMemory,
+                    compare: CompareRules::default(),
+                    on_conflict: ResolutionPolicy::default(),
                 },
                 TableConfig {
                     name: "tax_rules".into(),
                     primary_key: vec!["region_code".into(), "product_category".into()],
                     excluded_columns: ExcludedColumns::default(),
+                    strategy: DiffStrategy::InMemory,
+                    compare: CompareRules::default(),
+                    on_conflict: ResolutionPolicy::default(),
                 },
             ],
+            ..Default::default()
         },
         output: OutputConfig {
             dir: "./output".into(),
+            sql: SqlOutputConfig::default(),
         },
     };
 
     let changeset = diffly::run(&cfg).await?;
 
     // Write only the SQL migration file
-    let sql_writer = writer_for("sql").expect("sql writer always available");
+    let sql_writer = writer_for("sql", &cfg.output.sql).expect("sql writer always available");
     write_to_file(&*sql_writer, &changeset, &cfg.output.dir)?;
     println!(
         "SQL written: {}/{}.sql\n",
@@ -125,7 +138,10 @@ fn inspect_changeset(changeset: &Changeset) {
     println!("id      : {}", changeset.changeset_id);
     println!("source  : {}", changeset.source_schema);
     println!("target  : {}", changeset.target_schema);
-    println!("driver  : {}", changeset.driver);
+    println!(
+        "drivers : {} → {}",
+        changeset.source_driver, changeset.target_driver
+    );
     println!();
 
     for table in &changeset.tables {
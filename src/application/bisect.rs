@@ -0,0 +1,428 @@
+use anyhow::{ensure, Context, Result};
+use futures::future::try_join_all;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::domain::changeset::Changeset;
+use crate::domain::key_range::KeyRange;
+use crate::domain::ports::{Differ, RangeRepository};
+use crate::domain::table_diff::{ColumnTypes, TableDiff};
+use crate::domain::value_objects::{ColumnName, Schema, TableName};
+use crate::infrastructure::config::TableConfig;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// BisectDiffService
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Diffs tables by comparing server-side checksums of primary-key ranges.
+///
+/// Each range is hashed on both databases; equal checksums are skipped,
+/// mismatching ranges are split at the median key of the larger side until
+/// neither side holds more than `leaf_rows` rows. Only those leaf ranges are
+/// fetched and diffed row by row with the injected `Differ`, so the amount of
+/// data transferred grows with the number of changes, not the table size.
+///
+/// Produces the same `Changeset` as `DiffService::run_diff` (without
+/// fingerprints, since rows are never fully fetched). When the database
+/// cannot checksum server-side (SQLite), the whole table is fetched instead.
+pub struct BisectDiffService {
+    source_repo: Arc<dyn RangeRepository>,
+    target_repo: Arc<dyn RangeRepository>,
+    differ: Arc<dyn Differ>,
+    leaf_rows: u64,
+}
+
+impl BisectDiffService {
+    pub fn new(
+        source_repo: Arc<dyn RangeRepository>,
+        target_repo: Arc<dyn RangeRepository>,
+        differ: Arc<dyn Differ>,
+    ) -> Self {
+        Self {
+            source_repo,
+            target_repo,
+            differ,
+            leaf_rows: 1000,
+        }
+    }
+
+    /// Largest range (in rows, on either side) diffed row by row instead of
+    /// being split further. Clamped to at least 1.
+    pub fn with_leaf_rows(mut self, leaf_rows: u64) -> Self {
+        self.leaf_rows = leaf_rows.max(1);
+        self
+    }
+
+    /// Bisect every table in `tables` — drop-in alternative to
+    /// `DiffService::run_diff`.
+    pub async fn run_diff(
+        &self,
+        source_schema: &Schema,
+        target_schema: &Schema,
+        source_driver: &str,
+        target_driver: &str,
+        tables: &[TableConfig],
+    ) -> Result<Changeset> {
+        let table_diffs = try_join_all(
+            tables
+                .iter()
+                .map(|t| self.diff_table(source_schema, target_schema, t)),
+        )
+        .await?;
+
+        Ok(Changeset::new(
+            &source_schema.0,
+            &target_schema.0,
+            source_driver,
+            target_driver,
+            table_diffs,
+        ))
+    }
+
+    /// Bisect a single table.
+    pub async fn diff_table(
+        &self,
+        source_schema: &Schema,
+        target_schema: &Schema,
+        table_cfg: &TableConfig,
+    ) -> Result<TableDiff> {
+        let table_name = TableName(table_cfg.name.clone());
+        let pk_cols: Vec<ColumnName> = table_cfg
+            .primary_key
+            .iter()
+            .map(|pk| ColumnName(pk.clone()))
+            .collect();
+        let excluded = &table_cfg.excluded_columns;
+        ensure!(
+            !pk_cols.is_empty(),
+            "Bisecting {} requires a primary key",
+            table_name.0
+        );
+
+        let mut diff = TableDiff {
+            table_name: table_name.0.clone(),
+            primary_key: table_cfg.primary_key.clone(),
+            inserts: Vec::new(),
+            updates: Vec::new(),
+            deletes: Vec::new(),
+            data_quality: Vec::new(),
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+
+        // Depth-first, left range on top: leaves are diffed in key order.
+        let mut pending = vec![KeyRange::full()];
+        let (mut ranges_checked, mut rows_fetched) = (0usize, 0usize);
+
+        while let Some(range) = pending.pop() {
+            ranges_checked += 1;
+            let (source_sum, target_sum) = tokio::try_join!(
+                self.source_repo.checksum_range(
+                    source_schema,
+                    &table_name,
+                    &pk_cols,
+                    excluded,
+                    &range
+                ),
+                self.target_repo.checksum_range(
+                    target_schema,
+                    &table_name,
+                    &pk_cols,
+                    excluded,
+                    &range
+                )
+            )?;
+
+            let split_point = match (source_sum, target_sum) {
+                (Some(s), Some(t)) if s == t => continue,
+                (Some(s), Some(t)) if s.rows.max(t.rows) > self.leaf_rows => {
+                    // Median key of the larger side: both halves hold fewer
+                    // rows than the range, so the recursion always ends.
+                    let (repo, schema, rows) = if s.rows >= t.rows {
+                        (&self.source_repo, source_schema, s.rows)
+                    } else {
+                        (&self.target_repo, target_schema, t.rows)
+                    };
+                    repo.key_at(schema, &table_name, &pk_cols, &range, rows / 2 - 1)
+                        .await?
+                }
+                // Small mismatching range, or no server-side checksum.
+                _ => None,
+            };
+
+            if let Some(mid) = split_point {
+                let (left, right) = range.split_at(mid);
+                pending.push(right);
+                pending.push(left);
+                continue;
+            }
+
+            let (source_rows, target_rows) = tokio::try_join!(
+                self.source_repo.fetch_range(
+                    source_schema,
+                    &table_name,
+                    &pk_cols,
+                    excluded,
+                    &range
+                ),
+                self.target_repo.fetch_range(
+                    target_schema,
+                    &table_name,
+                    &pk_cols,
+                    excluded,
+                    &range
+                )
+            )
+            .with_context(|| format!("Failed to fetch a mismatching range of {}", table_name.0))?;
+            debug!(
+                table = %table_name.0,
+                source_rows = source_rows.len(),
+                target_rows = target_rows.len(),
+                "bisect leaf"
+            );
+            rows_fetched += source_rows.len() + target_rows.len();
+
+            let leaf = self
+                .differ
+                .diff_table(&source_rows, &target_rows, &pk_cols, &table_name);
+            diff.inserts.extend(leaf.inserts);
+            diff.updates.extend(leaf.updates);
+            diff.deletes.extend(leaf.deletes);
+            diff.data_quality.extend(leaf.data_quality);
+        }
+
+        info!(
+            table = %table_name.0,
+            ranges_checked,
+            rows_fetched,
+            "bisect completed"
+        );
+
+        Ok(diff)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::diff::TableDiffer;
+    use crate::domain::compare_rules::CompareRules;
+    use crate::domain::key_range::RangeChecksum;
+    use crate::domain::resolution::ResolutionPolicy;
+    use crate::domain::row_key::compare_keys;
+    use crate::domain::table_diff::RowMap;
+    use crate::domain::value_objects::ExcludedColumns;
+    use crate::infrastructure::config::DiffStrategy;
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use std::cmp::Ordering;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    /// In-memory `RangeRepository` over rows keyed by `id`.
+    struct FakeRanges {
+        rows: Vec<RowMap>,
+        checksums: bool,
+        fetched: AtomicUsize,
+    }
+
+    impl FakeRanges {
+        fn new(rows: Vec<RowMap>) -> Self {
+            Self {
+                rows,
+                checksums: true,
+                fetched: AtomicUsize::new(0),
+            }
+        }
+
+        fn in_range(&self, pk_cols: &[ColumnName], range: &KeyRange) -> Vec<&RowMap> {
+            let key =
+                |r: &RowMap| -> Vec<Value> { pk_cols.iter().map(|c| r[&c.0].clone()).collect() };
+            let mut rows: Vec<&RowMap> = self
+                .rows
+                .iter()
+                .filter(|r| {
+                    let k = key(r);
+                    range
+                        .lower
+                        .as_ref()
+                        .map_or(true, |l| compare_keys(&k, l) == Ordering::Greater)
+                        && range
+                            .upper
+                            .as_ref()
+                            .map_or(true, |u| compare_keys(&k, u) != Ordering::Greater)
+                })
+                .collect();
+            rows.sort_by(|a, b| compare_keys(&key(a), &key(b)));
+            rows
+        }
+    }
+
+    #[async_trait]
+    impl RangeRepository for FakeRanges {
+        async fn checksum_range(
+            &self,
+            _schema: &Schema,
+            _table: &TableName,
+            pk_cols: &[ColumnName],
+            _excluded: &ExcludedColumns,
+            range: &KeyRange,
+        ) -> Result<Option<RangeChecksum>> {
+            if !self.checksums {
+                return Ok(None);
+            }
+            let rows = self.in_range(pk_cols, range);
+            let content: Vec<String> = rows
+                .iter()
+                .map(|r| serde_json::to_string(r).unwrap())
+                .collect();
+            Ok(Some(RangeChecksum {
+                rows: rows.len() as u64,
+                checksum: content.join("\n"),
+            }))
+        }
+
+        async fn key_at(
+            &self,
+            _schema: &Schema,
+            _table: &TableName,
+            pk_cols: &[ColumnName],
+            range: &KeyRange,
+            offset: u64,
+        ) -> Result<Option<Vec<Value>>> {
+            Ok(self
+                .in_range(pk_cols, range)
+                .get(offset as usize)
+                .map(|r| pk_cols.iter().map(|c| r[&c.0].clone()).collect()))
+        }
+
+        async fn fetch_range(
+            &self,
+            _schema: &Schema,
+            _table: &TableName,
+            pk_cols: &[ColumnName],
+            _excluded: &ExcludedColumns,
+            range: &KeyRange,
+        ) -> Result<Vec<RowMap>> {
+            let rows: Vec<RowMap> = self.in_range(pk_cols, range).into_iter().cloned().collect();
+            self.fetched.fetch_add(rows.len(), AtomicOrdering::Relaxed);
+            Ok(rows)
+        }
+    }
+
+    fn row(id: i64, val: &str) -> RowMap {
+        [
+            ("id".to_string(), json!(id)),
+            ("val".to_string(), json!(val)),
+        ]
+        .into()
+    }
+
+    fn table_cfg() -> TableConfig {
+        TableConfig {
+            name: "events".to_string(),
+            primary_key: vec!["id".to_string()],
+            excluded_columns: ExcludedColumns::default(),
+            strategy: DiffStrategy::Bisect,
+            compare: CompareRules::default(),
+            on_conflict: ResolutionPolicy::default(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema("public".to_string())
+    }
+
+    #[tokio::test]
+    async fn bisect_matches_table_differ_and_fetches_only_mismatching_ranges() {
+        let source: Vec<RowMap> = (1..=200)
+            .filter(|i| *i != 50)
+            .map(|i| row(i, "a"))
+            .collect();
+        let mut target: Vec<RowMap> = (1..=200)
+            .filter(|i| *i != 120)
+            .map(|i| row(i, "a"))
+            .collect();
+        target[10] = row(11, "changed");
+
+        let expected = TableDiffer::new().diff_table(
+            &source,
+            &target,
+            &[ColumnName("id".to_string())],
+            &TableName("events".to_string()),
+        );
+
+        let source_repo = Arc::new(FakeRanges::new(source));
+        let target_repo = Arc::new(FakeRanges::new(target));
+        let service = BisectDiffService::new(
+            source_repo.clone(),
+            target_repo.clone(),
+            Arc::new(TableDiffer::new()),
+        )
+        .with_leaf_rows(8);
+
+        let diff = service
+            .diff_table(&schema(), &schema(), &table_cfg())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(diff.inserts[0].pk["id"], json!(120));
+        assert_eq!(diff.updates[0].pk["id"], json!(11));
+        assert_eq!(diff.deletes[0].pk["id"], json!(50));
+
+        let fetched = source_repo.fetched.load(AtomicOrdering::Relaxed)
+            + target_repo.fetched.load(AtomicOrdering::Relaxed);
+        assert!(fetched < 100, "fetched {fetched} rows out of ~400");
+    }
+
+    #[tokio::test]
+    async fn bisect_identical_tables_fetch_nothing() {
+        let rows: Vec<RowMap> = (1..=50).map(|i| row(i, "a")).collect();
+        let source_repo = Arc::new(FakeRanges::new(rows.clone()));
+        let target_repo = Arc::new(FakeRanges::new(rows));
+        let service = BisectDiffService::new(
+            source_repo.clone(),
+            target_repo.clone(),
+            Arc::new(TableDiffer::new()),
+        );
+
+        let diff = service
+            .diff_table(&schema(), &schema(), &table_cfg())
+            .await
+            .unwrap();
+
+        assert!(diff.is_empty());
+        assert_eq!(source_repo.fetched.load(AtomicOrdering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn bisect_without_checksums_falls_back_to_full_fetch() {
+        let mut source_repo = FakeRanges::new(vec![row(1, "a"), row(2, "b")]);
+        let mut target_repo = FakeRanges::new(vec![row(2, "c"), row(3, "d")]);
+        source_repo.checksums = false;
+        target_repo.checksums = false;
+
+        let service = BisectDiffService::new(
+            Arc::new(source_repo),
+            Arc::new(target_repo),
+            Arc::new(TableDiffer::new()),
+        )
+        .with_leaf_rows(1);
+
+        let changeset = service
+            .run_diff(&schema(), &schema(), "sqlite", "sqlite", &[table_cfg()])
+            .await
+            .unwrap();
+
+        assert_eq!(changeset.summary.total_inserts, 1);
+        assert_eq!(changeset.summary.total_updates, 1);
+        assert_eq!(changeset.summary.total_deletes, 1);
+    }
+}
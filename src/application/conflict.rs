@@ -1,15 +1,22 @@
 use std::collections::{BTreeMap, BTreeSet};
 
+use anyhow::{Context, Result};
 use serde_json::Value;
+use tracing::warn;
+
+use crate::application::diff::whole_row_key;
+use crate::application::resolution::ResolutionService;
 
 use crate::domain::changeset::Changeset;
-use crate::domain::conflict::ConflictReport;
+use crate::domain::compare_rules::{CompareRules, TableRules};
+use crate::domain::conflict::{ConflictKind, ConflictReport};
 use crate::domain::diff_result::DiffResult;
 use crate::domain::fingerprint::fingerprint;
 use crate::domain::ports::SnapshotProvider;
-use crate::domain::table_diff::RowMap;
+use crate::domain::resolution::{AutoResolution, ResolutionFile, ResolutionPolicy};
+use crate::domain::row_key::RowKey;
+use crate::domain::table_diff::{RowMap, TableDiff};
 use crate::domain::value_objects::{ColumnName, Fingerprint, TableName};
-use crate::infrastructure::db::sql_utils::pk_key;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // ConflictService
@@ -28,20 +35,48 @@ use crate::infrastructure::db::sql_utils::pk_key;
 /// For each table in the changeset:
 /// 1. Look up the base snapshot rows (target at clone time) from the provider.
 ///    If absent → skip (no base to compare against → treat as clean).
-/// 2. Build delta maps:
-///    - `base→source`: what the admin changed in the source.
-///    - `base→target`: what others deployed into target since the clone.
-/// 3. For each `(pk_key, column)` present in **both** deltas where:
-///    - the source value ≠ base value  (admin changed it)
-///    - the target value ≠ base value  (someone else changed it)
-///    - source value ≠ target value    (they chose different values)
-///    - emit a `ConflictReport`.
-/// 4. Auto-merged changes (only one side changed) require no action.
-pub struct ConflictService;
+/// 2. Line up every row of the changeset with its base row by primary key:
+///    - an **insert** (source only) whose base row the source changed:
+///      target deleted it → update/delete;
+///    - an **update** (both sides) without a base row: both inserted it →
+///      insert/insert; with one, each `(primary key, column)` where the
+///      source value ≠ base, the target value ≠ base, and source ≠ target
+///      → update/update;
+///    - a **delete** (target only) whose base row target changed: the source
+///      deleted it → delete/update;
+///    - a base row gone from both sides → delete/delete, which is benign.
+///
+///    A table without a key is compared by copies of whole rows: a row
+///    whose number of copies both sides changed since the base, to
+///    different numbers, is a row-level conflict (see `copies`).
+/// 3. Auto-merged changes (only one side changed) require no action.
+///
+/// "≠" follows the table's comparison rules, exactly like the differ.
+pub struct ConflictService {
+    rules: TableRules,
+    policies: BTreeMap<String, ResolutionPolicy>,
+}
 
 impl ConflictService {
     pub fn new() -> Self {
-        Self
+        Self {
+            rules: TableRules::default(),
+            policies: BTreeMap::new(),
+        }
+    }
+
+    /// Decide whether a value changed with the configured rules, the same
+    /// way the differ does.
+    pub fn with_rules(mut self, rules: TableRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Settle the conflicts of these tables (by name) with their strategies
+    /// instead of reporting them.
+    pub fn with_policies(mut self, policies: BTreeMap<String, ResolutionPolicy>) -> Self {
+        self.policies = policies;
+        self
     }
 
     /// Run the conflict check.
@@ -61,8 +96,9 @@ impl ConflictService {
         stored_fingerprints: &BTreeMap<String, Fingerprint>,
         current_target_rows: &BTreeMap<String, Vec<RowMap>>,
         pk_cols_by_table: &BTreeMap<String, Vec<ColumnName>>,
-    ) -> DiffResult {
+    ) -> Result<DiffResult> {
         let mut all_conflicts: Vec<ConflictReport> = Vec::new();
+        let mut auto_resolved: Vec<AutoResolution> = Vec::new();
 
         for table_diff in &changeset.tables {
             let table_name = TableName(table_diff.table_name.clone());
@@ -70,7 +106,6 @@ impl ConflictService {
                 Some(cols) => cols,
                 None => continue,
             };
-
             // Fast path: compare fingerprints first.
             // If current target fingerprint == stored fingerprint, target has
             // not changed since the clone — no conflict possible for this table.
@@ -94,94 +129,319 @@ impl ConflictService {
                 None => continue,
             };
 
-            // Build indexed maps keyed by pk_key string.
-            let base_index: BTreeMap<String, &RowMap> =
-                base_rows.iter().map(|r| (pk_key(r, pk_cols), r)).collect();
-            let current_index: BTreeMap<String, &RowMap> = current_rows
+            let rules = self.rules.for_table(&table_diff.table_name);
+
+            // Without a key, base, source and target rows cannot be lined up:
+            // keyless tables are compared by how many copies of each row
+            // every side holds.
+            if pk_cols.is_empty() {
+                TableConflicts {
+                    table_name: &table_diff.table_name,
+                    pk_cols,
+                    rules,
+                    policy: None,
+                    reports: &mut all_conflicts,
+                    auto_resolved: &mut auto_resolved,
+                }
+                .copies(table_diff, base_rows, current_rows);
+                continue;
+            }
+
+            // Build indexed maps keyed by typed primary key.
+            let base_index: BTreeMap<RowKey, &RowMap> = base_rows
                 .iter()
-                .map(|r| (pk_key(r, pk_cols), r))
+                .map(|r| (RowKey::from_row(r, pk_cols), r))
                 .collect();
+            let current_index: BTreeMap<RowKey, &RowMap> = current_rows
+                .iter()
+                .map(|r| (RowKey::from_row(r, pk_cols), r))
+                .collect();
+
+            let mut conflicts = TableConflicts {
+                table_name: &table_diff.table_name,
+                pk_cols,
+                rules,
+                policy: self.policies.get(&table_diff.table_name),
+                reports: &mut all_conflicts,
+                auto_resolved: &mut auto_resolved,
+            };
 
-            // Build source (source) index from the changeset inserts + updates.
-            // For conflict detection we only need rows that exist in source.
-            // We reconstruct the full source row from the changeset's after/data fields.
-            let mut source_index: BTreeMap<String, RowMap> = BTreeMap::new();
+            // Inserts: the source has the row, target does not. With a base
+            // row, target deleted it; a source change to it is update/delete.
             for ins in &table_diff.inserts {
-                let k = pk_key(&ins.data, pk_cols);
-                source_index.insert(k, ins.data.clone());
+                let k = RowKey::from_row(&ins.data, pk_cols);
+                if let Some(&base_row) = base_index.get(&k) {
+                    if conflicts.row_changed(&ins.data, base_row) {
+                        conflicts.row(
+                            ConflictKind::UpdateDelete,
+                            Some(base_row),
+                            Some(&ins.data),
+                            None,
+                        );
+                    }
+                }
             }
+
+            // Updates: both sides have the row, with different content.
+            // Without a base row both inserted it; otherwise compare column
+            // by column.
             for upd in &table_diff.updates {
-                let k = pk_key(&upd.after, pk_cols);
-                source_index.insert(k, upd.after.clone());
+                let k = RowKey::from_row(&upd.after, pk_cols);
+                let current_row = current_index.get(&k).copied().unwrap_or(&upd.before);
+                match base_index.get(&k) {
+                    None => conflicts.row(
+                        ConflictKind::InsertInsert,
+                        None,
+                        Some(&upd.after),
+                        Some(current_row),
+                    ),
+                    Some(&base_row) => conflicts.columns(base_row, &upd.after, current_row),
+                }
             }
 
-            // Iterate only over rows that the source actually changed.
-            // A conflict requires the source to have modified a row; rows that
-            // were only changed in target (with no source counterpart) are
-            // auto-merged — they cannot conflict with source changes.
-            for pk_str in source_index.keys() {
-                // Normalise: all three are `Option<&RowMap>`.
-                // base_index / current_index store `&RowMap` values so `.get()`
-                // would return `Option<&&RowMap>`; `.copied()` flattens one `&`.
-                let base_row: Option<&RowMap> = base_index.get(pk_str).copied();
-                let current_row: Option<&RowMap> = current_index.get(pk_str).copied();
-                let source_row: Option<&RowMap> = source_index.get(pk_str).map(|r| r as &RowMap);
-
-                // Collect all columns across all three states.
-                let all_cols: BTreeSet<String> = [base_row, current_row, source_row]
-                    .iter()
-                    .filter_map(|opt: &Option<&RowMap>| {
-                        opt.map(|r: &RowMap| r.keys().cloned().collect::<Vec<_>>())
-                    })
-                    .flatten()
-                    .collect();
-
-                for col in &all_cols {
-                    let null = Value::Null;
-                    let base_val = base_row.and_then(|r| r.get(col.as_str())).unwrap_or(&null);
-                    let current_val = current_row
-                        .and_then(|r| r.get(col.as_str()))
-                        .unwrap_or(&null);
-                    let source_val = source_row
-                        .and_then(|r| r.get(col.as_str()))
-                        .unwrap_or(&null);
-
-                    let target_changed = current_val != base_val;
-                    let source_changed = source_val != base_val;
-
-                    if target_changed && source_changed && source_val != current_val {
-                        // Reconstruct the PK map for the report.
-                        let pk_map: BTreeMap<String, Value> = pk_cols
-                            .iter()
-                            .filter_map(|c| {
-                                base_row
-                                    .or(current_row)
-                                    .and_then(|r| r.get(c.0.as_str()))
-                                    .map(|v| (c.0.clone(), v.clone()))
-                            })
-                            .collect();
-
-                        all_conflicts.push(ConflictReport {
-                            table_name: table_diff.table_name.clone(),
-                            pk: pk_map,
-                            column: col.clone(),
-                            base_value: base_val.clone(),
-                            source_value: source_val.clone(),
-                            target_value: current_val.clone(),
-                        });
+            // Deletes: target has the row, the source does not. With a base
+            // row the source deleted it; a target change to it is
+            // delete/update.
+            for del in &table_diff.deletes {
+                let k = RowKey::from_row(&del.data, pk_cols);
+                if let Some(&base_row) = base_index.get(&k) {
+                    let current_row = current_index.get(&k).copied().unwrap_or(&del.data);
+                    if conflicts.row_changed(current_row, base_row) {
+                        conflicts.row(
+                            ConflictKind::DeleteUpdate,
+                            Some(base_row),
+                            None,
+                            Some(current_row),
+                        );
                     }
                 }
             }
+
+            // Base rows gone from target and not re-inserted by the source
+            // were deleted on both sides.
+            let inserted: BTreeSet<RowKey> = table_diff
+                .inserts
+                .iter()
+                .map(|ins| RowKey::from_row(&ins.data, pk_cols))
+                .collect();
+            for (k, &base_row) in &base_index {
+                if !current_index.contains_key(k) && !inserted.contains(k) {
+                    conflicts.row(ConflictKind::DeleteDelete, Some(base_row), None, None);
+                }
+            }
         }
 
-        if all_conflicts.is_empty() {
-            DiffResult::Clean(changeset)
+        // Fold the settled conflicts into the changeset, like `resolve`.
+        let changeset = if auto_resolved.is_empty() {
+            changeset
+        } else {
+            ResolutionService::new()
+                .with_rules(self.rules.clone())
+                .resolve(ResolutionFile {
+                    resolutions: auto_resolved
+                        .iter()
+                        .map(AutoResolution::to_resolution)
+                        .collect(),
+                    changeset,
+                })
+                .context("Cannot apply the on_conflict strategies")?
+        };
+
+        // Delete/delete alone needs no decision: the result stays clean.
+        Ok(if !all_conflicts.iter().any(ConflictReport::is_blocking) {
+            DiffResult::Clean {
+                changeset,
+                auto_resolved,
+            }
         } else {
             DiffResult::Conflicted {
                 changeset,
                 conflicts: all_conflicts,
+                auto_resolved,
+            }
+        })
+    }
+}
+
+/// Collects the conflicts of one table.
+struct TableConflicts<'a> {
+    table_name: &'a str,
+    pk_cols: &'a [ColumnName],
+    rules: &'a CompareRules,
+    policy: Option<&'a ResolutionPolicy>,
+    reports: &'a mut Vec<ConflictReport>,
+    auto_resolved: &'a mut Vec<AutoResolution>,
+}
+
+impl TableConflicts<'_> {
+    /// Record `report`, settled by the table's policy when it has a strategy
+    /// for it that can decide between the `source` and `target` rows.
+    fn push(&mut self, report: ConflictReport, source: Option<&RowMap>, target: Option<&RowMap>) {
+        if report.is_blocking() {
+            let strategy = self
+                .policy
+                .and_then(|p| p.for_conflict(report.column.as_deref()));
+            if let Some(strategy) = strategy {
+                if let Some(choice) = strategy.choose(source, target) {
+                    self.auto_resolved.push(AutoResolution {
+                        conflict: report,
+                        strategy: strategy.clone(),
+                        choice,
+                    });
+                    return;
+                }
+                if let Some(reason) = strategy.undecided_reason(source, target) {
+                    warn!(table = %self.table_name, %reason, "on_conflict left a conflict undecided");
+                }
             }
         }
+        self.reports.push(report);
+    }
+
+    /// Whether any column differs between `a` and `b` (a missing column
+    /// counts as NULL).
+    fn row_changed(&self, a: &RowMap, b: &RowMap) -> bool {
+        let null = Value::Null;
+        a.keys().chain(b.keys()).any(|col| {
+            let va = a.get(col).unwrap_or(&null);
+            let vb = b.get(col).unwrap_or(&null);
+            !self.rules.values_equal(col, va, vb)
+        })
+    }
+
+    /// Report a row-level conflict; each side is the whole row, or `None`
+    /// where it does not exist.
+    fn row(
+        &mut self,
+        kind: ConflictKind,
+        base: Option<&RowMap>,
+        source: Option<&RowMap>,
+        target: Option<&RowMap>,
+    ) {
+        let as_value = |row: Option<&RowMap>| {
+            row.map_or(Value::Null, |r| {
+                Value::Object(r.clone().into_iter().collect())
+            })
+        };
+        let pk = self.pk_map([base, source, target].into_iter().flatten().next());
+        let report = ConflictReport {
+            kind,
+            table_name: self.table_name.to_string(),
+            pk,
+            column: None,
+            base_value: as_value(base),
+            source_value: as_value(source),
+            target_value: as_value(target),
+        };
+        self.push(report, source, target);
+    }
+
+    /// Report the whole rows of a keyless table whose number of copies both
+    /// sides changed since the base, to different numbers. Fewer copies on
+    /// both sides is delete/delete; more on both is insert/insert; otherwise
+    /// the side that removed copies deleted the row the other one kept.
+    /// Without a key there is no row to pick a side for: policies do not
+    /// apply.
+    fn copies(&mut self, table_diff: &TableDiff, base_rows: &[RowMap], current_rows: &[RowMap]) {
+        // Copies of every row the changeset inserts or deletes.
+        let mut changed: BTreeMap<RowKey, (&RowMap, usize, usize)> = BTreeMap::new();
+        for ins in &table_diff.inserts {
+            let entry = changed.entry(whole_row_key(&ins.data));
+            entry.or_insert((&ins.data, 0, 0)).1 += 1;
+        }
+        for del in &table_diff.deletes {
+            let entry = changed.entry(whole_row_key(&del.data));
+            entry.or_insert((&del.data, 0, 0)).2 += 1;
+        }
+        let count = |rows: &[RowMap]| {
+            let mut counts: BTreeMap<RowKey, usize> = BTreeMap::new();
+            for key in rows.iter().map(whole_row_key) {
+                if changed.contains_key(&key) {
+                    *counts.entry(key).or_default() += 1;
+                }
+            }
+            counts
+        };
+        let (base_counts, target_counts) = (count(base_rows), count(current_rows));
+
+        for (key, &(row, inserted, deleted)) in &changed {
+            let base = base_counts.get(key).copied().unwrap_or(0);
+            let target = target_counts.get(key).copied().unwrap_or(0);
+            // The changeset turns the target copies into the source ones.
+            let source = (target + inserted).saturating_sub(deleted);
+            if source == base || target == base {
+                continue;
+            }
+            let kind = match (source < base, target < base) {
+                (true, true) => ConflictKind::DeleteDelete,
+                (false, false) => ConflictKind::InsertInsert,
+                (false, true) => ConflictKind::UpdateDelete,
+                (true, false) => ConflictKind::DeleteUpdate,
+            };
+            let present = |n: usize| {
+                if n > 0 {
+                    Value::Object(row.clone().into_iter().collect())
+                } else {
+                    Value::Null
+                }
+            };
+            self.reports.push(ConflictReport {
+                kind,
+                table_name: self.table_name.to_string(),
+                pk: BTreeMap::new(),
+                column: None,
+                base_value: present(base),
+                source_value: present(source),
+                target_value: present(target),
+            });
+        }
+    }
+
+    /// Report every column changed on both sides to different values.
+    fn columns(&mut self, base_row: &RowMap, source_row: &RowMap, current_row: &RowMap) {
+        // Collect all columns across all three states.
+        let all_cols: BTreeSet<&String> = base_row
+            .keys()
+            .chain(source_row.keys())
+            .chain(current_row.keys())
+            .collect();
+
+        for col in all_cols {
+            let null = Value::Null;
+            let base_val = base_row.get(col).unwrap_or(&null);
+            let current_val = current_row.get(col).unwrap_or(&null);
+            let source_val = source_row.get(col).unwrap_or(&null);
+
+            let target_changed = !self.rules.values_equal(col, current_val, base_val);
+            let source_changed = !self.rules.values_equal(col, source_val, base_val);
+
+            if target_changed
+                && source_changed
+                && !self.rules.values_equal(col, source_val, current_val)
+            {
+                let report = ConflictReport {
+                    kind: ConflictKind::UpdateUpdate,
+                    table_name: self.table_name.to_string(),
+                    pk: self.pk_map(Some(base_row)),
+                    column: Some(col.clone()),
+                    base_value: base_val.clone(),
+                    source_value: source_val.clone(),
+                    target_value: current_val.clone(),
+                };
+                self.push(report, Some(source_row), Some(current_row));
+            }
+        }
+    }
+
+    /// The primary key of `row`, for the report.
+    fn pk_map(&self, row: Option<&RowMap>) -> BTreeMap<String, Value> {
+        self.pk_cols
+            .iter()
+            .filter_map(|c| {
+                row.and_then(|r| r.get(c.0.as_str()))
+                    .map(|v| (c.0.clone(), v.clone()))
+            })
+            .collect()
     }
 }
 
@@ -199,7 +459,9 @@ impl Default for ConflictService {
 mod tests {
     use super::*;
     use crate::domain::changeset::Changeset;
-    use crate::domain::table_diff::{ColumnDiff, RowUpdate, TableDiff};
+    use crate::domain::compare_rules::CompareRules;
+    use crate::domain::resolution::Choice;
+    use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate, TableDiff};
     use serde_json::json;
 
     // ── Helper: minimal snapshot provider backed by a BTreeMap ──
@@ -224,7 +486,7 @@ mod tests {
     }
 
     fn empty_changeset() -> Changeset {
-        Changeset::new("source", "target", "postgres", vec![])
+        Changeset::new("source", "target", "postgres", "postgres", vec![])
     }
 
     // ── Tests ──
@@ -234,13 +496,15 @@ mod tests {
         let svc = ConflictService::new();
         let cs = empty_changeset();
         let base = MapSnapshot(BTreeMap::new());
-        let result = svc.check(
-            cs,
-            &base,
-            &BTreeMap::new(),
-            &BTreeMap::new(),
-            &BTreeMap::new(),
-        );
+        let result = svc
+            .check(
+                cs,
+                &base,
+                &BTreeMap::new(),
+                &BTreeMap::new(),
+                &BTreeMap::new(),
+            )
+            .unwrap();
         assert!(result.is_clean());
     }
 
@@ -257,12 +521,16 @@ mod tests {
             "s",
             "target",
             "postgres",
+            "postgres",
             vec![TableDiff {
                 table_name: table.to_string(),
                 primary_key: vec!["id".to_string()],
                 inserts: vec![],
                 updates: vec![],
                 deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
             }],
         );
 
@@ -271,10 +539,163 @@ mod tests {
         let current_rows = [(table.to_string(), target_rows)].into();
         let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
 
-        let result = svc.check(cs, &base, &stored_fps, &current_rows, &pk_map);
+        let result = svc
+            .check(cs, &base, &stored_fps, &current_rows, &pk_map)
+            .unwrap();
+        assert!(result.is_clean());
+    }
+
+    #[test]
+    fn keyless_rows_changed_on_one_side_do_not_conflict() {
+        let svc = ConflictService::new();
+        let table = "audit_log";
+        let base_rows = vec![row(&[("msg", json!("a"))])];
+        let target_rows = vec![row(&[("msg", json!("b"))])];
+        let inserted = row(&[("msg", json!("c"))]);
+
+        let cs = Changeset::new(
+            "s",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: table.to_string(),
+                primary_key: vec![],
+                inserts: vec![RowChange {
+                    pk: BTreeMap::new(),
+                    data: inserted,
+                }],
+                updates: vec![],
+                deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+
+        // Lined up by the empty key, "msg" would be reported as changed on
+        // both sides; by copies, only the source touched "c".
+        let base = MapSnapshot([(table.to_string(), base_rows)].into());
+        let current_rows = [(table.to_string(), target_rows)].into();
+        let pk_map = [(table.to_string(), vec![])].into();
+
+        let result = svc
+            .check(cs, &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
         assert!(result.is_clean());
     }
 
+    #[test]
+    fn keyless_rows_whose_copies_both_sides_changed_conflict() {
+        let table = "audit_log";
+        let msg = |m: &str| row(&[("msg", json!(m))]);
+        let change = |m: &str| RowChange {
+            pk: BTreeMap::new(),
+            data: msg(m),
+        };
+        // "a": both deleted a copy, the source both.
+        // "b": the source deleted it, target added a copy.
+        // "c": target deleted it, the source added a copy.
+        // "d": only the source inserted it.
+        let base_rows = vec![msg("a"), msg("a"), msg("b"), msg("c")];
+        let target_rows = vec![msg("a"), msg("b"), msg("b")];
+        let cs = Changeset::new(
+            "s",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: table.to_string(),
+                primary_key: vec![],
+                inserts: vec![change("c"), change("c"), change("d")],
+                updates: vec![],
+                deletes: vec![change("a"), change("b"), change("b")],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+
+        let base = MapSnapshot([(table.to_string(), base_rows)].into());
+        let current_rows = [(table.to_string(), target_rows)].into();
+        let pk_map = [(table.to_string(), vec![])].into();
+        let result = ConflictService::new()
+            .check(cs, &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
+
+        let kinds: Vec<(ConflictKind, Value, Value)> = result
+            .conflicts()
+            .iter()
+            .map(|c| (c.kind, c.source_value.clone(), c.target_value.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (ConflictKind::DeleteDelete, Value::Null, json!({"msg": "a"})),
+                (ConflictKind::DeleteUpdate, Value::Null, json!({"msg": "b"})),
+                (ConflictKind::UpdateDelete, json!({"msg": "c"}), Value::Null),
+            ]
+        );
+        assert!(!result.is_clean());
+    }
+
+    #[test]
+    fn compare_rules_decide_what_target_changed() {
+        let table = "tiers";
+        let base_rows = vec![row(&[("id", json!(1)), ("name", json!("Gold"))])];
+        // Target only re-cased the name; source renamed it.
+        let target_rows = vec![row(&[("id", json!(1)), ("name", json!("GOLD"))])];
+        let changeset = || {
+            Changeset::new(
+                "source",
+                "target",
+                "postgres",
+                "postgres",
+                vec![TableDiff {
+                    table_name: table.to_string(),
+                    primary_key: vec!["id".to_string()],
+                    inserts: vec![],
+                    updates: vec![RowUpdate {
+                        pk: [("id".to_string(), json!(1))].into(),
+                        before: target_rows[0].clone(),
+                        after: row(&[("id", json!(1)), ("name", json!("Platinum"))]),
+                        changed_columns: vec![ColumnDiff {
+                            column: "name".to_string(),
+                            before: json!("GOLD"),
+                            after: json!("Platinum"),
+                            patch: None,
+                        }],
+                    }],
+                    deletes: vec![],
+                    data_quality: vec![],
+                    column_types: ColumnTypes::new(),
+                    excluded_columns: Vec::new(),
+                }],
+            )
+        };
+        let base = MapSnapshot([(table.to_string(), base_rows)].into());
+        let current_rows = [(table.to_string(), target_rows.clone())].into();
+        let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
+
+        let strict = ConflictService::new()
+            .check(changeset(), &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
+        assert_eq!(strict.conflicts().len(), 1);
+
+        let ignore_case: CompareRules = serde_json::from_value(json!({
+            "columns": { "name": { "ignore_case": true } }
+        }))
+        .unwrap();
+        let lenient = ConflictService::new()
+            .with_rules(TableRules::new(
+                &CompareRules::default(),
+                [(table, &ignore_case)],
+            ))
+            .check(changeset(), &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
+        assert!(lenient.is_clean());
+    }
+
     #[test]
     fn detects_conflict_on_same_row_same_column() {
         let svc = ConflictService::new();
@@ -296,6 +717,7 @@ mod tests {
             "source",
             "target",
             "postgres",
+            "postgres",
             vec![TableDiff {
                 table_name: table.to_string(),
                 primary_key: vec!["id".to_string()],
@@ -308,9 +730,13 @@ mod tests {
                         column: "discount_rate".to_string(),
                         before: json!(0.15),
                         after: json!(0.20),
+                        patch: None,
                     }],
                 }],
                 deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
             }],
         );
 
@@ -319,17 +745,194 @@ mod tests {
         let current_rows = [(table.to_string(), target_rows)].into();
         let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
 
-        let result = svc.check(cs, &base, &stored_fps, &current_rows, &pk_map);
+        let result = svc
+            .check(cs, &base, &stored_fps, &current_rows, &pk_map)
+            .unwrap();
         assert!(!result.is_clean());
 
         let conflicts = result.conflicts();
         assert_eq!(conflicts.len(), 1);
-        assert_eq!(conflicts[0].column, "discount_rate");
+        assert_eq!(conflicts[0].kind, ConflictKind::UpdateUpdate);
+        assert_eq!(conflicts[0].column.as_deref(), Some("discount_rate"));
         assert_eq!(conflicts[0].base_value, json!(0.10));
         assert_eq!(conflicts[0].source_value, json!(0.20));
         assert_eq!(conflicts[0].target_value, json!(0.15));
     }
 
+    #[test]
+    fn classifies_row_level_conflicts() {
+        let svc = ConflictService::new();
+        let table = "rules";
+        let r = |id: i64, val: &str| row(&[("id", json!(id)), ("val", json!(val))]);
+        let change = |data: RowMap| RowChange {
+            pk: [("id".to_string(), data["id"].clone())].into(),
+            data,
+        };
+
+        let base_rows = vec![r(1, "a"), r(2, "b"), r(3, "c"), r(5, "e"), r(6, "f")];
+        // Target deleted 1, 3 and 6, updated 2, inserted 4.
+        let target_rows = vec![r(2, "target"), r(4, "target"), r(5, "e")];
+        // Source updated 1, deleted 2, 3 and 5, inserted 4, kept 6:
+        // its diff against target is below.
+        let cs = Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: table.to_string(),
+                primary_key: vec!["id".to_string()],
+                inserts: vec![change(r(1, "source")), change(r(6, "f"))],
+                updates: vec![RowUpdate {
+                    pk: [("id".to_string(), json!(4))].into(),
+                    before: r(4, "target"),
+                    after: r(4, "source"),
+                    changed_columns: vec![ColumnDiff {
+                        column: "val".to_string(),
+                        before: json!("target"),
+                        after: json!("source"),
+                        patch: None,
+                    }],
+                }],
+                deletes: vec![change(r(2, "target")), change(r(5, "e"))],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+
+        let base = MapSnapshot([(table.to_string(), base_rows)].into());
+        let current_rows = [(table.to_string(), target_rows)].into();
+        let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
+
+        let result = svc
+            .check(cs, &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
+        let kinds: Vec<(ConflictKind, Value)> = result
+            .conflicts()
+            .iter()
+            .map(|c| (c.kind, c.pk["id"].clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (ConflictKind::UpdateDelete, json!(1)),
+                (ConflictKind::InsertInsert, json!(4)),
+                (ConflictKind::DeleteUpdate, json!(2)),
+                (ConflictKind::DeleteDelete, json!(3)),
+            ]
+        );
+
+        // Row-level reports carry whole rows, null where the row is gone.
+        let update_delete = &result.conflicts()[0];
+        assert_eq!(update_delete.column, None);
+        assert_eq!(update_delete.base_value, json!({"id": 1, "val": "a"}));
+        assert_eq!(
+            update_delete.source_value,
+            json!({"id": 1, "val": "source"})
+        );
+        assert_eq!(update_delete.target_value, Value::Null);
+    }
+
+    #[test]
+    fn delete_delete_alone_is_clean() {
+        let table = "rules";
+        let base_rows = vec![row(&[("id", json!(1))]), row(&[("id", json!(2))])];
+        let target_rows = vec![row(&[("id", json!(2))])];
+        let cs = Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: table.to_string(),
+                primary_key: vec!["id".to_string()],
+                inserts: vec![],
+                updates: vec![],
+                deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+        let base = MapSnapshot([(table.to_string(), base_rows)].into());
+        let current_rows = [(table.to_string(), target_rows)].into();
+        let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
+
+        let result = ConflictService::new()
+            .check(cs, &base, &BTreeMap::new(), &current_rows, &pk_map)
+            .unwrap();
+        assert!(result.is_clean());
+    }
+
+    #[test]
+    fn policies_settle_conflicts() {
+        let table = "pricing_rules";
+        let r = |name: &str, rate: f64| {
+            row(&[
+                ("id", json!(1)),
+                ("name", json!(name)),
+                ("rate", json!(rate)),
+            ])
+        };
+        let (base_row, source_row, target_row) = (r("a", 0.10), r("s", 0.20), r("t", 0.15));
+        let changeset = || {
+            Changeset::new(
+                "source",
+                "target",
+                "postgres",
+                "postgres",
+                vec![TableDiff {
+                    table_name: table.to_string(),
+                    primary_key: vec!["id".to_string()],
+                    inserts: vec![],
+                    updates: vec![RowUpdate {
+                        pk: [("id".to_string(), json!(1))].into(),
+                        before: target_row.clone(),
+                        after: source_row.clone(),
+                        changed_columns: vec![],
+                    }],
+                    deletes: vec![],
+                    data_quality: vec![],
+                    column_types: ColumnTypes::new(),
+                    excluded_columns: Vec::new(),
+                }],
+            )
+        };
+        let base = MapSnapshot([(table.to_string(), vec![base_row.clone()])].into());
+        let current_rows = [(table.to_string(), vec![target_row.clone()])].into();
+        let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
+        let check = |policy: serde_json::Value| {
+            let policy: ResolutionPolicy = serde_json::from_value(policy).unwrap();
+            ConflictService::new()
+                .with_policies([(table.to_string(), policy)].into())
+                .check(changeset(), &base, &BTreeMap::new(), &current_rows, &pk_map)
+                .unwrap()
+        };
+
+        // Only the rate has a strategy: the name still needs a decision.
+        let partial = check(json!({ "columns": { "rate": "source" } }));
+        assert!(!partial.is_clean());
+        assert_eq!(partial.conflicts().len(), 1);
+        assert_eq!(partial.conflicts()[0].column.as_deref(), Some("name"));
+        assert_eq!(partial.auto_resolved().len(), 1);
+        assert_eq!(partial.auto_resolved()[0].choice, Choice::Source);
+
+        // Target wins by default, except for the rate.
+        let settled = check(json!({ "strategy": "target", "columns": { "rate": "source" } }));
+        assert!(settled.is_clean());
+        assert_eq!(settled.auto_resolved().len(), 2);
+        let update = &settled.changeset().tables[0].updates[0];
+        assert_eq!(update.after["name"], json!("t"));
+        assert_eq!(update.after["rate"], json!(0.20));
+        assert_eq!(update.changed_columns.len(), 1);
+
+        // `fail` exempts a column from the table's strategy.
+        let exempt = check(json!({ "strategy": "target", "columns": { "name": "fail" } }));
+        assert_eq!(exempt.conflicts().len(), 1);
+        assert_eq!(exempt.auto_resolved().len(), 1);
+    }
+
     #[test]
     fn no_conflict_when_different_rows_changed() {
         let svc = ConflictService::new();
@@ -356,6 +959,7 @@ mod tests {
             "s",
             "t",
             "postgres",
+            "postgres",
             vec![TableDiff {
                 table_name: table.to_string(),
                 primary_key: vec!["id".to_string()],
@@ -368,9 +972,13 @@ mod tests {
                         column: "val".to_string(),
                         before: json!("a"),
                         after: json!("source"),
+                        patch: None,
                     }],
                 }],
                 deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
             }],
         );
 
@@ -379,7 +987,70 @@ mod tests {
         let current_rows = [(table.to_string(), target_rows)].into();
         let pk_map = [(table.to_string(), vec![pk_col("id")])].into();
 
-        let result = svc.check(cs, &base, &stored_fps, &current_rows, &pk_map);
+        let result = svc
+            .check(cs, &base, &stored_fps, &current_rows, &pk_map)
+            .unwrap();
         assert!(result.is_clean(), "Different rows changed → no conflict");
     }
+
+    #[test]
+    fn keys_that_collide_as_strings_are_checked_separately() {
+        let svc = ConflictService::new();
+        let table = "t";
+        let pk = vec![pk_col("a"), pk_col("b")];
+
+        // Two distinct rows whose `|`-joined keys are both "x|y|z".
+        let base_rows = vec![
+            row(&[("a", json!("x|y")), ("b", json!("z")), ("v", json!(0))]),
+            row(&[("a", json!("x")), ("b", json!("y|z")), ("v", json!(0))]),
+        ];
+        // Target changed only the second row…
+        let target_rows = vec![
+            row(&[("a", json!("x|y")), ("b", json!("z")), ("v", json!(0))]),
+            row(&[("a", json!("x")), ("b", json!("y|z")), ("v", json!(2))]),
+        ];
+        // …source changed only the first one: no conflict.
+        let source_after = row(&[("a", json!("x|y")), ("b", json!("z")), ("v", json!(1))]);
+
+        let cs = Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: table.to_string(),
+                primary_key: vec!["a".to_string(), "b".to_string()],
+                inserts: vec![],
+                updates: vec![RowUpdate {
+                    pk: [
+                        ("a".to_string(), json!("x|y")),
+                        ("b".to_string(), json!("z")),
+                    ]
+                    .into(),
+                    before: base_rows[0].clone(),
+                    after: source_after,
+                    changed_columns: vec![ColumnDiff {
+                        column: "v".to_string(),
+                        before: json!(0),
+                        after: json!(1),
+                        patch: None,
+                    }],
+                }],
+                deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+
+        let base = MapSnapshot([(table.to_string(), base_rows.clone())].into());
+        let stored_fps = [(table.to_string(), fingerprint(&base_rows))].into();
+        let current_rows = [(table.to_string(), target_rows)].into();
+        let pk_map = [(table.to_string(), pk)].into();
+
+        let result = svc
+            .check(cs, &base, &stored_fps, &current_rows, &pk_map)
+            .unwrap();
+        assert!(result.is_clean(), "Distinct composite keys → no conflict");
+    }
 }
@@ -1,19 +1,29 @@
-use anyhow::Result;
+use anyhow::{bail, ensure, Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use crate::application::bisect::BisectDiffService;
+use crate::domain::compare_rules::{is_document, CompareRules, TableRules};
 use crate::domain::fingerprint::fingerprint;
+use crate::domain::json_patch;
+use crate::domain::row_key::RowKey;
 use crate::domain::table_diff::RowMap;
 use crate::domain::{
     changeset::Changeset,
-    ports::{Differ, RowRepository},
-    table_diff::{ColumnDiff, RowChange, RowUpdate, TableDiff},
+    ports::{Differ, RowRepository, RowStream, StreamDiffer},
+    table_diff::{
+        ColumnDiff, ColumnTypes, DataQualityFinding, DataQualityKind, RowChange, RowUpdate, Side,
+        TableDiff,
+    },
     value_objects::{ColumnName, Schema, TableName},
 };
-use crate::infrastructure::{config::TableConfig, db::sql_utils::pk_key};
+use crate::infrastructure::config::{DiffStrategy, TableConfig};
 
 // ─── Diff Service ───
 
@@ -21,6 +31,8 @@ pub struct DiffService {
     source_repo: Arc<dyn RowRepository>,
     target_repo: Arc<dyn RowRepository>,
     differ: Arc<dyn Differ>,
+    stream_differ: Arc<dyn StreamDiffer>,
+    bisect: Option<Arc<BisectDiffService>>,
 }
 
 impl DiffService {
@@ -33,14 +45,30 @@ impl DiffService {
             source_repo,
             target_repo,
             differ,
+            stream_differ: Arc::new(MergeJoinDiffer::new()),
+            bisect: None,
         }
     }
 
+    /// Replace the differ used for tables configured with
+    /// `strategy = "streaming"` (defaults to [`MergeJoinDiffer`]).
+    pub fn with_stream_differ(mut self, stream_differ: Arc<dyn StreamDiffer>) -> Self {
+        self.stream_differ = stream_differ;
+        self
+    }
+
+    /// Enable tables configured with `strategy = "bisect"`.
+    pub fn with_bisect(mut self, bisect: Arc<BisectDiffService>) -> Self {
+        self.bisect = Some(bisect);
+        self
+    }
+
     pub async fn run_diff(
         &self,
         source_schema: &Schema,
         target_schema: &Schema,
-        driver: &str,
+        source_driver: &str,
+        target_driver: &str,
         tables: &[TableConfig],
     ) -> Result<Changeset> {
         let mut handles = Vec::with_capacity(tables.len());
@@ -49,6 +77,8 @@ impl DiffService {
             let source_repo = Arc::clone(&self.source_repo);
             let target_repo = Arc::clone(&self.target_repo);
             let differ = Arc::clone(&self.differ);
+            let stream_differ = Arc::clone(&self.stream_differ);
+            let bisect = self.bisect.clone();
             let source_schema = source_schema.clone();
             let target_schema = target_schema.clone();
             let table_cfg = table_cfg.clone();
@@ -60,6 +90,49 @@ impl DiffService {
                     .iter()
                     .map(|pk| ColumnName(pk.clone()))
                     .collect();
+                ensure!(
+                    !pk_cols.is_empty() || table_cfg.strategy == DiffStrategy::InMemory,
+                    "Table {} has no primary key: only the in_memory strategy can diff it",
+                    table_cfg.name
+                );
+
+                if table_cfg.strategy == DiffStrategy::Bisect {
+                    let bisect = bisect.with_context(|| {
+                        format!(
+                            "Table {} uses the bisect strategy, which is not enabled",
+                            table_cfg.name
+                        )
+                    })?;
+                    let diff = bisect
+                        .diff_table(&source_schema, &target_schema, &table_cfg)
+                        .await?;
+                    return Ok((diff, Vec::new(), Vec::new()));
+                }
+
+                if table_cfg.strategy == DiffStrategy::Streaming {
+                    let (source_stream, target_stream) = tokio::try_join!(
+                        source_repo.stream_rows(
+                            &source_schema,
+                            &table_name,
+                            &pk_cols,
+                            &table_cfg.excluded_columns
+                        ),
+                        target_repo.stream_rows(
+                            &target_schema,
+                            &table_name,
+                            &pk_cols,
+                            &table_cfg.excluded_columns
+                        )
+                    )?;
+
+                    let diff = stream_differ
+                        .diff_streams(source_stream, target_stream, &pk_cols, &table_name)
+                        .await?;
+
+                    // Streamed rows are never materialised, so they are left
+                    // out of the cross-table fingerprints below.
+                    return Ok((diff, Vec::new(), Vec::new()));
+                }
 
                 let (source_rows, target_rows) = tokio::join!(
                     source_repo.fetch_rows(
@@ -92,8 +165,17 @@ impl DiffService {
         let mut all_source_rows: Vec<RowMap> = Vec::new();
         let mut all_target_rows: Vec<RowMap> = Vec::new();
 
-        for h in handles {
-            let (diff, src_rows, tgt_rows) = h.await??;
+        for (h, table_cfg) in handles.into_iter().zip(tables) {
+            let (mut diff, src_rows, tgt_rows) = h.await??;
+            if !diff.is_empty() {
+                // Only changed tables produce statements needing typed literals.
+                let table_name = TableName(diff.table_name.clone());
+                diff.column_types = self
+                    .target_repo
+                    .column_sql_types(target_schema, &table_name)
+                    .await?;
+                diff.excluded_columns = table_cfg.excluded_columns.0.clone();
+            }
             all_source_rows.extend(src_rows);
             all_target_rows.extend(tgt_rows);
             table_diffs.push(diff);
@@ -104,7 +186,25 @@ impl DiffService {
         let source_fp = fingerprint(&all_source_rows);
         let target_fp = fingerprint(&all_target_rows);
 
-        let mut changeset = Changeset::new(&source_schema.0, &target_schema.0, driver, table_diffs);
+        // Statements are ordered along the keys between diffed tables.
+        let mut foreign_keys = Vec::new();
+        if table_diffs.iter().any(|t| !t.is_empty()) {
+            foreign_keys = self.target_repo.foreign_keys(target_schema).await?;
+            foreign_keys.retain(|fk| {
+                [&fk.table, &fk.referenced_table]
+                    .iter()
+                    .all(|name| table_diffs.iter().any(|t| &t.table_name == *name))
+            });
+        }
+
+        let mut changeset = Changeset::new(
+            &source_schema.0,
+            &target_schema.0,
+            source_driver,
+            target_driver,
+            table_diffs,
+        )
+        .with_foreign_keys(foreign_keys);
         changeset.source_fingerprint = source_fp.0;
         changeset.target_fingerprint = target_fp.0;
 
@@ -115,11 +215,20 @@ impl DiffService {
 // ─── Table Differ (implementation of the port) ───
 
 #[derive(Default)]
-pub struct TableDiffer;
+pub struct TableDiffer {
+    rules: TableRules,
+}
 
 impl TableDiffer {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Compare column values with the configured rules instead of the
+    /// built-in equality.
+    pub fn with_rules(mut self, rules: TableRules) -> Self {
+        self.rules = rules;
+        self
     }
 }
 
@@ -131,19 +240,39 @@ impl Differ for TableDiffer {
         pk_cols: &[ColumnName],
         table_name: &TableName,
     ) -> TableDiff {
-        let source_index: BTreeMap<String, &RowMap> =
-            source.iter().map(|r| (pk_key(r, pk_cols), r)).collect();
-        let target_index: BTreeMap<String, &RowMap> =
-            target.iter().map(|r| (pk_key(r, pk_cols), r)).collect();
+        if pk_cols.is_empty() {
+            return diff_keyless(source, target, table_name);
+        }
+        let rules = self.rules.for_table(&table_name.0);
+
+        let mut data_quality = Vec::new();
+        let mut duplicated = BTreeSet::new();
+        let mut source_index = index_rows(
+            source,
+            pk_cols,
+            Side::Source,
+            &mut data_quality,
+            &mut duplicated,
+        );
+        let mut target_index = index_rows(
+            target,
+            pk_cols,
+            Side::Target,
+            &mut data_quality,
+            &mut duplicated,
+        );
+        // A key duplicated on one side cannot be paired with the other side's row.
+        source_index.retain(|k, _| !duplicated.contains(k));
+        target_index.retain(|k, _| !duplicated.contains(k));
 
-        let source_keys: BTreeSet<&String> = source_index.keys().collect();
-        let target_keys: BTreeSet<&String> = target_index.keys().collect();
+        let source_keys: BTreeSet<&RowKey> = source_index.keys().collect();
+        let target_keys: BTreeSet<&RowKey> = target_index.keys().collect();
 
-        let insert_keys: Vec<&&String> = source_keys.difference(&target_keys).collect();
+        let insert_keys: Vec<&&RowKey> = source_keys.difference(&target_keys).collect();
         let inserts: Vec<RowChange> = insert_keys
             .iter()
             .map(|k| {
-                let row = source_index[k.as_str()];
+                let row = source_index[**k];
                 RowChange {
                     pk: extract_pk_from_row(row, pk_cols),
                     data: (*row).clone(),
@@ -151,11 +280,11 @@ impl Differ for TableDiffer {
             })
             .collect();
 
-        let delete_keys: Vec<&&String> = target_keys.difference(&source_keys).collect();
+        let delete_keys: Vec<&&RowKey> = target_keys.difference(&source_keys).collect();
         let deletes: Vec<RowChange> = delete_keys
             .iter()
             .map(|k| {
-                let row = target_index[k.as_str()];
+                let row = target_index[**k];
                 RowChange {
                     pk: extract_pk_from_row(row, pk_cols),
                     data: (*row).clone(),
@@ -163,14 +292,14 @@ impl Differ for TableDiffer {
             })
             .collect();
 
-        let common_keys: Vec<&&String> = source_keys.intersection(&target_keys).collect();
+        let common_keys: Vec<&&RowKey> = source_keys.intersection(&target_keys).collect();
         let mut updates = Vec::new();
 
         for key in common_keys {
-            let source_row = source_index[key.as_str()];
-            let target_row = target_index[key.as_str()];
+            let source_row = source_index[*key];
+            let target_row = target_index[*key];
 
-            let changed_columns = diff_columns(source_row, target_row);
+            let changed_columns = diff_columns(source_row, target_row, rules);
             if !changed_columns.is_empty() {
                 updates.push(RowUpdate {
                     pk: extract_pk_from_row(source_row, pk_cols),
@@ -187,13 +316,344 @@ impl Differ for TableDiffer {
             inserts,
             updates,
             deletes,
+            data_quality,
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        }
+    }
+}
+
+/// Compare a table without a key as two multisets of full rows: a row present
+/// `n` more times in source than in target gives `n` inserts, and the other
+/// way round `n` deletes. There are no updates — a changed row is a delete
+/// plus an insert.
+fn diff_keyless(source: &[RowMap], target: &[RowMap], table_name: &TableName) -> TableDiff {
+    let mut counts: BTreeMap<RowKey, (Vec<&RowMap>, Vec<&RowMap>)> = BTreeMap::new();
+    for row in source {
+        counts.entry(whole_row_key(row)).or_default().0.push(row);
+    }
+    for row in target {
+        counts.entry(whole_row_key(row)).or_default().1.push(row);
+    }
+
+    let change = |row: &&RowMap| RowChange {
+        pk: BTreeMap::new(),
+        data: (*row).clone(),
+    };
+    let (mut inserts, mut deletes) = (Vec::new(), Vec::new());
+    for (source_rows, target_rows) in counts.values() {
+        inserts.extend(source_rows.iter().skip(target_rows.len()).map(change));
+        deletes.extend(target_rows.iter().skip(source_rows.len()).map(change));
+    }
+
+    TableDiff {
+        table_name: table_name.0.clone(),
+        primary_key: Vec::new(),
+        inserts,
+        updates: Vec::new(),
+        deletes,
+        data_quality: Vec::new(),
+        column_types: ColumnTypes::new(),
+        excluded_columns: Vec::new(),
+    }
+}
+
+/// Every `(column, value)` pair of `row`, for multiset comparison: values
+/// must match exactly, comparison rules do not apply. Booleans count as
+/// 0 / 1, like the built-in equality treats SQLite flags.
+pub(crate) fn whole_row_key(row: &RowMap) -> RowKey {
+    row.iter()
+        .flat_map(|(col, val)| {
+            let val = match val {
+                Value::Bool(b) => Value::from(u8::from(*b)),
+                other => other.clone(),
+            };
+            [Value::String(col.clone()), val]
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Index rows by primary key. Rows whose key has a NULL component, and rows
+/// sharing a key, cannot be matched reliably: they are reported in
+/// `findings` and left out of the index; shared keys are added to `duplicated`.
+fn index_rows<'a>(
+    rows: &'a [RowMap],
+    pk_cols: &[ColumnName],
+    side: Side,
+    findings: &mut Vec<DataQualityFinding>,
+    duplicated: &mut BTreeSet<RowKey>,
+) -> BTreeMap<RowKey, &'a RowMap> {
+    let mut groups: BTreeMap<RowKey, Vec<&RowMap>> = BTreeMap::new();
+    for row in rows {
+        groups
+            .entry(RowKey::from_row(row, pk_cols))
+            .or_default()
+            .push(row);
+    }
+
+    let mut index = BTreeMap::new();
+    for (key, group) in groups {
+        let kind = if key.has_null() {
+            DataQualityKind::NullKey
+        } else if group.len() > 1 {
+            duplicated.insert(key.clone());
+            DataQualityKind::DuplicateKey
+        } else {
+            index.insert(key, group[0]);
+            continue;
+        };
+        let rows = group.into_iter().cloned().collect();
+        findings.push(DataQualityFinding::new(kind, side, pk_cols, &key, rows));
+    }
+    index
+}
+
+// ─── Merge-join Differ (implementation of the streaming port) ───
+
+/// Streaming differ: walks two PK-ordered row streams in lockstep.
+///
+/// Only the current row of each side is held in memory; unchanged rows are
+/// dropped as soon as they are compared, so memory grows with the number of
+/// changes rather than with the table size.
+#[derive(Default)]
+pub struct MergeJoinDiffer {
+    rules: TableRules,
+}
+
+impl MergeJoinDiffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare column values with the configured rules instead of the
+    /// built-in equality.
+    pub fn with_rules(mut self, rules: TableRules) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+#[async_trait]
+impl StreamDiffer for MergeJoinDiffer {
+    async fn diff_streams(
+        &self,
+        source: RowStream,
+        target: RowStream,
+        pk_cols: &[ColumnName],
+        table_name: &TableName,
+    ) -> Result<TableDiff> {
+        let rules = self.rules.for_table(&table_name.0);
+        let mut source = OrderedRows::new(source, Side::Source, pk_cols, table_name);
+        let mut target = OrderedRows::new(target, Side::Target, pk_cols, table_name);
+
+        let mut diff = TableDiff {
+            table_name: table_name.0.clone(),
+            primary_key: pk_cols.iter().map(|c| c.0.clone()).collect(),
+            inserts: Vec::new(),
+            updates: Vec::new(),
+            deletes: Vec::new(),
+            data_quality: Vec::new(),
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+
+        let mut source_row = source.next().await?;
+        let mut target_row = target.next().await?;
+
+        loop {
+            let ordering = match (&source_row, &target_row) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((sk, _)), Some((tk, _))) => sk.cmp(tk),
+            };
+
+            match ordering {
+                // Key only in source → insert (unless target has it twice)
+                Ordering::Less => {
+                    let (key, row) = source_row.take().expect("source row present");
+                    if !target.skipped_duplicate(&key) {
+                        diff.inserts.push(RowChange {
+                            pk: extract_pk_from_row(&row, pk_cols),
+                            data: row,
+                        });
+                    }
+                    source_row = source.next().await?;
+                }
+                // Key only in target → delete (unless source has it twice)
+                Ordering::Greater => {
+                    let (key, row) = target_row.take().expect("target row present");
+                    if !source.skipped_duplicate(&key) {
+                        diff.deletes.push(RowChange {
+                            pk: extract_pk_from_row(&row, pk_cols),
+                            data: row,
+                        });
+                    }
+                    target_row = target.next().await?;
+                }
+                Ordering::Equal => {
+                    let (_, after) = source_row.take().expect("source row present");
+                    let (_, before) = target_row.take().expect("target row present");
+                    let changed_columns = diff_columns(&after, &before, rules);
+                    if !changed_columns.is_empty() {
+                        diff.updates.push(RowUpdate {
+                            pk: extract_pk_from_row(&after, pk_cols),
+                            before,
+                            after,
+                            changed_columns,
+                        });
+                    }
+                    source_row = source.next().await?;
+                    target_row = target.next().await?;
+                }
+            }
+        }
+
+        diff.data_quality = source.into_findings();
+        diff.data_quality.extend(target.into_findings());
+        Ok(diff)
+    }
+}
+
+/// One side of the merge join: pulls rows and checks they arrive in
+/// increasing key order. A violation would silently turn matching rows into
+/// insert/delete pairs, so it is reported as an error instead.
+///
+/// Like `TableDiffer`, rows that the key does not identify are set aside as
+/// data-quality findings: consecutive rows sharing a key, and rows with a
+/// NULL key column (wherever the database sorts NULLs).
+struct OrderedRows<'a> {
+    rows: RowStream,
+    side: Side,
+    pk_cols: &'a [ColumnName],
+    table_name: &'a TableName,
+    last_key: Option<RowKey>,
+    /// Row read ahead while looking for duplicates of the previous key.
+    pending: Option<(RowKey, RowMap)>,
+    /// The stream returned its last row; it must not be polled again.
+    exhausted: bool,
+    /// Duplicate keys skipped and not yet looked up by the other side.
+    duplicates: VecDeque<RowKey>,
+    findings: Vec<DataQualityFinding>,
+    null_keys: BTreeMap<RowKey, Vec<RowMap>>,
+}
+
+impl<'a> OrderedRows<'a> {
+    fn new(
+        rows: RowStream,
+        side: Side,
+        pk_cols: &'a [ColumnName],
+        table_name: &'a TableName,
+    ) -> Self {
+        Self {
+            rows,
+            side,
+            pk_cols,
+            table_name,
+            last_key: None,
+            pending: None,
+            exhausted: false,
+            duplicates: VecDeque::new(),
+            findings: Vec::new(),
+            null_keys: BTreeMap::new(),
+        }
+    }
+
+    /// The next row whose key is unique and non-NULL.
+    async fn next(&mut self) -> Result<Option<(RowKey, RowMap)>> {
+        loop {
+            let Some((key, row)) = self.pull().await? else {
+                return Ok(None);
+            };
+            let mut duplicates = Vec::new();
+            loop {
+                match self.pull().await? {
+                    Some((k, r)) if k == key => duplicates.push(r),
+                    other => {
+                        self.pending = other;
+                        break;
+                    }
+                }
+            }
+            if duplicates.is_empty() {
+                return Ok(Some((key, row)));
+            }
+            duplicates.insert(0, row);
+            self.duplicates.push_back(key.clone());
+            self.findings.push(DataQualityFinding::new(
+                DataQualityKind::DuplicateKey,
+                self.side,
+                self.pk_cols,
+                &key,
+                duplicates,
+            ));
+        }
+    }
+
+    /// `true` when `key` was skipped as a duplicate. The other side asks in
+    /// increasing key order, which lets older keys be forgotten.
+    fn skipped_duplicate(&mut self, key: &RowKey) -> bool {
+        while self.duplicates.front().is_some_and(|k| k < key) {
+            self.duplicates.pop_front();
         }
+        self.duplicates.front() == Some(key)
+    }
+
+    /// The next row with a non-NULL key, checking the order.
+    async fn pull(&mut self) -> Result<Option<(RowKey, RowMap)>> {
+        if let Some(pending) = self.pending.take() {
+            return Ok(Some(pending));
+        }
+        loop {
+            if self.exhausted {
+                return Ok(None);
+            }
+            let Some(row) = self.rows.next().await.transpose()? else {
+                self.exhausted = true;
+                return Ok(None);
+            };
+            let key = RowKey::from_row(&row, self.pk_cols);
+            if key.has_null() {
+                self.null_keys.entry(key).or_default().push(row);
+                continue;
+            }
+
+            if let Some(last) = &self.last_key {
+                if last.cmp(&key) == Ordering::Greater {
+                    bail!(
+                        "{} rows of {} are not sorted by primary key ({} came after {})",
+                        self.side,
+                        self.table_name.0,
+                        Value::from(key.values()),
+                        Value::from(last.values())
+                    );
+                }
+            }
+            self.last_key = Some(key.clone());
+            return Ok(Some((key, row)));
+        }
+    }
+
+    /// Duplicate keys met so far, then the NULL keys.
+    fn into_findings(self) -> Vec<DataQualityFinding> {
+        let (side, pk_cols) = (self.side, self.pk_cols);
+        let mut findings = self.findings;
+        findings.extend(self.null_keys.into_iter().map(|(key, rows)| {
+            DataQualityFinding::new(DataQualityKind::NullKey, side, pk_cols, &key, rows)
+        }));
+        findings
     }
 }
 
 // ─── Optimized diff logic ───
 
-fn diff_columns(source: &RowMap, target: &RowMap) -> Vec<ColumnDiff> {
+/// Columns whose values differ between the two rows under `rules`.
+pub(crate) fn diff_columns(
+    source: &RowMap,
+    target: &RowMap,
+    rules: &CompareRules,
+) -> Vec<ColumnDiff> {
     let mut diffs = Vec::new();
     let all_keys: BTreeSet<_> = source.keys().chain(target.keys()).collect();
 
@@ -206,11 +666,19 @@ fn diff_columns(source: &RowMap, target: &RowMap) -> Vec<ColumnDiff> {
             continue;
         }
 
-        if !json_equal(source_val, target_val) {
+        let rule = rules.for_column(col);
+        if !rule.values_equal(source_val, target_val) {
+            let patch = (is_document(target_val) && is_document(source_val)).then(|| {
+                json_patch::diff(
+                    &rule.without_ignored(target_val),
+                    &rule.without_ignored(source_val),
+                )
+            });
             diffs.push(ColumnDiff {
                 column: col.clone(),
                 before: target_val.clone(),
                 after: source_val.clone(),
+                patch,
             });
         }
     }
@@ -218,38 +686,40 @@ fn diff_columns(source: &RowMap, target: &RowMap) -> Vec<ColumnDiff> {
     diffs
 }
 
-fn json_equal(a: &Value, b: &Value) -> bool {
-    match (a, b) {
-        (Value::Number(na), Value::Number(nb)) => match (na.as_f64(), nb.as_f64()) {
-            (Some(fa), Some(fb)) => float_eq(fa, fb),
-            _ => na == nb,
-        },
-        _ => normalize_json(a) == normalize_json(b),
-    }
-}
-
-fn float_eq(a: f64, b: f64) -> bool {
-    (a - b).abs() < 1e-9
-}
-
 fn json_hash(v: &Value) -> u64 {
     let mut hasher = DefaultHasher::new();
     hash_json(v, &mut hasher);
     hasher.finish()
 }
 
+/// Feed `v` to `state`. A variant tag precedes every value and a length
+/// every array or object, so equal hashes come from equal values only:
+/// `1` and `"1"`, or `[[1, 2], [3]]` and `[[1], [2, 3]]`, hash apart.
 fn hash_json(v: &Value, state: &mut impl Hasher) {
     match v {
         Value::Null => 0u8.hash(state),
-        Value::Bool(b) => b.hash(state),
-        Value::Number(n) => n.to_string().hash(state),
-        Value::String(s) => s.hash(state),
+        Value::Bool(b) => {
+            1u8.hash(state);
+            b.hash(state);
+        }
+        Value::Number(n) => {
+            2u8.hash(state);
+            n.to_string().hash(state);
+        }
+        Value::String(s) => {
+            3u8.hash(state);
+            s.hash(state);
+        }
         Value::Array(arr) => {
+            4u8.hash(state);
+            arr.len().hash(state);
             for el in arr {
                 hash_json(el, state);
             }
         }
         Value::Object(map) => {
+            5u8.hash(state);
+            map.len().hash(state);
             let mut entries: Vec<_> = map.iter().collect();
             entries.sort_by_key(|(k, _)| *k);
             for (k, v) in entries {
@@ -260,23 +730,6 @@ fn hash_json(v: &Value, state: &mut impl Hasher) {
     }
 }
 
-fn normalize_json(v: &Value) -> Value {
-    match v {
-        Value::Object(map) => {
-            let mut entries: Vec<_> = map.iter().collect();
-            entries.sort_by_key(|(k, _)| *k);
-            Value::Object(
-                entries
-                    .into_iter()
-                    .map(|(k, v)| (k.clone(), normalize_json(v)))
-                    .collect(),
-            )
-        }
-        Value::Array(arr) => Value::Array(arr.iter().map(normalize_json).collect()),
-        _ => v.clone(),
-    }
-}
-
 fn extract_pk_from_row(row: &RowMap, pk_cols: &[ColumnName]) -> BTreeMap<String, Value> {
     pk_cols
         .iter()
@@ -287,8 +740,13 @@ fn extract_pk_from_row(row: &RowMap, pk_cols: &[ColumnName]) -> BTreeMap<String,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::compare_rules::CompareRule;
     use serde_json::json;
 
+    fn json_equal(a: &Value, b: &Value) -> bool {
+        CompareRule::default().values_equal(a, b)
+    }
+
     fn row(pairs: &[(&str, Value)]) -> RowMap {
         pairs
             .iter()
@@ -315,10 +773,23 @@ mod tests {
     }
 
     #[test]
-    fn test_json_equal_float_within_epsilon() {
-        let a = serde_json::Number::from_f64(0.1 + 0.2).unwrap();
-        let b = serde_json::Number::from_f64(0.3).unwrap();
-        assert!(json_equal(&Value::Number(a), &Value::Number(b)));
+    fn test_json_equal_numbers_are_exact() {
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        assert!(!json_equal(&json!(0.1 + 0.2), &json!(0.3)));
+        assert!(!json_equal(
+            &n("1234567890123456.000000000001"),
+            &n("1234567890123456.000000000002")
+        ));
+        assert!(json_equal(&n("1.50"), &n("1.50")));
+        assert!(json_equal(&n("1.5"), &json!(1.5)));
+    }
+
+    #[test]
+    fn test_json_equal_bool_matches_zero_one() {
+        assert!(json_equal(&json!(true), &json!(1)));
+        assert!(json_equal(&json!(0), &json!(false)));
+        assert!(!json_equal(&json!(true), &json!(0)));
+        assert!(!json_equal(&json!(true), &json!(2)));
     }
 
     #[test]
@@ -381,7 +852,7 @@ mod tests {
             ("id", Value::Number(1.into())),
             ("val", Value::String("same".into())),
         ]);
-        assert!(diff_columns(&r, &r).is_empty());
+        assert!(diff_columns(&r, &r, &CompareRules::default()).is_empty());
     }
 
     #[test]
@@ -394,7 +865,7 @@ mod tests {
             ("id", Value::Number(1.into())),
             ("val", Value::String("old".into())),
         ]);
-        let diffs = diff_columns(&source, &target);
+        let diffs = diff_columns(&source, &target, &CompareRules::default());
         assert_eq!(diffs.len(), 1);
         assert_eq!(diffs[0].column, "val");
         assert_eq!(diffs[0].before, Value::String("old".into()));
@@ -405,20 +876,75 @@ mod tests {
     fn test_diff_columns_ignores_object_key_order() {
         let a = row(&[("meta", json!({"a":1,"b":2}))]);
         let b = row(&[("meta", json!({"b":2,"a":1}))]);
-        assert!(diff_columns(&a, &b).is_empty());
+        assert!(diff_columns(&a, &b, &CompareRules::default()).is_empty());
     }
 
     #[test]
-    fn test_diff_columns_float_tolerance() {
-        let a = row(&[(
-            "val",
-            Value::Number(serde_json::Number::from_f64(1.0000000001).unwrap()),
+    fn test_diff_columns_hash_keeps_structure_and_types() {
+        // Each pair flattens to the same sequence of scalars.
+        let pairs = [
+            (json!([[1, 2], [3]]), json!([[1], [2, 3]])),
+            (json!({"a": ["b", 1]}), json!({"a": [], "b": 1})),
+            (json!(1), json!("1")),
+        ];
+        for (a, b) in pairs {
+            assert_ne!(json_hash(&a), json_hash(&b), "{a} / {b}");
+            let diffs = diff_columns(
+                &row(&[("v", a.clone())]),
+                &row(&[("v", b.clone())]),
+                &CompareRules::default(),
+            );
+            assert_eq!(diffs.len(), 1, "{a} / {b}");
+        }
+    }
+
+    #[test]
+    fn test_diff_columns_json_patch_skips_ignored_paths() {
+        let rules: CompareRules = serde_json::from_value(json!({
+            "columns": {"settings": {"ignore_paths": ["$.meta.last_sync"]}}
+        }))
+        .unwrap();
+        let target = row(&[(
+            "settings",
+            json!({"theme": "dark", "meta": {"last_sync": "2024-01-01"}}),
+        )]);
+        let synced = row(&[(
+            "settings",
+            json!({"theme": "dark", "meta": {"last_sync": "2024-06-30"}}),
         )]);
-        let b = row(&[(
-            "val",
-            Value::Number(serde_json::Number::from_f64(1.0).unwrap()),
+        assert!(diff_columns(&synced, &target, &rules).is_empty());
+
+        let source = row(&[(
+            "settings",
+            json!({"theme": "light", "meta": {"last_sync": "2024-06-30"}}),
         )]);
-        assert!(diff_columns(&a, &b).is_empty());
+        let diffs = diff_columns(&source, &target, &rules);
+        assert_eq!(diffs[0].after, source["settings"]);
+        assert_eq!(
+            serde_json::to_value(&diffs[0].patch).unwrap(),
+            json!([{"op": "replace", "path": "/theme", "value": "light"}])
+        );
+        // Scalars and binary values get no patch.
+        let scalar = diff_columns(&row(&[("n", json!(2))]), &row(&[("n", json!(1))]), &rules);
+        assert!(scalar[0].patch.is_none());
+        let bytes = diff_columns(
+            &row(&[("b", json!({"$bytes": "01"}))]),
+            &row(&[("b", json!({"$bytes": "02"}))]),
+            &rules,
+        );
+        assert!(bytes[0].patch.is_none());
+    }
+
+    #[test]
+    fn test_diff_columns_decimal_scale() {
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        let a = row(&[("val", n("1.50"))]);
+        let b = row(&[("val", n("1.5"))]);
+        let diffs = diff_columns(&a, &b, &CompareRules::default());
+        assert_eq!(diffs[0].after.to_string(), "1.50");
+
+        let rules: CompareRules = serde_json::from_value(json!({"ignore_scale": true})).unwrap();
+        assert!(diff_columns(&a, &b, &rules).is_empty());
     }
 
     // ── TableDiffer ──
@@ -475,10 +1001,323 @@ mod tests {
         assert!(diff.updates.is_empty());
     }
 
+    #[test]
+    fn table_differ_keeps_rows_with_adversarial_keys_apart() {
+        let pk = vec![col("a"), col("b")];
+        let table = table("t");
+
+        // Each pair would have collided as a `|`-joined string key.
+        let source = vec![
+            row(&[("a", json!("x|y")), ("b", json!("z")), ("v", json!(1))]),
+            row(&[("a", json!("1")), ("b", json!("NULL")), ("v", json!(2))]),
+            row(&[("a", Value::Null), ("b", json!("")), ("v", json!(3))]),
+        ];
+        let target = vec![
+            row(&[("a", json!("x")), ("b", json!("y|z")), ("v", json!(1))]),
+            row(&[("a", json!(1)), ("b", Value::Null), ("v", json!(2))]),
+            row(&[("a", json!("null")), ("b", json!("")), ("v", json!(3))]),
+        ];
+
+        let diff = TableDiffer::new().diff_table(&source, &target, &pk, &table);
+
+        // The actual NULLs are data-quality findings, not changes.
+        assert_eq!(diff.inserts.len(), 2);
+        assert_eq!(diff.deletes.len(), 2);
+        assert!(diff.updates.is_empty());
+        assert_eq!(diff.data_quality.len(), 2);
+        assert!(diff
+            .data_quality
+            .iter()
+            .all(|f| f.kind == DataQualityKind::NullKey));
+    }
+
+    #[test]
+    fn table_differ_applies_compare_rules_per_table() {
+        let pk = vec![col("id")];
+        let source = vec![row(&[
+            ("id", json!(1)),
+            ("email", json!("Ann@Example.com ")),
+            ("rate", json!(0.1049)),
+        ])];
+        let target = vec![row(&[
+            ("id", json!(1)),
+            ("email", json!("ann@example.com")),
+            ("rate", json!(0.1)),
+        ])];
+        let rules: CompareRules = serde_json::from_value(json!({
+            "trim_whitespace": true,
+            "columns": {
+                "email": { "ignore_case": true },
+                "rate": { "round_decimals": 1 }
+            }
+        }))
+        .unwrap();
+        let differ = TableDiffer::new().with_rules(TableRules::new(
+            &CompareRules::default(),
+            [("users", &rules)],
+        ));
+
+        let users = differ.diff_table(&source, &target, &pk, &table("users"));
+        assert!(users.updates.is_empty());
+
+        // Same rows in a table without rules: both columns changed.
+        let other = differ.diff_table(&source, &target, &pk, &table("accounts"));
+        assert_eq!(other.updates[0].changed_columns.len(), 2);
+    }
+
+    #[test]
+    fn keyless_table_is_compared_as_a_multiset() {
+        let table = table("audit_log");
+        let login = row(&[("msg", json!("login")), ("user", json!(1))]);
+        let logout = row(&[("msg", json!("logout")), ("user", json!(1))]);
+        let flag = row(&[("msg", json!("flag")), ("on", json!(true))]);
+
+        let source = vec![login.clone(), login.clone(), login.clone(), flag.clone()];
+        let target = vec![
+            logout.clone(),
+            login.clone(),
+            logout.clone(),
+            row(&[("msg", json!("flag")), ("on", json!(1))]),
+        ];
+
+        let diff = TableDiffer::new().diff_table(&source, &target, &[], &table);
+
+        // Two extra logins in source, both logouts only in target; the flag
+        // row matches across bool / integer renderings.
+        assert!(diff.primary_key.is_empty());
+        assert!(diff.updates.is_empty());
+        assert!(diff.data_quality.is_empty());
+        assert_eq!(diff.inserts.len(), 2);
+        assert!(diff
+            .inserts
+            .iter()
+            .all(|r| r.data == login && r.pk.is_empty()));
+        assert_eq!(diff.deletes.len(), 2);
+        assert!(diff.deletes.iter().all(|r| r.data == logout));
+    }
+
+    #[test]
+    fn table_differ_reports_duplicate_keys_instead_of_picking_a_row() {
+        let pk = vec![col("id")];
+        let table = table("legacy");
+
+        let source = vec![
+            row(&[("id", json!(1)), ("v", json!("a"))]),
+            row(&[("id", json!(2)), ("v", json!("a"))]),
+            row(&[("id", json!(2)), ("v", json!("b"))]),
+        ];
+        let target = vec![
+            row(&[("id", json!(1)), ("v", json!("changed"))]),
+            row(&[("id", json!(2)), ("v", json!("a"))]),
+        ];
+
+        let diff = TableDiffer::new().diff_table(&source, &target, &pk, &table);
+
+        // Id 2 is neither an update nor a delete: it cannot be paired.
+        assert_eq!(diff.updates.len(), 1);
+        assert!(diff.inserts.is_empty() && diff.deletes.is_empty());
+        let finding = &diff.data_quality[0];
+        assert_eq!(diff.data_quality.len(), 1);
+        assert_eq!(finding.kind, DataQualityKind::DuplicateKey);
+        assert_eq!(finding.side, Side::Source);
+        assert_eq!(finding.pk["id"], json!(2));
+        assert_eq!(finding.rows.len(), 2);
+    }
+
+    #[test]
+    fn table_differ_matches_mixed_type_composite_keys() {
+        let pk = vec![col("id"), col("kind")];
+        let table = table("t");
+
+        let source = vec![
+            row(&[("id", json!(1)), ("kind", json!("1")), ("v", json!("new"))]),
+            row(&[("id", json!("1")), ("kind", json!(1)), ("v", json!("same"))]),
+        ];
+        let target = vec![
+            row(&[("id", json!("1")), ("kind", json!(1)), ("v", json!("same"))]),
+            row(&[("id", json!(1)), ("kind", json!("1")), ("v", json!("old"))]),
+        ];
+
+        let diff = TableDiffer::new().diff_table(&source, &target, &pk, &table);
+
+        assert!(diff.inserts.is_empty());
+        assert!(diff.deletes.is_empty());
+        assert_eq!(diff.updates.len(), 1);
+        assert_eq!(diff.updates[0].pk["id"], json!(1));
+        assert_eq!(diff.updates[0].after["v"], json!("new"));
+    }
+
     #[test]
     fn test_diff_columns_nested_json() {
         let a = row(&[("json", json!({"a": 1, "b": [1,2,3], "c": {"x": 10}}))]);
         let b = row(&[("json", json!({"b": [1,2,3], "a": 1, "c": {"x": 10}}))]);
-        assert!(diff_columns(&a, &b).is_empty());
+        assert!(diff_columns(&a, &b, &CompareRules::default()).is_empty());
+    }
+
+    // ── MergeJoinDiffer ──
+
+    fn stream_of(rows: Vec<RowMap>) -> RowStream {
+        Box::pin(futures::stream::iter(rows.into_iter().map(Ok)))
+    }
+
+    #[tokio::test]
+    async fn merge_join_matches_table_differ() {
+        let pk = vec![col("id")];
+        let table = table("users");
+
+        let source = vec![
+            row(&[("id", json!(1)), ("name", json!("Alice"))]),
+            row(&[("id", json!(2)), ("name", json!("Bob"))]),
+            row(&[("id", json!(4)), ("name", json!("Dan"))]),
+        ];
+        let target = vec![
+            row(&[("id", json!(2)), ("name", json!("Bobby"))]),
+            row(&[("id", json!(3)), ("name", json!("Charlie"))]),
+            row(&[("id", json!(4)), ("name", json!("Dan"))]),
+        ];
+
+        let expected = TableDiffer::new().diff_table(&source, &target, &pk, &table);
+        let diff = MergeJoinDiffer::new()
+            .diff_streams(stream_of(source), stream_of(target), &pk, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(diff.inserts[0].pk["id"], json!(1));
+        assert_eq!(diff.updates[0].pk["id"], json!(2));
+        assert_eq!(diff.deletes[0].pk["id"], json!(3));
+    }
+
+    #[tokio::test]
+    async fn merge_join_orders_numbers_numerically_and_composite_keys() {
+        let pk = vec![col("region"), col("seq")];
+        let table = table("ledger");
+
+        // 9 < 10 numerically; "FR" < "de" bytewise (uppercase first)
+        let source = vec![
+            row(&[("region", json!("FR")), ("seq", json!(9))]),
+            row(&[("region", json!("FR")), ("seq", json!(10))]),
+            row(&[("region", json!("de")), ("seq", json!(1))]),
+        ];
+        let target = vec![
+            row(&[("region", json!("FR")), ("seq", json!(10))]),
+            row(&[("region", json!("de")), ("seq", json!(1))]),
+            row(&[("region", json!("de")), ("seq", json!(2))]),
+        ];
+
+        let diff = MergeJoinDiffer::new()
+            .diff_streams(stream_of(source), stream_of(target), &pk, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(diff.inserts.len(), 1);
+        assert_eq!(diff.inserts[0].pk["seq"], json!(9));
+        assert_eq!(diff.deletes.len(), 1);
+        assert_eq!(diff.deletes[0].pk["seq"], json!(2));
+        assert!(diff.updates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_join_accepts_timestamp_keys_in_chronological_order() {
+        let pk = vec![col("at")];
+        let table = table("events");
+        // Canonical `timestamptz` keys, as PostgreSQL sorts them natively.
+        let at = |s: &str, v: &str| row(&[("at", json!(s)), ("v", json!(v))]);
+        let source = vec![
+            at("2024-01-01T10:00:00+00:00", "a"),
+            at("2024-01-01T10:00:00.500+00:00", "a"),
+            at("2024-01-01T10:00:00.500001+00:00", "new"),
+            at("2024-01-01T10:00:01+00:00", "a"),
+        ];
+        let target = vec![
+            at("2024-01-01T10:00:00+00:00", "a"),
+            at("2024-01-01T10:00:00.500+00:00", "b"),
+            at("2024-01-01T10:00:01+00:00", "a"),
+        ];
+
+        let diff = MergeJoinDiffer::new()
+            .diff_streams(stream_of(source), stream_of(target), &pk, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(diff.inserts.len(), 1);
+        assert_eq!(diff.updates.len(), 1);
+        assert_eq!(
+            diff.updates[0].pk["at"],
+            json!("2024-01-01T10:00:00.500+00:00")
+        );
+        assert!(diff.deletes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_join_handles_empty_sides() {
+        let pk = vec![col("id")];
+        let table = table("events");
+        let rows = vec![row(&[("id", json!(1))]), row(&[("id", json!(2))])];
+
+        let diff = MergeJoinDiffer::new()
+            .diff_streams(stream_of(rows), stream_of(vec![]), &pk, &table)
+            .await
+            .unwrap();
+
+        assert_eq!(diff.inserts.len(), 2);
+        assert!(diff.deletes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_join_rejects_unsorted_stream() {
+        let pk = vec![col("code")];
+        let table = table("events");
+        // Case-insensitive collation order — not bytewise
+        let source = vec![row(&[("code", json!("a"))]), row(&[("code", json!("B"))])];
+
+        let err = MergeJoinDiffer::new()
+            .diff_streams(stream_of(source), stream_of(vec![]), &pk, &table)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("not sorted"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn merge_join_reports_duplicate_and_null_keys() {
+        let pk = vec![col("id")];
+        let table = table("events");
+        let source = vec![
+            row(&[("id", json!(null)), ("v", json!("a"))]),
+            row(&[("id", json!(1)), ("v", json!("a"))]),
+            row(&[("id", json!(2)), ("v", json!("a"))]),
+        ];
+        let target = vec![
+            row(&[("id", json!(1)), ("v", json!("a"))]),
+            row(&[("id", json!(1)), ("v", json!("b"))]),
+            row(&[("id", json!(2)), ("v", json!("b"))]),
+            row(&[("id", json!(null)), ("v", json!("a"))]),
+        ];
+
+        let diff = MergeJoinDiffer::new()
+            .diff_streams(stream_of(source), stream_of(target), &pk, &table)
+            .await
+            .unwrap();
+
+        // Id 1 is only a finding, id 2 still diffs normally.
+        assert!(diff.inserts.is_empty() && diff.deletes.is_empty());
+        assert_eq!(diff.updates.len(), 1);
+        let found: Vec<_> = diff
+            .data_quality
+            .iter()
+            .map(|f| (f.side, f.kind, f.rows.len()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Side::Source, DataQualityKind::NullKey, 1),
+                (Side::Target, DataQualityKind::DuplicateKey, 2),
+                (Side::Target, DataQualityKind::NullKey, 1),
+            ]
+        );
     }
 }
@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{info, warn};
+
+use crate::domain::compare_rules::CompareRules;
+use crate::domain::discovery::{DiscoveredTable, SkippedTable};
+use crate::domain::ports::SchemaIntrospector;
+use crate::domain::resolution::ResolutionPolicy;
+use crate::domain::value_objects::{ExcludedColumns, Schema};
+use crate::infrastructure::config::{DiffStrategy, DiscoverConfig, TableConfig};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TableDiscovery
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Turns a `[diff.discover]` section into the list of tables to diff.
+///
+/// Every side (source, target — or the target alone for a snapshot) is
+/// introspected. A table matching the patterns is diffed when it exists on
+/// every side with the same primary key, or without one on every side (then
+/// compared as a multiset of rows); otherwise it is returned as a
+/// [`SkippedTable`] with the reason, so the caller can report it.
+///
+/// Explicit `[[diff.tables]]` entries always win: they replace the discovered
+/// table of the same name (their key, excluded columns and strategy are used
+/// as-is) and are kept even when they match no pattern.
+pub struct TableDiscovery {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+/// One introspected database: a label used in skip reasons ("source",
+/// "target"), the introspector and the schema to list.
+pub type DiscoverySide<'a> = (&'a str, &'a dyn SchemaIntrospector, &'a Schema);
+
+impl TableDiscovery {
+    pub fn new(cfg: &DiscoverConfig) -> Self {
+        Self {
+            include: cfg.include.clone(),
+            exclude: cfg.exclude.clone(),
+        }
+    }
+
+    /// `true` when `table` matches an include pattern and no exclude pattern.
+    pub fn matches(&self, table: &str) -> bool {
+        self.include.iter().any(|p| glob_match(p, table))
+            && !self.exclude.iter().any(|p| glob_match(p, table))
+    }
+
+    /// Resolve the tables to diff: discovered tables in name order (explicit
+    /// entries substituted in place), then explicit entries that were not
+    /// discovered, in config order.
+    pub async fn resolve(
+        &self,
+        sides: &[DiscoverySide<'_>],
+        explicit: &[TableConfig],
+    ) -> Result<(Vec<TableConfig>, Vec<SkippedTable>)> {
+        let mut found: Vec<BTreeMap<String, DiscoveredTable>> = Vec::with_capacity(sides.len());
+        for (label, introspector, schema) in sides {
+            let tables = introspector
+                .discover_tables(schema)
+                .await
+                .with_context(|| {
+                    format!("Failed to discover tables in {} ({})", label, schema.0)
+                })?;
+            found.push(tables.into_iter().map(|t| (t.name.clone(), t)).collect());
+        }
+
+        let names: BTreeSet<&String> = found.iter().flat_map(|side| side.keys()).collect();
+        let explicit_names: BTreeSet<&str> = explicit.iter().map(|t| t.name.as_str()).collect();
+
+        let mut tables = Vec::new();
+        let mut skipped = Vec::new();
+
+        for name in names {
+            if explicit_names.contains(name.as_str()) {
+                tables.extend(explicit.iter().find(|t| &t.name == name).cloned());
+                continue;
+            }
+            if !self.matches(name) {
+                continue;
+            }
+            match self.primary_key(name, sides, &found) {
+                Ok(primary_key) => tables.push(TableConfig {
+                    name: name.clone(),
+                    primary_key,
+                    excluded_columns: ExcludedColumns::default(),
+                    strategy: DiffStrategy::default(),
+                    compare: CompareRules::default(),
+                    on_conflict: ResolutionPolicy::default(),
+                }),
+                Err(reason) => {
+                    warn!(table = %name, %reason, "table skipped by discovery");
+                    skipped.push(SkippedTable {
+                        table_name: name.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        for table in explicit {
+            if !tables.iter().any(|t| t.name == table.name) {
+                tables.push(table.clone());
+            }
+        }
+
+        info!(
+            tables = tables.len(),
+            skipped = skipped.len(),
+            "table discovery complete"
+        );
+        Ok((tables, skipped))
+    }
+
+    /// The primary key shared by every side (empty for a table keyless on
+    /// every side), or why there is none.
+    fn primary_key(
+        &self,
+        name: &str,
+        sides: &[DiscoverySide<'_>],
+        found: &[BTreeMap<String, DiscoveredTable>],
+    ) -> std::result::Result<Vec<String>, String> {
+        let mut key: Option<(&str, &Vec<String>)> = None;
+        for ((label, _, _), side) in sides.iter().zip(found) {
+            let table = side
+                .get(name)
+                .ok_or_else(|| format!("not found in {}", label))?;
+            match key {
+                // Keyed on one side only: neither diff applies.
+                Some((first, pk)) if pk.is_empty() != table.primary_key.is_empty() => {
+                    let keyless = if pk.is_empty() { first } else { label };
+                    return Err(format!("no primary key in {}", keyless));
+                }
+                Some((first, pk)) if pk != &table.primary_key => {
+                    return Err(format!(
+                        "primary key differs: {} ({}) vs {} ({})",
+                        first,
+                        pk.join(", "),
+                        label,
+                        table.primary_key.join(", ")
+                    ));
+                }
+                Some(_) => {}
+                None => key = Some((label, &table.primary_key)),
+            }
+        }
+        Ok(key.map(|(_, pk)| pk.clone()).unwrap_or_default())
+    }
+}
+
+/// Glob match on a whole table name: `*` matches any run of characters
+/// (including none), `?` exactly one; everything else matches itself.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    // Position of the last `*` seen, and the name position it currently covers up to.
+    let mut star: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        match p.get(pi) {
+            Some('*') => {
+                star = Some((pi, ni));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == n[ni] => {
+                pi += 1;
+                ni += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry.
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    pi = sp + 1;
+                    ni = sn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::discovery::DiscoveredColumn;
+    use async_trait::async_trait;
+
+    struct FakeCatalog(Vec<DiscoveredTable>);
+
+    impl FakeCatalog {
+        fn new(tables: &[(&str, &[&str])]) -> Self {
+            Self(
+                tables
+                    .iter()
+                    .map(|(name, pk)| DiscoveredTable {
+                        name: name.to_string(),
+                        primary_key: pk.iter().map(|c| c.to_string()).collect(),
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    #[async_trait]
+    impl SchemaIntrospector for FakeCatalog {
+        async fn discover_tables(&self, _schema: &Schema) -> Result<Vec<DiscoveredTable>> {
+            Ok(self.0.clone())
+        }
+
+        async fn describe_columns(&self, _schema: &Schema) -> Result<Vec<DiscoveredColumn>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn discovery(include: &[&str], exclude: &[&str]) -> TableDiscovery {
+        TableDiscovery::new(&DiscoverConfig {
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+        })
+    }
+
+    fn names(tables: &[TableConfig]) -> Vec<&str> {
+        tables.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "users"));
+        assert!(glob_match("tmp_*", "tmp_import"));
+        assert!(glob_match("*_backup", "orders_backup"));
+        assert!(glob_match("user?", "users"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("tmp_*", "users"));
+        assert!(!glob_match("user?", "user"));
+        assert!(!glob_match("users", "users_old"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_applies_patterns_and_includes_keyless_tables() {
+        let schema = Schema("public".into());
+        let source = FakeCatalog::new(&[
+            ("users", &["id"]),
+            ("audit_log", &[]),
+            ("staging", &[]),
+            ("tmp_import", &["id"]),
+            ("orders", &["order_id", "user_id"]),
+        ]);
+        let target = FakeCatalog::new(&[
+            ("users", &["id"]),
+            ("audit_log", &[]),
+            ("staging", &["id"]),
+            ("orders", &["order_id", "user_id"]),
+        ]);
+
+        let (tables, skipped) = discovery(&["*"], &["tmp_*"])
+            .resolve(
+                &[("source", &source, &schema), ("target", &target, &schema)],
+                &[],
+            )
+            .await
+            .unwrap();
+
+        // Keyless on both sides: diffed as a multiset of rows.
+        assert_eq!(names(&tables), vec!["audit_log", "orders", "users"]);
+        assert!(tables[0].primary_key.is_empty());
+        assert_eq!(tables[1].primary_key, vec!["order_id", "user_id"]);
+        assert_eq!(
+            skipped,
+            vec![SkippedTable {
+                table_name: "staging".into(),
+                reason: "no primary key in source".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_skips_tables_missing_or_keyed_differently() {
+        let schema = Schema("public".into());
+        let source = FakeCatalog::new(&[("users", &["id"]), ("new_table", &["id"])]);
+        let target = FakeCatalog::new(&[("users", &["email"])]);
+
+        let (tables, skipped) = discovery(&["*"], &[])
+            .resolve(
+                &[("source", &source, &schema), ("target", &target, &schema)],
+                &[],
+            )
+            .await
+            .unwrap();
+
+        assert!(tables.is_empty());
+        assert_eq!(skipped[0].table_name, "new_table");
+        assert_eq!(skipped[0].reason, "not found in target");
+        assert_eq!(skipped[1].table_name, "users");
+        assert_eq!(
+            skipped[1].reason,
+            "primary key differs: source (id) vs target (email)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explicit_tables_override_discovered_ones() {
+        let schema = Schema("public".into());
+        let catalog = FakeCatalog::new(&[("audit_log", &[]), ("users", &["id"])]);
+        let explicit = vec![
+            TableConfig {
+                name: "audit_log".into(),
+                primary_key: vec!["event_id".into()],
+                excluded_columns: ExcludedColumns(vec!["logged_at".into()]),
+                strategy: DiffStrategy::Streaming,
+                compare: CompareRules::default(),
+                on_conflict: ResolutionPolicy::default(),
+            },
+            TableConfig {
+                name: "legacy".into(),
+                primary_key: vec!["id".into()],
+                excluded_columns: ExcludedColumns::default(),
+                strategy: DiffStrategy::InMemory,
+                compare: CompareRules::default(),
+                on_conflict: ResolutionPolicy::default(),
+            },
+        ];
+
+        let (tables, skipped) = discovery(&["users"], &[])
+            .resolve(&[("target", &catalog, &schema)], &explicit)
+            .await
+            .unwrap();
+
+        // The keyless table is diffed with the explicit key, and the explicit
+        // entry is kept although it matches no include pattern.
+        assert_eq!(names(&tables), vec!["audit_log", "users", "legacy"]);
+        assert_eq!(tables[0].primary_key, vec!["event_id"]);
+        assert_eq!(tables[0].strategy, DiffStrategy::Streaming);
+        assert!(skipped.is_empty());
+    }
+}
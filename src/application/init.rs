@@ -0,0 +1,176 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::domain::compare_rules::CompareRules;
+use crate::domain::discovery::{DiscoveredColumn, SkippedTable};
+use crate::domain::ports::SchemaIntrospector;
+use crate::domain::resolution::ResolutionPolicy;
+use crate::domain::value_objects::{ExcludedColumns, Schema};
+use crate::infrastructure::config::{DiffStrategy, TableConfig};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// InitService
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Column names that conventionally hold write timestamps. They differ
+/// between environments for the same logical row, so they are suggested as
+/// `excluded_columns`.
+const TIMESTAMP_COLUMNS: &[&str] = &["created_at", "updated_at", "modified_at", "inserted_at"];
+
+/// Drafts the `[[diff.tables]]` entries of a new config (`diffly init`).
+///
+/// Every table of the schema gets its detected primary key and, as
+/// `excluded_columns`, the columns that are filled in by the database rather
+/// than copied between environments: write timestamps (by name) and columns
+/// defaulting to the current time.
+pub struct InitService {
+    introspector: Arc<dyn SchemaIntrospector>,
+}
+
+impl InitService {
+    pub fn new(introspector: Arc<dyn SchemaIntrospector>) -> Self {
+        Self { introspector }
+    }
+
+    /// Tables with a primary key, in name order, and the tables without one.
+    pub async fn draft_tables(
+        &self,
+        schema: &Schema,
+    ) -> Result<(Vec<TableConfig>, Vec<SkippedTable>)> {
+        let discovered = self.introspector.discover_tables(schema).await?;
+        let mut columns: BTreeMap<String, Vec<DiscoveredColumn>> = BTreeMap::new();
+        for column in self.introspector.describe_columns(schema).await? {
+            columns
+                .entry(column.table_name.clone())
+                .or_default()
+                .push(column);
+        }
+
+        let mut tables = Vec::new();
+        let mut keyless = Vec::new();
+        for table in discovered {
+            if table.primary_key.is_empty() {
+                keyless.push(SkippedTable {
+                    table_name: table.name,
+                    reason: "no primary key".to_string(),
+                });
+                continue;
+            }
+            let excluded = columns
+                .get(&table.name)
+                .into_iter()
+                .flatten()
+                .filter(|c| !table.primary_key.contains(&c.name) && is_write_timestamp(c))
+                .map(|c| c.name.clone())
+                .collect();
+            tables.push(TableConfig {
+                name: table.name,
+                primary_key: table.primary_key,
+                excluded_columns: ExcludedColumns(excluded),
+                strategy: DiffStrategy::default(),
+                compare: CompareRules::default(),
+                on_conflict: ResolutionPolicy::default(),
+            });
+        }
+        Ok((tables, keyless))
+    }
+}
+
+/// `true` for columns named like a write timestamp, or defaulting to the
+/// current time (`now()`, `CURRENT_TIMESTAMP`, SQLite's `datetime('now')`).
+fn is_write_timestamp(column: &DiscoveredColumn) -> bool {
+    if TIMESTAMP_COLUMNS.contains(&column.name.to_lowercase().as_str()) {
+        return true;
+    }
+    column.default.as_deref().is_some_and(|default| {
+        let default = default.to_lowercase();
+        default.contains("now()")
+            || default.contains("current_timestamp")
+            || default.contains("'now'")
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::discovery::DiscoveredTable;
+    use async_trait::async_trait;
+
+    struct FakeCatalog {
+        tables: Vec<DiscoveredTable>,
+        columns: Vec<DiscoveredColumn>,
+    }
+
+    #[async_trait]
+    impl SchemaIntrospector for FakeCatalog {
+        async fn discover_tables(&self, _schema: &Schema) -> Result<Vec<DiscoveredTable>> {
+            Ok(self.tables.clone())
+        }
+
+        async fn describe_columns(&self, _schema: &Schema) -> Result<Vec<DiscoveredColumn>> {
+            Ok(self.columns.clone())
+        }
+    }
+
+    fn column(table: &str, name: &str, default: Option<&str>) -> DiscoveredColumn {
+        DiscoveredColumn {
+            table_name: table.into(),
+            name: name.into(),
+            default: default.map(Into::into),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_draft_tables_suggests_database_filled_columns() {
+        let catalog = FakeCatalog {
+            tables: vec![
+                DiscoveredTable {
+                    name: "audit_log".into(),
+                    primary_key: vec![],
+                },
+                DiscoveredTable {
+                    name: "orders".into(),
+                    primary_key: vec!["id".into()],
+                },
+            ],
+            columns: vec![
+                column("orders", "id", Some("nextval('orders_id_seq'::regclass)")),
+                column("orders", "status", Some("'new'::text")),
+                column("orders", "placed_at", Some("now()")),
+                column("orders", "synced_at", Some("CURRENT_TIMESTAMP")),
+                column("orders", "updated_at", None),
+                column("audit_log", "msg", None),
+            ],
+        };
+
+        let (tables, keyless) = InitService::new(Arc::new(catalog))
+            .draft_tables(&Schema("public".into()))
+            .await
+            .unwrap();
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "orders");
+        assert_eq!(tables[0].primary_key, vec!["id"]);
+        assert_eq!(
+            tables[0].excluded_columns.0,
+            vec!["placed_at", "synced_at", "updated_at"]
+        );
+        assert_eq!(keyless[0].table_name, "audit_log");
+    }
+
+    #[test]
+    fn test_sqlite_now_default_is_a_write_timestamp() {
+        let c = column("t", "seen", Some("(datetime('now'))"));
+        assert!(is_write_timestamp(&c));
+        assert!(!is_write_timestamp(&column(
+            "t",
+            "note",
+            Some("'now here'")
+        )));
+    }
+}
@@ -1,4 +1,8 @@
+pub mod bisect;
 pub mod conflict;
 pub mod diff;
+pub mod discovery;
+pub mod init;
 pub mod monitoring;
+pub mod resolution;
 pub mod snapshot;
@@ -1,10 +1,13 @@
-use crate::domain::ports::{Differ, RowRepository};
+use crate::domain::ports::{Differ, RowRepository, RowStream, StreamDiffer};
 use crate::domain::{
-    table_diff::{RowMap, TableDiff},
+    foreign_key::ForeignKey,
+    table_diff::{ColumnTypes, RowMap, TableDiff},
     value_objects::{ColumnName, ExcludedColumns, Schema, TableName},
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tracing::{info, instrument};
@@ -103,6 +106,55 @@ impl RowRepository for MonitoringRowRepository {
 
         Ok(rows)
     }
+
+    /// Streamed tables are timed from the query until the stream is
+    /// exhausted, so the duration overlaps with the merge-join diff.
+    async fn stream_rows(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+    ) -> Result<RowStream> {
+        let start = Instant::now();
+        let inner = self
+            .inner
+            .stream_rows(schema, table, pk_cols, excluded)
+            .await?;
+
+        let state = (inner, 0usize, Arc::clone(&self.report), table.0.clone());
+        Ok(Box::pin(stream::unfold(
+            Some(state),
+            move |state| async move {
+                let (mut inner, rows, report, table) = state?;
+                match inner.next().await {
+                    Some(item) => Some((item, Some((inner, rows + 1, report, table)))),
+                    None => {
+                        let duration_ms = start.elapsed().as_millis();
+                        info!(table = %table, rows, duration_ms, "stream_rows completed");
+                        PerfReport::record(
+                            &report,
+                            OpTiming {
+                                operation: "fetch_rows",
+                                table,
+                                duration_ms,
+                                rows,
+                            },
+                        );
+                        None
+                    }
+                }
+            },
+        )))
+    }
+
+    async fn column_sql_types(&self, schema: &Schema, table: &TableName) -> Result<ColumnTypes> {
+        self.inner.column_sql_types(schema, table).await
+    }
+
+    async fn foreign_keys(&self, schema: &Schema) -> Result<Vec<ForeignKey>> {
+        self.inner.foreign_keys(schema).await
+    }
 }
 
 // ─── MonitoringDiffer ────────────────────────────────────────────────────────
@@ -158,3 +210,66 @@ impl Differ for MonitoringDiffer {
         result
     }
 }
+
+// ─── MonitoringStreamDiffer ──────────────────────────────────────────────────
+
+/// Decorator: wraps any `StreamDiffer`, measures wall time per `diff_streams`
+/// call, and appends the result to the shared `PerfReport`.
+pub struct MonitoringStreamDiffer {
+    inner: Arc<dyn StreamDiffer>,
+    report: Arc<Mutex<PerfReport>>,
+}
+
+impl MonitoringStreamDiffer {
+    pub fn new(inner: Arc<dyn StreamDiffer>, report: Arc<Mutex<PerfReport>>) -> Self {
+        Self { inner, report }
+    }
+}
+
+#[async_trait]
+impl StreamDiffer for MonitoringStreamDiffer {
+    #[instrument(
+        name = "diff_streams",
+        skip(self, source, target, pk_cols, table_name),
+        fields(db.table = %table_name.0),
+        level = "info"
+    )]
+    async fn diff_streams(
+        &self,
+        source: RowStream,
+        target: RowStream,
+        pk_cols: &[ColumnName],
+        table_name: &TableName,
+    ) -> Result<TableDiff> {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let count = |rows: RowStream| -> RowStream {
+            let seen = Arc::clone(&seen);
+            Box::pin(rows.inspect(move |_| {
+                seen.fetch_add(1, Ordering::Relaxed);
+            }))
+        };
+
+        let start = Instant::now();
+        let result = self
+            .inner
+            .diff_streams(count(source), count(target), pk_cols, table_name)
+            .await?;
+        let duration_ms = start.elapsed().as_millis();
+
+        let rows = seen.load(Ordering::Relaxed);
+        let changes = result.inserts.len() + result.updates.len() + result.deletes.len();
+        info!(table = %table_name.0, rows, changes, duration_ms, "diff_streams completed");
+
+        PerfReport::record(
+            &self.report,
+            OpTiming {
+                operation: "diff_table",
+                table: table_name.0.clone(),
+                duration_ms,
+                rows,
+            },
+        );
+
+        Ok(result)
+    }
+}
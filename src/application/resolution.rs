@@ -0,0 +1,369 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::application::diff::{diff_columns, whole_row_key};
+use crate::domain::changeset::{Changeset, Summary};
+use crate::domain::compare_rules::{CompareRules, TableRules};
+use crate::domain::resolution::{Choice, Resolution, ResolutionFile};
+use crate::domain::row_key::RowKey;
+use crate::domain::table_diff::{RowChange, RowMap, RowUpdate, TableDiff};
+use crate::domain::value_objects::ColumnName;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ResolutionService
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Folds the decisions of a [`ResolutionFile`] into its changeset.
+///
+/// Each resolution fixes what the conflicting row must look like once the
+/// changeset is applied; the row's insert / update / delete is rewritten to
+/// get there from its current target state:
+///
+/// - update/update: the column takes the chosen value in the row's update;
+///   an update left with no changed column is dropped.
+/// - any other kind: the chosen value is the whole row, `null` for no row.
+///   Against the target row (`target_value`) that is an insert, an update,
+///   a delete or nothing at all.
+/// - a table without a key: the row keeps the source copies (the
+///   changeset is left as is) or the target ones (its inserts and deletes
+///   of the row are dropped).
+///
+/// "Changed" follows the table's comparison rules, exactly like the differ.
+pub struct ResolutionService {
+    rules: TableRules,
+}
+
+impl ResolutionService {
+    pub fn new() -> Self {
+        Self {
+            rules: TableRules::default(),
+        }
+    }
+
+    /// Decide which columns of a rewritten update changed with the
+    /// configured rules, the same way the differ does.
+    pub fn with_rules(mut self, rules: TableRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// The changeset of `file` with every conflict resolved.
+    ///
+    /// Fails while any `choice` is undecided, and on a decision that cannot
+    /// apply (a custom row that is not an object, a conflict on a table or
+    /// row the changeset does not hold).
+    pub fn resolve(&self, file: ResolutionFile) -> Result<Changeset> {
+        let undecided: Vec<String> = file.undecided().map(Resolution::describe).collect();
+        if !undecided.is_empty() {
+            bail!(
+                "{} conflict(s) have no choice yet: {}",
+                undecided.len(),
+                undecided.join("; ")
+            );
+        }
+
+        let mut changeset = file.changeset;
+        for resolution in &file.resolutions {
+            let table = changeset
+                .tables
+                .iter_mut()
+                .find(|t| t.table_name == resolution.table_name)
+                .with_context(|| {
+                    format!("{}: table not in the changeset", resolution.describe())
+                })?;
+            let rules = self.rules.for_table(&table.table_name);
+            self.apply(table, resolution, rules)
+                .with_context(|| format!("Cannot resolve {}", resolution.describe()))?;
+        }
+        changeset.summary = Summary::of(&changeset.tables);
+        Ok(changeset)
+    }
+
+    fn apply(
+        &self,
+        table: &mut TableDiff,
+        resolution: &Resolution,
+        rules: &CompareRules,
+    ) -> Result<()> {
+        let pk_cols: Vec<ColumnName> = table
+            .primary_key
+            .iter()
+            .map(|c| ColumnName(c.clone()))
+            .collect();
+        if pk_cols.is_empty() {
+            return Self::apply_keyless(table, resolution);
+        }
+        let key = RowKey::from_row(&resolution.pk, &pk_cols);
+        // Checked by `resolve`.
+        let chosen = resolution.chosen_value().cloned().unwrap_or_default();
+
+        let (target_row, desired_row) = match &resolution.column {
+            Some(column) => {
+                let update = table
+                    .updates
+                    .iter()
+                    .find(|u| RowKey::from_row(&u.after, &pk_cols) == key)
+                    .context("no update for this row in the changeset")?;
+                let mut desired = update.after.clone();
+                desired.insert(column.clone(), chosen);
+                (Some(update.before.clone()), Some(desired))
+            }
+            None => (as_row(&resolution.target_value)?, as_row(&chosen)?),
+        };
+
+        // Drop whatever the changeset does to the row, then rewrite it.
+        table
+            .inserts
+            .retain(|i| RowKey::from_row(&i.data, &pk_cols) != key);
+        table
+            .updates
+            .retain(|u| RowKey::from_row(&u.after, &pk_cols) != key);
+        table
+            .deletes
+            .retain(|d| RowKey::from_row(&d.data, &pk_cols) != key);
+
+        match (target_row, desired_row) {
+            (None, None) => {}
+            (None, Some(data)) => table.inserts.push(RowChange {
+                pk: resolution.pk.clone(),
+                data,
+            }),
+            (Some(data), None) => table.deletes.push(RowChange {
+                pk: resolution.pk.clone(),
+                data,
+            }),
+            (Some(before), Some(after)) => {
+                let changed_columns = diff_columns(&after, &before, rules);
+                if !changed_columns.is_empty() {
+                    table.updates.push(RowUpdate {
+                        pk: resolution.pk.clone(),
+                        before,
+                        after,
+                        changed_columns,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Without a key every row shares the empty key: only the changes to
+    /// copies of the conflicting row may be touched.
+    fn apply_keyless(table: &mut TableDiff, resolution: &Resolution) -> Result<()> {
+        let row = [
+            &resolution.source_value,
+            &resolution.target_value,
+            &resolution.base_value,
+        ]
+        .into_iter()
+        .find(|v| !v.is_null())
+        .map(as_row)
+        .transpose()?
+        .flatten()
+        .context("the conflict holds no row")?;
+        let key = whole_row_key(&row);
+
+        match resolution.choice {
+            Some(Choice::Source) => {}
+            Some(Choice::Target) => {
+                table.inserts.retain(|i| whole_row_key(&i.data) != key);
+                table.deletes.retain(|d| whole_row_key(&d.data) != key);
+            }
+            _ => bail!("a table without a key only keeps the source or the target copies of a row"),
+        }
+        Ok(())
+    }
+}
+
+impl Default for ResolutionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A row-level value: an object is a row, `null` is no row.
+fn as_row(value: &Value) -> Result<Option<RowMap>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Object(map) => Ok(Some(map.clone().into_iter().collect())),
+        other => bail!("expected a whole row (an object) or null, got {}", other),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::conflict::{ConflictKind, ConflictReport};
+    use crate::domain::table_diff::ColumnTypes;
+    use serde_json::json;
+
+    fn row(id: i64, name: &str, rate: f64) -> RowMap {
+        [
+            ("id".to_string(), json!(id)),
+            ("name".to_string(), json!(name)),
+            ("rate".to_string(), json!(rate)),
+        ]
+        .into()
+    }
+
+    fn pk(id: i64) -> std::collections::BTreeMap<String, Value> {
+        [("id".to_string(), json!(id))].into()
+    }
+
+    /// Row 1: source renamed it and changed the rate, target changed the
+    /// rate too. Row 2: source updated it, target deleted it.
+    fn file() -> ResolutionFile {
+        let before = row(1, "Gold", 0.15);
+        let after = row(1, "Platinum", 0.20);
+        let changeset = Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: "rules".to_string(),
+                primary_key: vec!["id".to_string()],
+                inserts: vec![RowChange {
+                    pk: pk(2),
+                    data: row(2, "Silver", 0.30),
+                }],
+                updates: vec![RowUpdate {
+                    pk: pk(1),
+                    changed_columns: diff_columns(&after, &before, &CompareRules::default()),
+                    before,
+                    after,
+                }],
+                deletes: vec![],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+        let conflicts = [
+            ConflictReport {
+                kind: ConflictKind::UpdateUpdate,
+                table_name: "rules".to_string(),
+                pk: pk(1),
+                column: Some("rate".to_string()),
+                base_value: json!(0.10),
+                source_value: json!(0.20),
+                target_value: json!(0.15),
+            },
+            ConflictReport {
+                kind: ConflictKind::UpdateDelete,
+                table_name: "rules".to_string(),
+                pk: pk(2),
+                column: None,
+                base_value: json!({"id": 2, "name": "Silver", "rate": 0.25}),
+                source_value: json!({"id": 2, "name": "Silver", "rate": 0.30}),
+                target_value: Value::Null,
+            },
+        ];
+        ResolutionFile::new(changeset, &conflicts)
+    }
+
+    #[test]
+    fn undecided_conflicts_are_refused() {
+        let err = ResolutionService::new().resolve(file()).unwrap_err();
+        assert!(err.to_string().contains("2 conflict(s)"), "{err}");
+    }
+
+    #[test]
+    fn choices_rewrite_the_changeset() {
+        let mut f = file();
+        f.resolutions[0].choice = Some(Choice::Target);
+        f.resolutions[1].choice = Some(Choice::Target);
+        let cs = ResolutionService::new().resolve(f).unwrap();
+        let t = &cs.tables[0];
+        // The rate keeps target's value: only the rename is left.
+        assert_eq!(t.updates.len(), 1);
+        let changed: Vec<&str> = t.updates[0]
+            .changed_columns
+            .iter()
+            .map(|c| c.column.as_str())
+            .collect();
+        assert_eq!(changed, ["name"]);
+        assert_eq!(t.updates[0].after["rate"], json!(0.15));
+        // Target deleted row 2: keeping that drops the re-insert.
+        assert!(t.inserts.is_empty());
+        assert_eq!(cs.summary.total_changes, 1);
+    }
+
+    #[test]
+    fn custom_and_base_values_are_applied() {
+        let mut f = file();
+        f.resolutions[0].choice = Some(Choice::Custom);
+        f.resolutions[0].custom_value = json!(0.18);
+        f.resolutions[1].choice = Some(Choice::Base);
+        let cs = ResolutionService::new().resolve(f).unwrap();
+        let t = &cs.tables[0];
+        assert_eq!(t.updates[0].after["rate"], json!(0.18));
+        assert_eq!(t.inserts[0].data["rate"], json!(0.25));
+
+        let mut f = file();
+        f.resolutions[0].choice = Some(Choice::Source);
+        f.resolutions[1].choice = Some(Choice::Custom);
+        f.resolutions[1].custom_value = json!("no row");
+        assert!(ResolutionService::new().resolve(f).is_err());
+    }
+
+    #[test]
+    fn keyless_choices_keep_one_side_of_the_row() {
+        let msg = |m: &str| RowChange {
+            pk: Default::default(),
+            data: [("msg".to_string(), json!(m))].into(),
+        };
+        let changeset = Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![TableDiff {
+                table_name: "audit_log".to_string(),
+                primary_key: vec![],
+                inserts: vec![msg("c"), msg("d")],
+                updates: vec![],
+                deletes: vec![msg("b")],
+                data_quality: vec![],
+                column_types: ColumnTypes::new(),
+                excluded_columns: Vec::new(),
+            }],
+        );
+        let conflict = ConflictReport {
+            kind: ConflictKind::UpdateDelete,
+            table_name: "audit_log".to_string(),
+            pk: Default::default(),
+            column: None,
+            base_value: json!({"msg": "c"}),
+            source_value: json!({"msg": "c"}),
+            target_value: Value::Null,
+        };
+        let file = |choice| {
+            let mut f = ResolutionFile::new(changeset.clone(), std::slice::from_ref(&conflict));
+            f.resolutions[0].choice = Some(choice);
+            f
+        };
+
+        // Only the copies of "c" are dropped, not every keyless change.
+        let cs = ResolutionService::new()
+            .resolve(file(Choice::Target))
+            .unwrap();
+        let t = &cs.tables[0];
+        assert_eq!(t.inserts.len(), 1);
+        assert_eq!(t.inserts[0].data["msg"], json!("d"));
+        assert_eq!(t.deletes.len(), 1);
+
+        let cs = ResolutionService::new()
+            .resolve(file(Choice::Source))
+            .unwrap();
+        assert_eq!(cs.tables[0].inserts.len(), 2);
+
+        assert!(ResolutionService::new()
+            .resolve(file(Choice::Base))
+            .is_err());
+    }
+}
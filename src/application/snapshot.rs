@@ -23,6 +23,10 @@ use crate::infrastructure::config::TableConfig;
 /// The returned `BTreeMap<table_name, Vec<RowMap>>` is the raw data the
 /// orchestrator should serialise (JSON/DynamoDB/S3) and pass back to
 /// `run_with_conflicts` at deploy time via `snapshot_provider()`.
+///
+/// The tables are read in parallel, so the repository must read through a
+/// consistent snapshot (see `SqlxRowRepository::begin_consistent_read`) for
+/// the captured base to be internally consistent.
 pub struct SnapshotService {
     target_repo: Arc<dyn RowRepository>,
 }
@@ -0,0 +1,64 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Kind of statement executed for a single changeset row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Insert => "insert",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+        }
+        .fmt(f)
+    }
+}
+
+/// A changeset row whose statement did not behave as expected.
+///
+/// Either the database rejected the statement, or it affected a number of
+/// rows other than exactly one (e.g. the row was already deleted, or the PK
+/// matched nothing because target changed after the diff was taken).
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyFailure {
+    pub table_name: String,
+    pub operation: Operation,
+    pub pk: BTreeMap<String, Value>,
+    /// The exact statement sent to the database.
+    pub sql: String,
+    /// Human-readable reason (driver error or row-count mismatch).
+    pub reason: String,
+}
+
+/// Outcome of applying a changeset to the target database
+/// (produced by a `ChangesetApplier`).
+///
+/// All statements run inside a single transaction. The transaction is
+/// committed only when every statement affected exactly one row and the run
+/// was not a dry run; otherwise it is rolled back and `committed` is `false`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyReport {
+    pub changeset_id: String,
+    pub dry_run: bool,
+    pub committed: bool,
+    /// Number of statements sent to the database (including failed ones).
+    pub statements_executed: usize,
+    /// Sum of rows affected across all executed statements.
+    pub rows_affected: u64,
+    pub failures: Vec<ApplyFailure>,
+}
+
+impl ApplyReport {
+    /// Returns `true` if every statement affected exactly one row.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
@@ -0,0 +1,80 @@
+use serde_json::{Map, Value};
+
+/// Member name of the object standing for a binary value:
+/// `{"$bytes": "<lowercase hex>"}`.
+///
+/// Hex rather than base64 because every dialect writes binary literals in
+/// hex (`'\x..'::bytea`, `0x..`, `X'..'`), and because equal bytes always
+/// give equal text, so binary values compare byte-exactly like any other
+/// JSON value.
+pub const BYTES_KEY: &str = "$bytes";
+
+/// The tagged representation of `bytes`.
+pub fn bytes_value(bytes: &[u8]) -> Value {
+    let mut map = Map::new();
+    map.insert(BYTES_KEY.to_string(), Value::String(encode_hex(bytes)));
+    Value::Object(map)
+}
+
+/// The hex digits of a binary value, `None` for any other value.
+pub fn bytes_hex(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.get(BYTES_KEY)?.as_str(),
+        _ => None,
+    }
+}
+
+/// The bytes of a binary value.
+pub fn as_bytes(value: &Value) -> Option<Vec<u8>> {
+    decode_hex(bytes_hex(value)?)
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        hex.push(char::from(DIGITS[usize::from(b >> 4)]));
+        hex.push(char::from(DIGITS[usize::from(b & 0xf)]));
+    }
+    hex
+}
+
+/// Decode hex digits (either case); `None` on an odd length or a non-hex
+/// character.
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let digits = hex.as_bytes();
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let nibble = |c: u8| char::from(c).to_digit(16);
+            Some((nibble(pair[0])? << 4 | nibble(pair[1])?) as u8)
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bytes_round_trip_through_tagged_hex() {
+        let raw = [0x00, 0xff, 0x10, b'\''];
+        let value = bytes_value(&raw);
+        assert_eq!(value, json!({"$bytes": "00ff1027"}));
+        assert_eq!(as_bytes(&value).unwrap(), raw);
+        assert_eq!(decode_hex("0A0b").unwrap(), [0x0a, 0x0b]);
+        assert!(decode_hex("abc").is_none());
+        assert!(decode_hex("zz").is_none());
+        // Only the exact one-member shape is binary.
+        assert!(bytes_hex(&json!({"$bytes": "00", "x": 1})).is_none());
+        assert!(bytes_hex(&json!("00")).is_none());
+    }
+}
@@ -1,55 +1,87 @@
 use crate::application::monitoring::PerfReport;
+use crate::domain::discovery::SkippedTable;
+use crate::domain::foreign_key::ForeignKey;
 use crate::domain::table_diff::TableDiff;
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Changeset {
     pub changeset_id: String,
     pub source_schema: String,
     pub target_schema: String,
-    /// Database driver used to produce this changeset: "postgres", "mysql", "mariadb", "sqlite".
-    /// Used by SqlWriter to generate the correct SQL dialect.
+    /// Driver of the source database: "postgres", "mysql", "mariadb", "sqlite".
     #[serde(default = "default_driver")]
-    pub driver: String,
+    pub source_driver: String,
+    /// Driver of the target database. The changeset is applied to the target,
+    /// so SqlWriter (and the JSON `sql` fields) render SQL in this dialect.
+    /// Reads the single `driver` field of changesets written before source
+    /// and target drivers were recorded separately.
+    #[serde(default = "default_driver", alias = "driver")]
+    pub target_driver: String,
     pub created_at: String,
     pub source_fingerprint: String,
     pub target_fingerprint: String,
     pub tables: Vec<TableDiff>,
     pub summary: Summary,
+    /// Tables found by `[diff.discover]` but left out of the diff (no primary
+    /// key, missing on one side, …). Empty when discovery is off.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_tables: Vec<SkippedTable>,
+    /// Foreign keys between the tables of the changeset, read from target:
+    /// statements are ordered along them (see `StatementOrder`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_keys: Vec<ForeignKey>,
     /// Optional performance report embedded at diff time.
     /// Present in JSON and HTML outputs when `run_with_timing` is used.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Not read back when a changeset is loaded from JSON (e.g. `diffly apply`).
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
     pub perf: Option<PerfReport>,
 }
 
-#[allow(dead_code)] // invoked by serde(default), not called directly
 fn default_driver() -> String {
     "postgres".to_string()
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Summary {
     pub total_inserts: usize,
     pub total_updates: usize,
     pub total_deletes: usize,
     pub total_changes: usize,
     pub tables_affected: usize,
+    /// Duplicate / NULL primary keys found while diffing (see
+    /// `TableDiff::data_quality`).
+    #[serde(default)]
+    pub data_quality_findings: usize,
+}
+
+impl Summary {
+    /// Totals over `tables`.
+    pub fn of(tables: &[TableDiff]) -> Self {
+        let total_inserts: usize = tables.iter().map(|t| t.inserts.len()).sum();
+        let total_updates: usize = tables.iter().map(|t| t.updates.len()).sum();
+        let total_deletes: usize = tables.iter().map(|t| t.deletes.len()).sum();
+        Summary {
+            total_inserts,
+            total_updates,
+            total_deletes,
+            total_changes: total_inserts + total_updates + total_deletes,
+            tables_affected: tables.iter().filter(|t| !t.is_empty()).count(),
+            data_quality_findings: tables.iter().map(|t| t.data_quality.len()).sum(),
+        }
+    }
 }
 
 impl Changeset {
     pub fn new(
         source_schema: &str,
         target_schema: &str,
-        driver: &str,
+        source_driver: &str,
+        target_driver: &str,
         tables: Vec<TableDiff>,
     ) -> Self {
-        let total_inserts: usize = tables.iter().map(|t| t.inserts.len()).sum();
-        let total_updates: usize = tables.iter().map(|t| t.updates.len()).sum();
-        let total_deletes: usize = tables.iter().map(|t| t.deletes.len()).sum();
-        let tables_affected = tables.iter().filter(|t| !t.is_empty()).count();
-
         Changeset {
             changeset_id: format!(
                 "cs_{}_{}",
@@ -58,18 +90,15 @@ impl Changeset {
             ),
             source_schema: source_schema.to_string(),
             target_schema: target_schema.to_string(),
-            driver: driver.to_string(),
+            source_driver: source_driver.to_string(),
+            target_driver: target_driver.to_string(),
             created_at: Utc::now().to_rfc3339(),
             source_fingerprint: String::new(), // Computed during diff if needed
             target_fingerprint: String::new(),
+            summary: Summary::of(&tables),
             tables,
-            summary: Summary {
-                total_inserts,
-                total_updates,
-                total_deletes,
-                total_changes: total_inserts + total_updates + total_deletes,
-                tables_affected,
-            },
+            skipped_tables: Vec::new(),
+            foreign_keys: Vec::new(),
             perf: None,
         }
     }
@@ -81,4 +110,107 @@ impl Changeset {
         self.perf = Some(perf);
         self
     }
+
+    /// Record the tables that discovery left out (builder pattern).
+    pub fn with_skipped_tables(mut self, skipped: Vec<SkippedTable>) -> Self {
+        self.skipped_tables = skipped;
+        self
+    }
+
+    /// Record the foreign keys between the changeset's tables (builder
+    /// pattern).
+    pub fn with_foreign_keys(mut self, foreign_keys: Vec<ForeignKey>) -> Self {
+        self.foreign_keys = foreign_keys;
+        self
+    }
+
+    /// The changeset rolling this one back once it has been applied to
+    /// target: every table inverted (see [`TableDiff::invert`]), under the
+    /// same id, schemas and drivers. The fingerprints swap, the one of the
+    /// state to reach coming first.
+    ///
+    /// Statements keep following the foreign keys, so the rollback deletes
+    /// inserted children before their parents and re-inserts deleted
+    /// parents before their children.
+    pub fn invert(&self) -> Changeset {
+        let tables: Vec<TableDiff> = self.tables.iter().map(TableDiff::invert).collect();
+        Changeset {
+            changeset_id: self.changeset_id.clone(),
+            source_schema: self.source_schema.clone(),
+            target_schema: self.target_schema.clone(),
+            source_driver: self.source_driver.clone(),
+            target_driver: self.target_driver.clone(),
+            created_at: self.created_at.clone(),
+            source_fingerprint: self.target_fingerprint.clone(),
+            target_fingerprint: self.source_fingerprint.clone(),
+            summary: Summary::of(&tables),
+            tables,
+            skipped_tables: self.skipped_tables.clone(),
+            foreign_keys: self.foreign_keys.clone(),
+            perf: None,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate};
+    use serde_json::json;
+
+    fn row(id: i64, doc: serde_json::Value) -> RowChange {
+        RowChange {
+            pk: [("id".to_string(), json!(id))].into(),
+            data: [("id".to_string(), json!(id)), ("doc".to_string(), doc)].into(),
+        }
+    }
+
+    #[test]
+    fn invert_undoes_every_change() {
+        let (before, after) = (json!({"a": 1}), json!({"a": 2, "b": 3}));
+        let update = RowUpdate {
+            pk: [("id".to_string(), json!(1))].into(),
+            before: row(1, before.clone()).data,
+            after: row(1, after.clone()).data,
+            changed_columns: vec![ColumnDiff {
+                column: "doc".to_string(),
+                before: before.clone(),
+                after: after.clone(),
+                patch: Some(crate::domain::json_patch::diff(&before, &after)),
+            }],
+        };
+        let table = TableDiff {
+            table_name: "docs".to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![row(2, json!({}))],
+            updates: vec![update],
+            deletes: vec![row(3, json!({})), row(4, json!({}))],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        let mut cs = Changeset::new("source", "target", "postgres", "postgres", vec![table]);
+        cs.source_fingerprint = "after".to_string();
+        cs.target_fingerprint = "before".to_string();
+
+        let inverse = cs.invert();
+        assert_eq!(inverse.changeset_id, cs.changeset_id);
+        assert_eq!(inverse.source_fingerprint, "before");
+        assert_eq!(inverse.summary.total_inserts, 2);
+        assert_eq!(inverse.summary.total_deletes, 1);
+        let t = &inverse.tables[0];
+        assert_eq!(t.inserts[0].data["id"], json!(3));
+        assert_eq!(t.deletes[0].data["id"], json!(2));
+        let diff = &t.updates[0].changed_columns[0];
+        assert_eq!((&diff.before, &diff.after), (&after, &before));
+        assert_eq!(t.updates[0].after["doc"], before);
+        assert_eq!(
+            serde_json::to_value(&diff.patch).unwrap(),
+            json!([{"op": "remove", "path": "/b"}, {"op": "replace", "path": "/a", "value": 1}])
+        );
+    }
 }
@@ -0,0 +1,431 @@
+use chrono::{DateTime, DurationRound, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::domain::binary::bytes_hex;
+use crate::domain::decimal::Decimal;
+use crate::domain::json_patch::JsonPath;
+
+/// How the values of one column are compared. Every setting is optional so
+/// rules can be layered: a column rule overrides its table's rule, which
+/// overrides the global `[diff.compare]` rule, setting by setting.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CompareRule {
+    /// Numbers equal when they differ by at most this much.
+    pub abs_tolerance: Option<f64>,
+    /// Numbers equal when they differ by at most this fraction of the larger
+    /// magnitude (`0.01` = 1 %). Either tolerance is enough.
+    pub rel_tolerance: Option<f64>,
+    /// Round numbers to this many decimal places before comparing.
+    pub round_decimals: Option<u32>,
+    /// Numbers equal by value whatever their scale: `1.50` == `1.5`.
+    pub ignore_scale: Option<bool>,
+    /// Compare text case-insensitively.
+    pub ignore_case: Option<bool>,
+    /// Ignore leading and trailing whitespace in text.
+    pub trim_whitespace: Option<bool>,
+    /// Treat NULL and the empty string (after trimming) as the same value.
+    pub null_equals_empty: Option<bool>,
+    /// Truncate timestamps to this unit before comparing.
+    pub timestamp_precision: Option<TimestampPrecision>,
+    /// Parts of a JSON document to leave out of the comparison, e.g.
+    /// `["$.meta.last_sync"]`.
+    pub ignore_paths: Option<Vec<JsonPath>>,
+    /// Set when the numeric settings come from the table or global scope
+    /// rather than the column's own rule: they then apply to numbers only,
+    /// so `'007'` stays different from `'7'` in a text column.
+    #[serde(skip)]
+    inherited_numbers: bool,
+}
+
+/// Unit timestamps are truncated to by [`CompareRule::timestamp_precision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampPrecision {
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Millisecond,
+    Microsecond,
+}
+
+/// Rules of one scope (global or table): a rule for every column, refined
+/// per column name.
+///
+/// ```toml
+/// [diff.compare]
+/// timestamp_precision = "second"
+///
+/// [diff.compare.columns.email]
+/// ignore_case = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CompareRules {
+    #[serde(flatten)]
+    pub all: CompareRule,
+    #[serde(default)]
+    pub columns: BTreeMap<String, CompareRule>,
+}
+
+impl CompareRule {
+    /// `self`, with every setting `over` defines taking precedence.
+    pub fn overridden_by(&self, over: &CompareRule) -> CompareRule {
+        CompareRule {
+            abs_tolerance: over.abs_tolerance.or(self.abs_tolerance),
+            rel_tolerance: over.rel_tolerance.or(self.rel_tolerance),
+            round_decimals: over.round_decimals.or(self.round_decimals),
+            ignore_scale: over.ignore_scale.or(self.ignore_scale),
+            ignore_case: over.ignore_case.or(self.ignore_case),
+            trim_whitespace: over.trim_whitespace.or(self.trim_whitespace),
+            null_equals_empty: over.null_equals_empty.or(self.null_equals_empty),
+            timestamp_precision: over.timestamp_precision.or(self.timestamp_precision),
+            ignore_paths: over
+                .ignore_paths
+                .clone()
+                .or_else(|| self.ignore_paths.clone()),
+            inherited_numbers: false,
+        }
+    }
+
+    /// `true` when `a` and `b` count as the same value under this rule.
+    /// Without any setting: numbers by exact decimal value and scale
+    /// (`1.5` ≠ `1.50`), a boolean equal to its 0 / 1 integer, everything
+    /// else exactly.
+    pub fn values_equal(&self, a: &Value, b: &Value) -> bool {
+        if a == b {
+            return true;
+        }
+        if self.ignore_paths.is_some() && is_document(a) && is_document(b) {
+            return self.without_ignored(a) == self.without_ignored(b);
+        }
+        let (a, b) = (self.normalize_text(a), self.normalize_text(b));
+
+        if let (Some(x), Some(y)) = (self.as_number(&a), self.as_number(&b)) {
+            return self.numbers_equal(x, y);
+        }
+        if let (Some(precision), Value::String(x), Value::String(y)) =
+            (self.timestamp_precision, &a, &b)
+        {
+            if let (Some(x), Some(y)) = (truncate(x, precision), truncate(y, precision)) {
+                return x == y;
+            }
+        }
+        strict_equal(&a, &b)
+    }
+
+    /// `doc` without the parts listed in `ignore_paths`.
+    pub fn without_ignored(&self, doc: &Value) -> Value {
+        let mut doc = doc.clone();
+        for path in self.ignore_paths.iter().flatten() {
+            path.remove_from(&mut doc);
+        }
+        doc
+    }
+
+    /// Apply the text settings: trimming, case folding, empty → NULL.
+    fn normalize_text(&self, v: &Value) -> Value {
+        let Value::String(s) = v else {
+            return v.clone();
+        };
+        let s = if self.trim_whitespace == Some(true) {
+            s.trim()
+        } else {
+            s.as_str()
+        };
+        if self.null_equals_empty == Some(true) && s.trim().is_empty() {
+            Value::Null
+        } else if self.ignore_case == Some(true) {
+            Value::String(s.to_lowercase())
+        } else {
+            Value::String(s.to_string())
+        }
+    }
+
+    /// A JSON number, or numeric text when the column's own rule has a
+    /// numeric setting (numbers stored in a text column).
+    fn as_number(&self, v: &Value) -> Option<Decimal> {
+        match v {
+            Value::Number(n) => Decimal::parse(&n.to_string()),
+            Value::String(s) if !self.exact_numbers() && !self.inherited_numbers => {
+                Decimal::parse(s)
+            }
+            _ => None,
+        }
+    }
+
+    /// `true` when no numeric setting is configured.
+    fn exact_numbers(&self) -> bool {
+        self.abs_tolerance.is_none()
+            && self.rel_tolerance.is_none()
+            && self.round_decimals.is_none()
+            && self.ignore_scale != Some(true)
+    }
+
+    fn numbers_equal(&self, x: Decimal, y: Decimal) -> bool {
+        let (x, y) = match self.round_decimals {
+            Some(places) => (x.round(places), y.round(places)),
+            None => (x, y),
+        };
+        if x == y {
+            return !self.exact_numbers() || x.scale() == y.scale();
+        }
+        // Tolerances are bounds, not values: the difference itself is exact.
+        let Some(diff) = x.abs_diff(&y) else {
+            return false;
+        };
+        self.abs_tolerance
+            .and_then(|tol| Decimal::parse(&tol.to_string()))
+            .is_some_and(|tol| diff <= tol)
+            || self
+                .rel_tolerance
+                .is_some_and(|rel| diff.to_f64() <= rel * x.to_f64().abs().max(y.to_f64().abs()))
+    }
+}
+
+impl CompareRules {
+    /// `self`, refined by the rules of a narrower scope.
+    pub fn overridden_by(&self, over: &CompareRules) -> CompareRules {
+        let mut columns = self.columns.clone();
+        for (name, rule) in &over.columns {
+            let merged = columns.get(name).cloned().unwrap_or_default();
+            columns.insert(name.clone(), merged.overridden_by(rule));
+        }
+        CompareRules {
+            all: self.all.overridden_by(&over.all),
+            columns,
+        }
+    }
+
+    /// The rule applied to `column`.
+    pub fn for_column(&self, column: &str) -> CompareRule {
+        match self.columns.get(column) {
+            Some(rule) => CompareRule {
+                inherited_numbers: rule.exact_numbers(),
+                ..self.all.overridden_by(rule)
+            },
+            None => CompareRule {
+                inherited_numbers: true,
+                ..self.all.clone()
+            },
+        }
+    }
+
+    /// `true` when `a` and `b`, two values of `column`, count as equal.
+    pub fn values_equal(&self, column: &str, a: &Value, b: &Value) -> bool {
+        self.for_column(column).values_equal(a, b)
+    }
+}
+
+/// The rules of every table: the global rules, refined per table name.
+/// Tables without rules of their own (e.g. discovered ones) get the global
+/// rules.
+#[derive(Debug, Clone, Default)]
+pub struct TableRules {
+    global: CompareRules,
+    tables: BTreeMap<String, CompareRules>,
+}
+
+impl TableRules {
+    pub fn new<'a>(
+        global: &CompareRules,
+        tables: impl IntoIterator<Item = (&'a str, &'a CompareRules)>,
+    ) -> Self {
+        Self {
+            global: global.clone(),
+            tables: tables
+                .into_iter()
+                .map(|(name, rules)| (name.to_string(), global.overridden_by(rules)))
+                .collect(),
+        }
+    }
+
+    pub fn for_table(&self, table: &str) -> &CompareRules {
+        self.tables.get(table).unwrap_or(&self.global)
+    }
+}
+
+/// A JSON object or array (a `json` / `jsonb` value), as opposed to a scalar
+/// or a binary value.
+pub fn is_document(v: &Value) -> bool {
+    matches!(v, Value::Object(_) | Value::Array(_)) && bytes_hex(v).is_none()
+}
+
+/// Equality of non-numbers without rules: a boolean equal to its 0 / 1
+/// integer, objects regardless of key order.
+fn strict_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        // Only seen across drivers: a boolean stored as 0/1 where the other
+        // database has no boolean type (SQLite).
+        (Value::Bool(b), Value::Number(n)) | (Value::Number(n), Value::Bool(b)) => {
+            n.as_i64() == Some(i64::from(*b))
+        }
+        _ => a == b,
+    }
+}
+
+/// Parse a timestamp (any offset converted to UTC) or a date, truncated to
+/// `precision`. `None` when `s` is neither.
+fn truncate(s: &str, precision: TimestampPrecision) -> Option<NaiveDateTime> {
+    let s = s.trim();
+    let parsed = DateTime::parse_from_rfc3339(s)
+        .ok()
+        .or_else(|| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z").ok())
+        .map(|dt| dt.with_timezone(&Utc).naive_utc())
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+
+    let unit = match precision {
+        TimestampPrecision::Day => TimeDelta::days(1),
+        TimestampPrecision::Hour => TimeDelta::hours(1),
+        TimestampPrecision::Minute => TimeDelta::minutes(1),
+        TimestampPrecision::Second => TimeDelta::seconds(1),
+        TimestampPrecision::Millisecond => TimeDelta::milliseconds(1),
+        TimestampPrecision::Microsecond => TimeDelta::microseconds(1),
+    };
+    parsed.duration_trunc(unit).ok()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(f: impl FnOnce(&mut CompareRule)) -> CompareRule {
+        let mut r = CompareRule::default();
+        f(&mut r);
+        r
+    }
+
+    #[test]
+    fn default_rule_keeps_builtin_equality() {
+        let r = CompareRule::default();
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        assert!(!r.values_equal(&json!(0.1), &json!(0.1 + 1e-12)));
+        assert!(!r.values_equal(&n("1.50"), &n("1.5")));
+        assert!(r.values_equal(&n("1.5"), &json!(1.5)));
+        assert!(!r.values_equal(&json!(0.1), &json!(0.11)));
+        assert!(r.values_equal(&json!(true), &json!(1)));
+        assert!(!r.values_equal(&json!("A"), &json!("a")));
+        assert!(!r.values_equal(&Value::Null, &json!("")));
+    }
+
+    #[test]
+    fn numeric_tolerances_and_rounding() {
+        let abs = rule(|r| r.abs_tolerance = Some(0.01));
+        assert!(abs.values_equal(&json!(1.004), &json!(1.0)));
+        assert!(!abs.values_equal(&json!(1.02), &json!(1.0)));
+
+        let rel = rule(|r| r.rel_tolerance = Some(0.01));
+        assert!(rel.values_equal(&json!(1000), &json!(1009)));
+        assert!(!rel.values_equal(&json!(1), &json!(1.02)));
+
+        let round = rule(|r| r.round_decimals = Some(2));
+        assert!(round.values_equal(&json!(0.123), &json!("0.12")));
+        assert!(!round.values_equal(&json!(0.126), &json!(0.12)));
+        assert!(round.values_equal(&json!(1.005), &json!(1.01)));
+
+        let scale = rule(|r| r.ignore_scale = Some(true));
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        assert!(scale.values_equal(&n("1.50"), &n("1.5")));
+        assert!(!scale.values_equal(&n("1.50"), &n("1.51")));
+
+        // Tolerances apply to the exact difference, however large the values.
+        let cents = rule(|r| r.abs_tolerance = Some(0.01));
+        assert!(cents.values_equal(&n("123456789012345678.004"), &n("123456789012345678.000")));
+        assert!(!cents.values_equal(&n("123456789012345678.02"), &n("123456789012345678.00")));
+    }
+
+    #[test]
+    fn wider_numeric_settings_leave_text_alone() {
+        let rules = CompareRules {
+            all: rule(|r| r.ignore_scale = Some(true)),
+            columns: [("qty".into(), rule(|r| r.round_decimals = Some(0)))].into(),
+        };
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        assert!(rules.values_equal("code", &n("1.50"), &n("1.5")));
+        assert!(!rules.values_equal("code", &json!("007"), &json!("7")));
+        assert!(!rules.values_equal("code", &json!("1e3"), &json!("1000")));
+        // A numeric setting on the column itself also reads its text.
+        assert!(rules.values_equal("qty", &json!("1.0"), &json!("1")));
+    }
+
+    #[test]
+    fn text_rules() {
+        let r = rule(|r| {
+            r.ignore_case = Some(true);
+            r.trim_whitespace = Some(true);
+            r.null_equals_empty = Some(true);
+        });
+        assert!(r.values_equal(&json!(" Alice@Example.com "), &json!("alice@example.com")));
+        assert!(r.values_equal(&Value::Null, &json!("  ")));
+        assert!(!r.values_equal(&json!("alice"), &json!("bob")));
+    }
+
+    #[test]
+    fn timestamps_truncated_to_precision() {
+        let r = rule(|r| r.timestamp_precision = Some(TimestampPrecision::Second));
+        assert!(r.values_equal(
+            &json!("2024-01-01 10:00:00.123456"),
+            &json!("2024-01-01T10:00:00.9Z")
+        ));
+        assert!(r.values_equal(
+            &json!("2024-01-01 12:00:00+02"),
+            &json!("2024-01-01 10:00:00")
+        ));
+        assert!(!r.values_equal(&json!("2024-01-01 10:00:01"), &json!("2024-01-01 10:00:00")));
+        // Not a timestamp: compared as text.
+        assert!(!r.values_equal(&json!("later"), &json!("soon")));
+    }
+
+    #[test]
+    fn ignored_json_paths() {
+        let r = rule(|r| r.ignore_paths = Some(vec![JsonPath::parse("$.meta.last_sync").unwrap()]));
+        let a = json!({"theme": "dark", "meta": {"last_sync": "2024-01-01"}});
+        let b = json!({"theme": "dark", "meta": {"last_sync": "2024-06-30"}});
+        assert!(r.values_equal(&a, &b));
+        assert!(!r.values_equal(&a, &json!({"theme": "light", "meta": {}})));
+        assert!(!CompareRule::default().values_equal(&a, &b));
+    }
+
+    #[test]
+    fn column_rules_override_table_and_global_rules() {
+        let global = CompareRules {
+            all: rule(|r| r.ignore_case = Some(true)),
+            columns: [("price".into(), rule(|r| r.abs_tolerance = Some(0.5)))].into(),
+        };
+        let table = CompareRules {
+            all: CompareRule::default(),
+            columns: [
+                ("price".into(), rule(|r| r.round_decimals = Some(0))),
+                ("code".into(), rule(|r| r.ignore_case = Some(false))),
+            ]
+            .into(),
+        };
+        let rules = TableRules::new(&global, [("orders", &table)]);
+
+        let orders = rules.for_table("orders");
+        let price = orders.for_column("price");
+        assert_eq!(price.abs_tolerance, Some(0.5));
+        assert_eq!(price.round_decimals, Some(0));
+        assert!(!orders.values_equal("code", &json!("A"), &json!("a")));
+        assert!(orders.values_equal("name", &json!("A"), &json!("a")));
+        // Other tables only see the global rules.
+        assert!(rules.for_table("users").columns["price"]
+            .round_decimals
+            .is_none());
+    }
+}
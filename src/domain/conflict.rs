@@ -1,26 +1,74 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::fmt;
 
-/// A single cell-level conflict detected by the 3-way merge.
+/// How the source and target changes to one row collide, relative to the
+/// base snapshot.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both sides changed the same column to different values.
+    UpdateUpdate,
+    /// The source updated a row that target deleted.
+    UpdateDelete,
+    /// The source deleted a row that target updated.
+    DeleteUpdate,
+    /// Both sides inserted the same key with different content.
+    InsertInsert,
+    /// Both sides deleted the row: nothing to apply, reported for the record.
+    DeleteDelete,
+}
+
+impl ConflictKind {
+    /// `true` for a collision that needs no decision (delete/delete).
+    pub fn is_benign(self) -> bool {
+        matches!(self, Self::DeleteDelete)
+    }
+}
+
+/// `source/target` change pairs: `update/delete`, `insert/insert`, …
+impl fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UpdateUpdate => "update/update",
+            Self::UpdateDelete => "update/delete",
+            Self::DeleteUpdate => "delete/update",
+            Self::InsertInsert => "insert/insert",
+            Self::DeleteDelete => "delete/delete",
+        })
+    }
+}
+
+/// A conflict detected by the 3-way merge.
+///
+/// A conflict exists when the same row was changed **both** in the source
+/// AND in target since the base snapshot was taken at clone time, in ways
+/// that do not merge: see [`ConflictKind`].
 ///
-/// A conflict exists when the same `(table, pk, column)` triple was modified
-/// **both** in the source (source) AND in target (target) since the base
-/// snapshot was taken at clone time. Both values differ from the base, and
-/// they differ from each other — so there is no safe automatic resolution.
+/// - An update/update conflict is cell-level: `column` names the column, and
+///   the three values are that column's. Both values differ from the base,
+///   and they differ from each other — so there is no safe automatic
+///   resolution.
+/// - Every other kind is row-level: `column` is `None`, and the three values
+///   are whole rows (JSON objects), `null` on a side where the row does not
+///   exist.
 ///
 /// The external orchestrator / back-office presents these to the admin who
 /// chooses which value to keep (source, target, or a custom value).
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct ConflictReport {
+    pub kind: ConflictKind,
+
     /// Table where the conflict was found.
     pub table_name: String,
 
     /// Primary key identifying the conflicting row.
     pub pk: BTreeMap<String, Value>,
 
-    /// Column whose value conflicts.
-    pub column: String,
+    /// Column whose value conflicts; `None` for a row-level conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
 
     /// Value in the base snapshot (target at source-clone time).
     pub base_value: Value,
@@ -31,3 +79,10 @@ pub struct ConflictReport {
     /// Current value in target (what another admin deployed since the clone).
     pub target_value: Value,
 }
+
+impl ConflictReport {
+    /// `true` when the conflict needs a decision before deploying.
+    pub fn is_blocking(&self) -> bool {
+        !self.kind.is_benign()
+    }
+}
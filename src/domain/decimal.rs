@@ -0,0 +1,326 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Most digits in a mantissa, and largest exponent, [`Decimal::parse`]
+/// accepts: the widest PostgreSQL `NUMERIC` has 147455 digits. Beyond that
+/// a "number" is more likely hostile input than data, and aligning its
+/// scale would allocate without bound.
+const MAX_DIGITS: usize = 147_455;
+
+/// An exact decimal number, parsed from the text of a JSON number or of a
+/// `NUMERIC` / `DECIMAL` value, with no precision limit.
+///
+/// Equality and ordering are by value (`1.50 == 1.5`); [`Decimal::scale`]
+/// keeps the number of fraction digits as written, for comparisons that
+/// also require the same scale.
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    negative: bool,
+    /// Decimal digits (0–9), most significant first, without leading zeros:
+    /// empty for zero.
+    digits: Vec<u8>,
+    /// The value is `digits × 10^-scale`.
+    scale: i64,
+}
+
+impl Decimal {
+    /// Parse `123`, `-0.0150`, `1.5e-3`, … `None` for anything else
+    /// (including `NaN`, `Infinity`, and more than 147455 digits, the widest
+    /// PostgreSQL `NUMERIC`, or exponent).
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (negative, s) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int.len() + frac.len() > MAX_DIGITS || exponent.unsigned_abs() > MAX_DIGITS as u64 {
+            return None;
+        }
+        if int.is_empty() && frac.is_empty()
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits: Vec<u8> = int
+            .bytes()
+            .chain(frac.bytes())
+            .map(|b| b - b'0')
+            .skip_while(|&d| d == 0)
+            .collect();
+        Some(Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale: (frac.len() as i64).checked_sub(exponent)?,
+        })
+    }
+
+    /// Number of fraction digits as written: 2 for `1.50`, 0 for `15`.
+    pub fn scale(&self) -> i64 {
+        self.scale.max(0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// The same value without trailing fraction zeros: `1.50` → `1.5`,
+    /// `5.00` → `5`.
+    pub fn normalized(&self) -> Self {
+        let mut n = self.clone();
+        while n.scale > 0 && n.digits.last() == Some(&0) {
+            n.digits.pop();
+            n.scale -= 1;
+        }
+        if n.is_zero() {
+            n.scale = 0;
+        }
+        n
+    }
+
+    /// Round half away from zero to `places` fraction digits.
+    pub fn round(&self, places: u32) -> Self {
+        let places = i64::from(places);
+        if self.scale <= places {
+            return self.clone();
+        }
+        let cut = (self.scale - places) as usize;
+        let len = self.digits.len();
+        let (mut kept, round_up) = if cut > len {
+            (Vec::new(), false)
+        } else {
+            (
+                self.digits[..len - cut].to_vec(),
+                self.digits[len - cut] >= 5,
+            )
+        };
+        if round_up {
+            kept = add_digits(&kept, &[1]);
+        }
+        Self::from_parts(self.negative, kept, places)
+    }
+
+    /// `|self - other|`, exactly. `None` if the scales are too far apart to
+    /// align.
+    pub fn abs_diff(&self, other: &Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        let a = self.aligned(scale)?;
+        let b = other.aligned(scale)?;
+        let digits = if self.negative != other.negative {
+            add_digits(&a, &b)
+        } else if cmp_digits(&a, &b).is_ge() {
+            sub_digits(&a, &b)
+        } else {
+            sub_digits(&b, &a)
+        };
+        Some(Self::from_parts(false, digits, scale))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    fn from_parts(negative: bool, digits: Vec<u8>, scale: i64) -> Self {
+        let digits: Vec<u8> = digits.into_iter().skip_while(|&d| d == 0).collect();
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale,
+        }
+    }
+
+    /// The digits scaled up to `scale` (which is at least `self.scale`).
+    fn aligned(&self, scale: i64) -> Option<Vec<u8>> {
+        let zeros = usize::try_from(scale.checked_sub(self.scale)?).ok()?;
+        let mut digits = self.digits.clone();
+        digits.resize(digits.len().checked_add(zeros)?, 0);
+        Some(digits)
+    }
+
+    /// Position of the leading digit: `digits.len() - scale`.
+    fn magnitude(&self) -> i64 {
+        self.digits.len() as i64 - self.scale
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let signum = |d: &Self| match (d.is_zero(), d.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+        let by_sign = signum(self).cmp(&signum(other));
+        if by_sign.is_ne() || self.is_zero() {
+            return by_sign;
+        }
+        let (a, b) = (self.normalized(), other.normalized());
+        // Without trailing zeros, equal magnitudes compare digit by digit.
+        let by_abs = a
+            .magnitude()
+            .cmp(&b.magnitude())
+            .then_with(|| a.digits.cmp(&b.digits));
+        if self.negative {
+            by_abs.reverse()
+        } else {
+            by_abs
+        }
+    }
+}
+
+/// Plain notation, keeping the scale: `-0.0150`, `1500`.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits: String = self.digits.iter().map(|d| char::from(b'0' + d)).collect();
+        if self.scale <= 0 {
+            if digits.is_empty() {
+                digits.push('0');
+            } else {
+                digits.extend(std::iter::repeat('0').take(-self.scale as usize));
+            }
+        } else {
+            let scale = self.scale as usize;
+            if digits.len() <= scale {
+                digits.insert_str(0, &"0".repeat(scale + 1 - digits.len()));
+            }
+            digits.insert(digits.len() - scale, '.');
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        f.write_str(&digits)
+    }
+}
+
+fn cmp_digits(a: &[u8], b: &[u8]) -> Ordering {
+    let strip = |d: &[u8]| d.iter().position(|&x| x != 0).map_or(0, |i| d.len() - i);
+    let (la, lb) = (strip(a), strip(b));
+    la.cmp(&lb)
+        .then_with(|| a[a.len() - la..].cmp(&b[b.len() - lb..]))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let (mut ia, mut ib, mut carry) = (a.iter().rev(), b.iter().rev(), 0);
+    loop {
+        let (x, y) = (ia.next(), ib.next());
+        if x.is_none() && y.is_none() {
+            break;
+        }
+        let sum = x.unwrap_or(&0) + y.unwrap_or(&0) + carry;
+        out.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        out.push(carry);
+    }
+    out.reverse();
+    out
+}
+
+/// `a - b`, for `a >= b`.
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(a.len());
+    let (mut ib, mut borrow) = (b.iter().rev(), 0);
+    for &x in a.iter().rev() {
+        let y = ib.next().copied().unwrap_or(0) + borrow;
+        if x >= y {
+            out.push(x - y);
+            borrow = 0;
+        } else {
+            out.push(x + 10 - y);
+            borrow = 1;
+        }
+    }
+    out.reverse();
+    out
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        Decimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_without_losing_digits() {
+        let money = "12345678901234567890123456.123456789012";
+        assert_eq!(d(money).to_string(), money);
+        assert_eq!(
+            d("18446744073709551615").to_string(),
+            "18446744073709551615"
+        );
+        assert_eq!(d("-0.0150").to_string(), "-0.0150");
+        assert_eq!(d("1.5e3").to_string(), "1500");
+        assert_eq!(d("15e-4").to_string(), "0.0015");
+        assert_eq!(d("-0").to_string(), "0");
+        for bad in ["", "-", ".", "NaN", "Infinity", "1.2.3", "1e", "0x10"] {
+            assert!(Decimal::parse(bad).is_none(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn rejects_unbounded_exponents_and_digits() {
+        assert!(Decimal::parse("1e-99999999999").is_none());
+        assert!(Decimal::parse("1e9223372036854775807").is_none());
+        assert!(Decimal::parse(&"9".repeat(MAX_DIGITS + 1)).is_none());
+        assert_eq!(d("1e-147455").scale(), 147_455);
+        // The widest accepted scales still align.
+        let diff = d("1e147455").abs_diff(&d("1e-147455")).unwrap();
+        assert!(diff > d("1"));
+    }
+
+    #[test]
+    fn compares_by_exact_value() {
+        assert_eq!(d("1.50"), d("1.5"));
+        assert_eq!(d("1.50").scale(), 2);
+        assert_eq!(d("0.00"), d("-0"));
+        assert!(d("0.1000000000000000001") > d("0.1"));
+        assert!(d("-2") < d("-1.5"));
+        assert!(d("-1") < d("0.001"));
+        assert!(d("99.9") < d("100"));
+        assert!(d("18446744073709551615") > d("9223372036854775807"));
+        assert_eq!(d("5.00").normalized().to_string(), "5");
+    }
+
+    #[test]
+    fn rounds_and_subtracts_exactly() {
+        assert_eq!(d("1.005").round(2).to_string(), "1.01");
+        assert_eq!(d("-1.005").round(2).to_string(), "-1.01");
+        assert_eq!(d("9.996").round(2).to_string(), "10.00");
+        assert_eq!(d("0.004").round(2).to_string(), "0.00");
+        assert_eq!(d("0.0004").round(2).to_string(), "0.00");
+        assert_eq!(d("1.5").round(3).to_string(), "1.5");
+
+        let a = d("12345678901234567890.000000000001");
+        let b = d("12345678901234567890.000000000002");
+        assert_eq!(a.abs_diff(&b).unwrap().to_string(), "0.000000000001");
+        assert_eq!(d("-1.5").abs_diff(&d("2")).unwrap().to_string(), "3.5");
+        assert_eq!(d("2").abs_diff(&d("2.00")).unwrap().to_string(), "0.00");
+    }
+}
@@ -2,6 +2,7 @@ use serde::Serialize;
 
 use crate::domain::changeset::Changeset;
 use crate::domain::conflict::ConflictReport;
+use crate::domain::resolution::AutoResolution;
 
 /// The outcome of a conflict-aware diff run (produced by `ConflictService`).
 ///
@@ -16,7 +17,14 @@ use crate::domain::conflict::ConflictReport;
 pub enum DiffResult {
     /// No concurrent target changes detected — the changeset can be applied
     /// directly. The Step Function proceeds to `AwaitApproval`.
-    Clean(Changeset),
+    Clean {
+        #[serde(flatten)]
+        changeset: Changeset,
+        /// Conflicts settled by the tables' `on_conflict` strategies, already
+        /// folded into `changeset`.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        auto_resolved: Vec<AutoResolution>,
+    },
 
     /// Concurrent target changes were detected. Some or all of them conflict
     /// with source changes on the same rows/columns.
@@ -24,10 +32,14 @@ pub enum DiffResult {
     /// The `changeset` reflects the auto-merged rows (non-conflicting changes
     /// are already resolved). The `conflicts` list must be resolved by the
     /// admin before the changeset can be applied. The Step Function routes to
-    /// `CONFLICT_RESOLUTION`.
+    /// `CONFLICT_RESOLUTION`. Benign delete/delete entries are listed with
+    /// the others but never make a result conflicted on their own.
     Conflicted {
         changeset: Changeset,
         conflicts: Vec<ConflictReport>,
+        /// As for `Clean`: settled, not to be resolved again.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        auto_resolved: Vec<AutoResolution>,
     },
 }
 
@@ -36,21 +48,29 @@ impl DiffResult {
     /// Useful for output writers (JSON/SQL/HTML) that work on the changeset only.
     pub fn changeset(&self) -> &Changeset {
         match self {
-            DiffResult::Clean(cs) => cs,
+            DiffResult::Clean { changeset, .. } => changeset,
             DiffResult::Conflicted { changeset, .. } => changeset,
         }
     }
 
     /// Returns `true` if the result has no conflicts.
     pub fn is_clean(&self) -> bool {
-        matches!(self, DiffResult::Clean(_))
+        matches!(self, DiffResult::Clean { .. })
     }
 
     /// Returns the conflicts slice (empty if clean).
     pub fn conflicts(&self) -> &[ConflictReport] {
         match self {
-            DiffResult::Clean(_) => &[],
+            DiffResult::Clean { .. } => &[],
             DiffResult::Conflicted { conflicts, .. } => conflicts,
         }
     }
+
+    /// Conflicts settled automatically by an `on_conflict` strategy.
+    pub fn auto_resolved(&self) -> &[AutoResolution] {
+        match self {
+            DiffResult::Clean { auto_resolved, .. }
+            | DiffResult::Conflicted { auto_resolved, .. } => auto_resolved,
+        }
+    }
 }
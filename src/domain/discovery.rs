@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// A base table found by schema introspection (see `SchemaIntrospector`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredTable {
+    pub name: String,
+    /// Primary-key columns in key order; empty when the table has none.
+    pub primary_key: Vec<String>,
+}
+
+/// A column found by schema introspection, with its declared default
+/// expression as the database renders it (`now()`, `CURRENT_TIMESTAMP`, …).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredColumn {
+    pub table_name: String,
+    pub name: String,
+    pub default: Option<String>,
+}
+
+/// A discovered table that was left out of the diff, and why
+/// (no primary key, missing on one side, …).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedTable {
+    pub table_name: String,
+    pub reason: String,
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::domain::table_diff::TableDiff;
+
+/// A foreign key between two tables of the target schema: `columns` of
+/// `table` reference a key of `referenced_table`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+}
+
+/// The order in which the statements of a changeset respect its foreign
+/// keys, shared by the SQL script and `diffly apply`.
+///
+/// Statements run in three passes: deletes children first, then updates and
+/// inserts parents first. That leaves two cases no table order can fix, and
+/// where constraints must be deferred to the end of the transaction:
+///
+/// - a foreign-key cycle (self-references included) whose referenced rows
+///   are inserted or deleted;
+/// - an update changing a foreign-key column while the referenced table has
+///   inserts (the new parent may not exist yet) or deletes (the old parent
+///   may already be gone).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementOrder {
+    /// Indexes into the changeset's tables, every referenced table before
+    /// the tables referencing it. Tables on a cycle keep their relative
+    /// order from the changeset.
+    pub tables: Vec<usize>,
+    /// Names of the tables on a foreign-key cycle, in changeset order.
+    pub cyclic: Vec<String>,
+    pub defer_constraints: bool,
+}
+
+impl StatementOrder {
+    /// Order `tables` along `foreign_keys`; keys involving a table outside
+    /// `tables` are ignored. Without foreign keys, the changeset's order.
+    pub fn plan(tables: &[TableDiff], foreign_keys: &[ForeignKey]) -> Self {
+        let index: BTreeMap<&str, usize> = tables
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.table_name.as_str(), i))
+            .collect();
+        // (parent, child, key) of every key between two changeset tables.
+        let edges: Vec<(usize, usize, &ForeignKey)> = foreign_keys
+            .iter()
+            .filter_map(|fk| {
+                let parent = *index.get(fk.referenced_table.as_str())?;
+                let child = *index.get(fk.table.as_str())?;
+                Some((parent, child, fk))
+            })
+            .collect();
+
+        let component = components(tables.len(), &edges);
+        let same_component = |a: usize, b: usize| component[a] == component[b];
+
+        // Kahn's algorithm over the edges between components, lowest index
+        // first so unrelated tables keep the changeset's order.
+        let links: BTreeSet<(usize, usize)> = edges
+            .iter()
+            .filter(|(p, c, _)| !same_component(*p, *c))
+            .map(|(p, c, _)| (*p, *c))
+            .collect();
+        let mut parents = vec![0usize; tables.len()];
+        for (_, child) in &links {
+            parents[*child] += 1;
+        }
+        let mut ready: BTreeSet<usize> = (0..tables.len()).filter(|i| parents[*i] == 0).collect();
+        let mut order = Vec::with_capacity(tables.len());
+        while let Some(next) = ready.pop_first() {
+            order.push(next);
+            for (_, child) in links.range((next, 0)..=(next, usize::MAX)) {
+                parents[*child] -= 1;
+                if parents[*child] == 0 {
+                    ready.insert(*child);
+                }
+            }
+        }
+
+        let is_cyclic = |i: usize| {
+            edges
+                .iter()
+                .any(|(p, c, _)| (*p == i || *c == i) && same_component(*p, *c))
+        };
+        let cyclic = (0..tables.len())
+            .filter(|i| is_cyclic(*i))
+            .map(|i| tables[i].table_name.clone())
+            .collect();
+
+        let rows_come_and_go = |t: &TableDiff| !t.inserts.is_empty() || !t.deletes.is_empty();
+        let defer_constraints = edges.iter().any(|(p, c, fk)| {
+            let (parent, child) = (&tables[*p], &tables[*c]);
+            let child_moves = child.updates.iter().any(|u| {
+                u.changed_columns
+                    .iter()
+                    .any(|col| fk.columns.contains(&col.column))
+            });
+            rows_come_and_go(parent)
+                && (child_moves || (same_component(*p, *c) && rows_come_and_go(child)))
+        });
+
+        Self {
+            tables: order,
+            cyclic,
+            defer_constraints,
+        }
+    }
+
+    /// Table indexes in the order their deletes run: children first.
+    pub fn deletes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.tables.iter().rev().copied()
+    }
+}
+
+/// Strongly connected component of every node, by mutual reachability.
+/// Changesets hold tens of tables, not thousands: no need for Tarjan.
+fn components(nodes: usize, edges: &[(usize, usize, &ForeignKey)]) -> Vec<usize> {
+    let mut children = vec![Vec::new(); nodes];
+    for (parent, child, _) in edges {
+        children[*parent].push(*child);
+    }
+    let reachable: Vec<BTreeSet<usize>> = (0..nodes)
+        .map(|start| {
+            let mut seen = BTreeSet::from([start]);
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for next in &children[node] {
+                    if seen.insert(*next) {
+                        stack.push(*next);
+                    }
+                }
+            }
+            seen
+        })
+        .collect();
+    (0..nodes)
+        .map(|i| {
+            (0..nodes)
+                .find(|j| reachable[i].contains(j) && reachable[*j].contains(&i))
+                .unwrap_or(i)
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate};
+    use serde_json::json;
+
+    fn table(name: &str) -> TableDiff {
+        TableDiff {
+            table_name: name.to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![],
+            updates: vec![],
+            deletes: vec![],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        }
+    }
+
+    fn row() -> RowChange {
+        RowChange {
+            pk: [("id".to_string(), json!(1))].into(),
+            data: [("id".to_string(), json!(1))].into(),
+        }
+    }
+
+    fn fk(table: &str, column: &str, referenced_table: &str) -> ForeignKey {
+        ForeignKey {
+            table: table.to_string(),
+            columns: vec![column.to_string()],
+            referenced_table: referenced_table.to_string(),
+        }
+    }
+
+    #[test]
+    fn parents_come_first_and_children_are_deleted_first() {
+        let mut items = table("order_items");
+        items.inserts.push(row());
+        let mut orders = table("orders");
+        orders.inserts.push(row());
+        orders.deletes.push(row());
+        let tables = [items, orders, table("customers"), table("audit")];
+        let keys = [
+            fk("order_items", "order_id", "orders"),
+            fk("orders", "customer_id", "customers"),
+            fk("orders", "region_id", "regions"),
+        ];
+
+        let order = StatementOrder::plan(&tables, &keys);
+        assert_eq!(order.tables, [2, 1, 0, 3]);
+        assert_eq!(order.deletes().collect::<Vec<_>>(), [3, 0, 1, 2]);
+        assert!(order.cyclic.is_empty());
+        assert!(!order.defer_constraints);
+        assert_eq!(StatementOrder::plan(&tables, &[]).tables, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn moving_a_child_to_a_new_parent_defers_constraints() {
+        let mut orders = table("orders");
+        orders.updates.push(RowUpdate {
+            pk: [("id".to_string(), json!(1))].into(),
+            before: [("customer_id".to_string(), json!(1))].into(),
+            after: [("customer_id".to_string(), json!(2))].into(),
+            changed_columns: vec![ColumnDiff {
+                column: "customer_id".to_string(),
+                before: json!(1),
+                after: json!(2),
+                patch: None,
+            }],
+        });
+        let mut customers = table("customers");
+        let keys = [fk("orders", "customer_id", "customers")];
+        assert!(
+            !StatementOrder::plan(&[orders.clone(), customers.clone()], &keys).defer_constraints
+        );
+
+        customers.inserts.push(row());
+        assert!(StatementOrder::plan(&[orders, customers], &keys).defer_constraints);
+    }
+
+    #[test]
+    fn cycles_are_reported_and_deferred_when_rows_come_and_go() {
+        let mut employees = table("employees");
+        let keys = [
+            fk("employees", "manager_id", "employees"),
+            fk("a", "b_id", "b"),
+            fk("b", "a_id", "a"),
+            fk("c", "a_id", "a"),
+        ];
+        let tables = [table("c"), employees.clone(), table("b"), table("a")];
+        let order = StatementOrder::plan(&tables, &keys);
+        assert_eq!(order.cyclic, ["employees", "b", "a"]);
+        // `c` still follows the cycle it references.
+        assert_eq!(order.tables, [1, 2, 3, 0]);
+        assert!(!order.defer_constraints);
+
+        employees.inserts.push(row());
+        let order = StatementOrder::plan(&[employees], &keys);
+        assert!(order.defer_constraints);
+    }
+}
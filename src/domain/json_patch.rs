@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// JsonPath
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A location inside a JSON document, written the JSONPath way:
+/// `$.meta.last_sync`, `$.items[0].price`, `$.items[*].ts`, `$['a.b']`.
+///
+/// Only plain member and index steps are supported (no filters, slices or
+/// recursive descent); `*` matches every member of an object or array.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct JsonPath {
+    text: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Member(String),
+    Index(usize),
+    Wildcard,
+}
+
+impl JsonPath {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = |why: &str| format!("invalid JSON path {:?}: {}", text, why);
+        let rest = text
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with `$`"))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut steps = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    let start = i + 1;
+                    i = start;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    steps.push(match name.as_str() {
+                        "" => return Err(invalid("empty member name")),
+                        "*" => Step::Wildcard,
+                        _ => Step::Member(name),
+                    });
+                }
+                '[' => {
+                    let close = chars[i..]
+                        .iter()
+                        .position(|&c| c == ']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| invalid("unclosed `[`"))?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+                    let inner = inner.trim();
+                    steps.push(if inner == "*" {
+                        Step::Wildcard
+                    } else if let Some(name) = quoted(inner) {
+                        Step::Member(name.to_string())
+                    } else {
+                        Step::Index(
+                            inner
+                                .parse()
+                                .map_err(|_| invalid("expected an index, `*` or a quoted name"))?,
+                        )
+                    });
+                    i = close + 1;
+                }
+                c => return Err(invalid(&format!("unexpected {:?}", c))),
+            }
+        }
+        Ok(Self {
+            text: text.trim().to_string(),
+            steps,
+        })
+    }
+
+    /// Remove what the path designates from `doc`. Removing an array element
+    /// shifts the following ones, as on both sides of a comparison.
+    pub fn remove_from(&self, doc: &mut Value) {
+        if let Some((last, parents)) = self.steps.split_last() {
+            remove(doc, parents, last);
+        }
+    }
+}
+
+impl TryFrom<String> for JsonPath {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        Self::parse(&text)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn quoted(s: &str) -> Option<&str> {
+    s.strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .or_else(|| s.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+}
+
+fn remove(doc: &mut Value, parents: &[Step], last: &Step) {
+    let Some((step, rest)) = parents.split_first() else {
+        match (doc, last) {
+            (Value::Object(map), Step::Member(name)) => {
+                map.remove(name);
+            }
+            (Value::Object(map), Step::Wildcard) => map.clear(),
+            (Value::Array(items), Step::Index(i)) if *i < items.len() => {
+                items.remove(*i);
+            }
+            (Value::Array(items), Step::Wildcard) => items.clear(),
+            _ => {}
+        }
+        return;
+    };
+    match (doc, step) {
+        (Value::Object(map), Step::Member(name)) => {
+            if let Some(child) = map.get_mut(name) {
+                remove(child, rest, last);
+            }
+        }
+        (Value::Array(items), Step::Index(i)) => {
+            if let Some(child) = items.get_mut(*i) {
+                remove(child, rest, last);
+            }
+        }
+        (Value::Object(map), Step::Wildcard) => {
+            for child in map.values_mut() {
+                remove(child, rest, last);
+            }
+        }
+        (Value::Array(items), Step::Wildcard) => {
+            for child in items {
+                remove(child, rest, last);
+            }
+        }
+        _ => {}
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// JSON Patch (RFC 6902)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// One operation of an RFC 6902 JSON Patch. Paths are RFC 6901 JSON
+/// Pointers (`/meta/last_sync`, `/items/0`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+impl PatchOperation {
+    /// `"add"`, `"remove"` or `"replace"`.
+    pub fn op(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Remove { .. } => "remove",
+            Self::Replace { .. } => "replace",
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Add { path, .. } | Self::Remove { path } | Self::Replace { path, .. } => path,
+        }
+    }
+
+    /// The new value; `None` for a removal.
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            Self::Add { value, .. } | Self::Replace { value, .. } => Some(value),
+            Self::Remove { .. } => None,
+        }
+    }
+}
+
+/// The JSON Patch turning `before` into `after`: members are added, removed
+/// or replaced one by one, array elements index by index (trailing elements
+/// appended or removed from the end), anything else replaced as a whole.
+pub fn diff(before: &Value, after: &Value) -> Vec<PatchOperation> {
+    let mut ops = Vec::new();
+    diff_into(&mut String::new(), before, after, &mut ops);
+    ops
+}
+
+fn diff_into(path: &mut String, before: &Value, after: &Value, ops: &mut Vec<PatchOperation>) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            for key in b.keys().filter(|k| !a.contains_key(*k)) {
+                ops.push(PatchOperation::Remove {
+                    path: child_path(path, key),
+                });
+            }
+            for (key, value) in a {
+                match b.get(key) {
+                    Some(old) => {
+                        let len = path.len();
+                        path.push_str(&child_path("", key));
+                        diff_into(path, old, value, ops);
+                        path.truncate(len);
+                    }
+                    None => ops.push(PatchOperation::Add {
+                        path: child_path(path, key),
+                        value: value.clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(b), Value::Array(a)) => {
+            let common = b.len().min(a.len());
+            for i in 0..common {
+                let len = path.len();
+                path.push_str(&format!("/{}", i));
+                diff_into(path, &b[i], &a[i], ops);
+                path.truncate(len);
+            }
+            for (i, value) in a.iter().enumerate().skip(common) {
+                ops.push(PatchOperation::Add {
+                    path: format!("{}/{}", path, i),
+                    value: value.clone(),
+                });
+            }
+            // From the end, so earlier removals don't shift later indices.
+            for i in (common..b.len()).rev() {
+                ops.push(PatchOperation::Remove {
+                    path: format!("{}/{}", path, i),
+                });
+            }
+        }
+        _ => ops.push(PatchOperation::Replace {
+            path: path.clone(),
+            value: after.clone(),
+        }),
+    }
+}
+
+/// `parent` extended with `key`, escaped per RFC 6901 (`~` → `~0`, `/` → `~1`).
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn paths_remove_what_they_designate() {
+        let mut doc = json!({
+            "meta": {"last_sync": "2024-01-01", "owner": "ops"},
+            "items": [{"ts": 1, "sku": "a"}, {"ts": 2, "sku": "b"}],
+            "a.b": 1
+        });
+        for path in [
+            "$.meta.last_sync",
+            "$.items[*].ts",
+            "$['a.b']",
+            "$.missing.x",
+        ] {
+            JsonPath::parse(path).unwrap().remove_from(&mut doc);
+        }
+        assert_eq!(
+            doc,
+            json!({"meta": {"owner": "ops"}, "items": [{"sku": "a"}, {"sku": "b"}]})
+        );
+
+        assert!(JsonPath::parse("meta.x").is_err());
+        assert!(JsonPath::parse("$.items[x]").is_err());
+        assert!(JsonPath::parse("$.items[0").is_err());
+    }
+
+    #[test]
+    fn patch_lists_nested_changes() {
+        let before =
+            json!({"theme": "dark", "meta": {"v": 1, "a/b": true}, "tags": ["x", "y", "z"]});
+        let after = json!({"meta": {"v": 2, "a/b": true}, "tags": ["x", "w"], "lang": "fr"});
+
+        let ops = diff(&before, &after);
+        assert_eq!(
+            serde_json::to_value(&ops).unwrap(),
+            json!([
+                {"op": "remove", "path": "/theme"},
+                {"op": "add", "path": "/lang", "value": "fr"},
+                {"op": "replace", "path": "/meta/v", "value": 2},
+                {"op": "replace", "path": "/tags/1", "value": "w"},
+                {"op": "remove", "path": "/tags/2"}
+            ])
+        );
+        assert_eq!(child_path("", "a~/b"), "/a~0~1b");
+        assert_eq!(
+            diff(&json!([1]), &json!({"a": 1})),
+            vec![PatchOperation::Replace {
+                path: String::new(),
+                value: json!({"a": 1})
+            }]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use serde_json::Value;
+
+/// A contiguous slice of a table in primary-key order.
+///
+/// Holds the keys `k` with `lower < k <= upper`, compared column by column
+/// in the same order rows are streamed (see `StreamDiffer`). A missing bound
+/// is unbounded on that side, so `KeyRange::full()` covers the whole table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyRange {
+    /// Exclusive lower bound (one value per primary-key column).
+    pub lower: Option<Vec<Value>>,
+    /// Inclusive upper bound (one value per primary-key column).
+    pub upper: Option<Vec<Value>>,
+}
+
+impl KeyRange {
+    /// The unbounded range covering every row.
+    pub fn full() -> Self {
+        Self::default()
+    }
+
+    /// Split at `mid` into `(lower, mid]` and `(mid, upper]`.
+    pub fn split_at(self, mid: Vec<Value>) -> (KeyRange, KeyRange) {
+        let left = KeyRange {
+            lower: self.lower,
+            upper: Some(mid.clone()),
+        };
+        let right = KeyRange {
+            lower: Some(mid),
+            upper: self.upper,
+        };
+        (left, right)
+    }
+}
+
+/// Row count and aggregate content hash of a `KeyRange`, computed by the
+/// database. Two ranges with equal checksums hold the same rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeChecksum {
+    pub rows: u64,
+    /// Opaque, dialect-specific digest — only comparable between databases
+    /// using the same driver.
+    pub checksum: String,
+}
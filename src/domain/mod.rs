@@ -1,8 +1,18 @@
+pub mod apply_report;
+pub mod binary;
 pub mod changeset;
+pub mod compare_rules;
 pub mod conflict;
+pub mod decimal;
 pub mod diff_result;
+pub mod discovery;
 pub mod fingerprint;
+pub mod foreign_key;
+pub mod json_patch;
+pub mod key_range;
 pub mod ports;
+pub mod resolution;
+pub mod row_key;
 pub mod snapshot;
 pub mod table_diff;
 pub mod value_objects;
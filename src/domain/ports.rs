@@ -1,10 +1,19 @@
 use crate::domain::{
+    apply_report::ApplyReport,
     changeset::Changeset,
-    table_diff::{RowMap, TableDiff},
+    discovery::{DiscoveredColumn, DiscoveredTable},
+    foreign_key::ForeignKey,
+    key_range::{KeyRange, RangeChecksum},
+    table_diff::{ColumnTypes, RowMap, TableDiff},
     value_objects::{ColumnName, ExcludedColumns, Schema, TableName},
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use serde_json::Value;
+
+/// Rows of a single table, yielded one at a time in primary-key order.
+pub type RowStream = BoxStream<'static, Result<RowMap>>;
 
 /// Port: access to data in a table (implemented by SqlxRowRepository)
 #[async_trait]
@@ -16,6 +25,102 @@ pub trait RowRepository: Send + Sync {
         pk_cols: &[ColumnName],
         excluded: &ExcludedColumns,
     ) -> Result<Vec<RowMap>>;
+
+    /// Stream the rows of a table in primary-key order (see `StreamDiffer`
+    /// for the exact ordering contract).
+    ///
+    /// The default implementation wraps `fetch_rows`, so it offers no memory
+    /// benefit — repositories backed by a database should override it.
+    async fn stream_rows(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+    ) -> Result<RowStream> {
+        let rows = self.fetch_rows(schema, table, pk_cols, excluded).await?;
+        Ok(Box::pin(stream::iter(rows.into_iter().map(Ok))))
+    }
+
+    /// SQL type of every column of a table, spelled as a cast target (see
+    /// `TableDiff::column_types`). The default knows no type, which leaves
+    /// generated statements with untyped literals.
+    async fn column_sql_types(&self, _schema: &Schema, _table: &TableName) -> Result<ColumnTypes> {
+        Ok(ColumnTypes::new())
+    }
+
+    /// Every foreign key between two tables of `schema`, so generated
+    /// statements can be ordered along them. The default knows none.
+    async fn foreign_keys(&self, _schema: &Schema) -> Result<Vec<ForeignKey>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Port: server-side range checksums used by bisection (implemented by
+/// SqlxRowRepository)
+///
+/// Ranges and offsets follow the key order described on `StreamDiffer`.
+#[async_trait]
+pub trait RangeRepository: Send + Sync {
+    /// Row count and content hash of `range`, excluding `excluded` columns.
+    /// Returns `None` when the database cannot hash rows server-side
+    /// (e.g. SQLite), in which case callers fall back to fetching rows.
+    async fn checksum_range(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+        range: &KeyRange,
+    ) -> Result<Option<RangeChecksum>>;
+
+    /// Primary key of the row at position `offset` (0-based) within `range`,
+    /// or `None` if the range holds fewer rows.
+    async fn key_at(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        range: &KeyRange,
+        offset: u64,
+    ) -> Result<Option<Vec<Value>>>;
+
+    /// All rows of `range`, in key order.
+    async fn fetch_range(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+        range: &KeyRange,
+    ) -> Result<Vec<RowMap>>;
+}
+
+/// Port: lists the tables of a schema with their primary keys and columns
+/// (implemented by SqlxRowRepository)
+#[async_trait]
+pub trait SchemaIntrospector: Send + Sync {
+    /// Every base table of `schema`, ordered by name.
+    async fn discover_tables(&self, schema: &Schema) -> Result<Vec<DiscoveredTable>>;
+
+    /// Every column of every base table of `schema`, ordered by table name
+    /// then column position.
+    async fn describe_columns(&self, schema: &Schema) -> Result<Vec<DiscoveredColumn>>;
+}
+
+/// Port: executes a changeset against a database (implemented by SqlxRowRepository)
+///
+/// Implementations run every delete/update/insert inside one transaction and
+/// roll back unless each statement affected exactly one row. With `dry_run`
+/// the statements are executed and then always rolled back.
+#[async_trait]
+pub trait ChangesetApplier: Send + Sync {
+    async fn apply(
+        &self,
+        schema: &Schema,
+        changeset: &Changeset,
+        dry_run: bool,
+    ) -> Result<ApplyReport>;
 }
 
 /// Port: table diff algorithm (implemented by TableDiffer)
@@ -29,10 +134,32 @@ pub trait Differ: Send + Sync {
     ) -> TableDiff;
 }
 
+/// Port: table diff over two PK-ordered row streams (implemented by MergeJoinDiffer)
+///
+/// Both streams must be sorted by primary key in ascending order, comparing
+/// key columns left to right: numbers numerically, strings by their raw
+/// UTF-8 bytes (i.e. the database's binary / `C` collation). Implementations
+/// return an error rather than a wrong diff when a stream is out of order.
+#[async_trait]
+pub trait StreamDiffer: Send + Sync {
+    async fn diff_streams(
+        &self,
+        source: RowStream,
+        target: RowStream,
+        pk_cols: &[ColumnName],
+        table_name: &TableName,
+    ) -> Result<TableDiff>;
+}
+
 /// Port: output formatting (implemented by JsonWriter, SqlWriter, HtmlWriter)
 pub trait OutputWriter: Send + Sync {
     /// Serializes the changeset to a string (JSON, SQL, HTML, etc.)
     fn format(&self, changeset: &Changeset) -> Result<String>;
+    /// The files to write, in order: the formatted changeset by default,
+    /// several parts for a writer splitting its output.
+    fn format_parts(&self, changeset: &Changeset) -> Result<Vec<String>> {
+        Ok(vec![self.format(changeset)?])
+    }
     /// Extension of the produced file (e.g. "json", "sql", "html")
     fn extension(&self) -> &'static str;
 }
@@ -0,0 +1,410 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::domain::changeset::Changeset;
+use crate::domain::conflict::{ConflictKind, ConflictReport};
+use crate::domain::decimal::Decimal;
+use crate::domain::table_diff::RowMap;
+
+/// Which value a resolved conflict keeps.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Choice {
+    /// The source change.
+    Source,
+    /// The concurrent target change.
+    Target,
+    /// The value at clone time, discarding both changes.
+    Base,
+    /// `custom_value`.
+    Custom,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Source => "source",
+            Self::Target => "target",
+            Self::Base => "base",
+            Self::Custom => "custom",
+        })
+    }
+}
+
+/// One conflict of a [`ResolutionFile`], with the admin's decision.
+///
+/// The values are those of the [`ConflictReport`]: a column's values for an
+/// update/update conflict, whole rows (or `null` where the row does not
+/// exist) for every other kind.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Resolution {
+    pub kind: ConflictKind,
+    pub table_name: String,
+    pub pk: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    pub base_value: Value,
+    pub source_value: Value,
+    pub target_value: Value,
+    /// `null` until decided.
+    pub choice: Option<Choice>,
+    /// The value kept with `"choice": "custom"`: a column value, or for a
+    /// row-level conflict the whole row (`null` for no row).
+    #[serde(default)]
+    pub custom_value: Value,
+}
+
+impl Resolution {
+    /// The value to keep, `None` while undecided.
+    pub fn chosen_value(&self) -> Option<&Value> {
+        Some(match self.choice? {
+            Choice::Source => &self.source_value,
+            Choice::Target => &self.target_value,
+            Choice::Base => &self.base_value,
+            Choice::Custom => &self.custom_value,
+        })
+    }
+
+    /// `table pk=… column`, to point at an entry in messages.
+    pub fn describe(&self) -> String {
+        let pk = self
+            .pk
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match &self.column {
+            Some(column) => format!("{} ({}) column {}", self.table_name, pk, column),
+            None => format!("{} ({})", self.table_name, pk),
+        }
+    }
+}
+
+impl From<&ConflictReport> for Resolution {
+    fn from(c: &ConflictReport) -> Self {
+        Self {
+            kind: c.kind,
+            table_name: c.table_name.clone(),
+            pk: c.pk.clone(),
+            column: c.column.clone(),
+            base_value: c.base_value.clone(),
+            source_value: c.source_value.clone(),
+            target_value: c.target_value.clone(),
+            choice: None,
+            custom_value: Value::Null,
+        }
+    }
+}
+
+/// The conflicts of a changeset, to be decided by editing each `choice`,
+/// then folded back into the changeset by `diffly resolve`.
+///
+/// The changeset travels with its conflicts, so the file is all `resolve`
+/// needs: no database is read again.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolutionFile {
+    pub resolutions: Vec<Resolution>,
+    pub changeset: Changeset,
+}
+
+impl ResolutionFile {
+    /// One undecided entry per conflict needing a decision (benign
+    /// delete/delete conflicts are left out).
+    pub fn new(changeset: Changeset, conflicts: &[ConflictReport]) -> Self {
+        Self {
+            resolutions: conflicts
+                .iter()
+                .filter(|c| c.is_blocking())
+                .map(Resolution::from)
+                .collect(),
+            changeset,
+        }
+    }
+
+    /// Entries whose `choice` is still `null`.
+    pub fn undecided(&self) -> impl Iterator<Item = &Resolution> {
+        self.resolutions.iter().filter(|r| r.choice.is_none())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Automatic resolution
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A standing rule settling a conflict without asking: `"source"`,
+/// `"target"`, `"base"`, `"latest_by:<column>"` or `"fail"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ResolutionStrategy {
+    /// Keep the source change.
+    Source,
+    /// Keep the concurrent target change.
+    Target,
+    /// Keep the value at clone time.
+    Base,
+    /// Keep the row whose column is the greater (e.g. the newest
+    /// `updated_at`). A tie, or a side without the row or a value, leaves
+    /// the conflict for a decision, see [`Self::undecided_reason`].
+    LatestBy(String),
+    /// Always leave the conflict for a decision, e.g. to exempt a column
+    /// from its table's strategy.
+    Fail,
+}
+
+impl ResolutionStrategy {
+    /// The side this strategy keeps for a conflict between the `source` and
+    /// `target` rows (`None` where the row does not exist); `None` when it
+    /// cannot decide.
+    pub fn choose(&self, source: Option<&RowMap>, target: Option<&RowMap>) -> Option<Choice> {
+        match self {
+            Self::Source => Some(Choice::Source),
+            Self::Target => Some(Choice::Target),
+            Self::Base => Some(Choice::Base),
+            Self::Fail => None,
+            Self::LatestBy(column) => latest_by(column, source, target).ok(),
+        }
+    }
+
+    /// Why `latest_by` cannot decide between the `source` and `target` rows,
+    /// naming its column; `None` when it decides, and for other strategies.
+    pub fn undecided_reason(
+        &self,
+        source: Option<&RowMap>,
+        target: Option<&RowMap>,
+    ) -> Option<String> {
+        match self {
+            Self::LatestBy(column) => latest_by(column, source, target).err(),
+            _ => None,
+        }
+    }
+}
+
+/// The side whose `column` is the greater, or why there is none.
+fn latest_by(
+    column: &str,
+    source: Option<&RowMap>,
+    target: Option<&RowMap>,
+) -> Result<Choice, String> {
+    fn value<'a>(column: &str, side: &str, row: Option<&'a RowMap>) -> Result<&'a Value, String> {
+        match row.map(|r| r.get(column)) {
+            None => Err(format!("latest_by:{}: no {} row", column, side)),
+            Some(None) => Err(format!(
+                "latest_by:{}: the {} row has no column {:?}",
+                column, side, column
+            )),
+            Some(Some(Value::Null)) => Err(format!("latest_by:{}: NULL on {}", column, side)),
+            Some(Some(v)) => Ok(v),
+        }
+    }
+    let source = value(column, "source", source)?;
+    let target = value(column, "target", target)?;
+    match compare_latest(source, target) {
+        Some(Ordering::Greater) => Ok(Choice::Source),
+        Some(Ordering::Less) => Ok(Choice::Target),
+        Some(Ordering::Equal) => Err(format!("latest_by:{}: both sides hold {}", column, source)),
+        None => Err(format!(
+            "latest_by:{}: cannot order {} and {}",
+            column, source, target
+        )),
+    }
+}
+
+impl FromStr for ResolutionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(Self::Source),
+            "target" => Ok(Self::Target),
+            "base" => Ok(Self::Base),
+            "fail" => Ok(Self::Fail),
+            _ => match s.strip_prefix("latest_by:") {
+                Some(column) if !column.is_empty() => Ok(Self::LatestBy(column.to_string())),
+                _ => Err(format!(
+                    "unknown resolution strategy {:?} (expected source, target, base, \
+                     latest_by:<column> or fail)",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ResolutionStrategy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for ResolutionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source => f.write_str("source"),
+            Self::Target => f.write_str("target"),
+            Self::Base => f.write_str("base"),
+            Self::LatestBy(column) => write!(f, "latest_by:{}", column),
+            Self::Fail => f.write_str("fail"),
+        }
+    }
+}
+
+impl From<ResolutionStrategy> for String {
+    fn from(strategy: ResolutionStrategy) -> Self {
+        strategy.to_string()
+    }
+}
+
+/// The strategies of one table: one for every conflict, refined per column.
+/// Row-level conflicts only follow `strategy`.
+///
+/// ```toml
+/// [diff.tables.on_conflict]
+/// strategy = "latest_by:updated_at"
+/// columns = { is_active = "target", price = "source" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ResolutionPolicy {
+    pub strategy: Option<ResolutionStrategy>,
+    #[serde(default)]
+    pub columns: BTreeMap<String, ResolutionStrategy>,
+}
+
+impl ResolutionPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.strategy.is_none() && self.columns.is_empty()
+    }
+
+    /// The strategy for a conflict on `column` (`None` for a row-level one).
+    pub fn for_conflict(&self, column: Option<&str>) -> Option<&ResolutionStrategy> {
+        column
+            .and_then(|c| self.columns.get(c))
+            .or(self.strategy.as_ref())
+    }
+}
+
+/// A conflict settled by a [`ResolutionStrategy`], kept for the audit trail.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AutoResolution {
+    #[serde(flatten)]
+    pub conflict: ConflictReport,
+    pub strategy: ResolutionStrategy,
+    /// The side kept.
+    pub choice: Choice,
+}
+
+impl AutoResolution {
+    /// The decision, in the shape `diffly resolve` folds into a changeset.
+    pub fn to_resolution(&self) -> Resolution {
+        Resolution {
+            choice: Some(self.choice),
+            ..Resolution::from(&self.conflict)
+        }
+    }
+}
+
+/// Order two values of a `latest_by` column: numbers by value, dates and
+/// times chronologically, other text as text. `None` for values that do not
+/// compare (a number against a text, …).
+fn compare_latest(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            Some(Decimal::parse(&x.to_string())?.cmp(&Decimal::parse(&y.to_string())?))
+        }
+        (Value::String(x), Value::String(y)) => Some(match (moment(x), moment(y)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => match (Decimal::parse(x), Decimal::parse(y)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+        }),
+        _ => None,
+    }
+}
+
+/// A date, a date and time or an instant (taken in UTC), as naive UTC.
+fn moment(s: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.naive_utc())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(updated_at: Value) -> RowMap {
+        [("updated_at".to_string(), updated_at)].into()
+    }
+
+    #[test]
+    fn strategies_parse_and_print() {
+        for s in ["source", "target", "base", "fail", "latest_by:updated_at"] {
+            assert_eq!(s.parse::<ResolutionStrategy>().unwrap().to_string(), s);
+        }
+        assert!("latest_by:".parse::<ResolutionStrategy>().is_err());
+        assert!("newest".parse::<ResolutionStrategy>().is_err());
+    }
+
+    #[test]
+    fn latest_by_keeps_the_newest_side() {
+        let latest = ResolutionStrategy::LatestBy("updated_at".to_string());
+        let choose = |s: Value, t: Value| latest.choose(Some(&row(s)), Some(&row(t)));
+        // Fractional seconds would sort wrong as text.
+        assert_eq!(
+            choose(
+                json!("2024-01-01T10:00:00.500Z"),
+                json!("2024-01-01T10:00:00Z")
+            ),
+            Some(Choice::Source)
+        );
+        assert_eq!(
+            choose(json!("2024-01-01 09:00:00"), json!("2024-01-01 10:00:00")),
+            Some(Choice::Target)
+        );
+        assert_eq!(choose(json!(10), json!(9)), Some(Choice::Source));
+        assert_eq!(choose(json!(1), json!(1)), None);
+        assert_eq!(choose(json!(null), json!(1)), None);
+        assert_eq!(latest.choose(None, Some(&row(json!(1)))), None);
+    }
+
+    #[test]
+    fn latest_by_names_its_column_when_undecided() {
+        let latest = ResolutionStrategy::LatestBy("updated_at".to_string());
+        let reason = |s: Option<&RowMap>, t: Option<&RowMap>| latest.undecided_reason(s, t);
+        let (one, null) = (row(json!(1)), row(json!(null)));
+
+        assert_eq!(reason(Some(&row(json!(2))), Some(&one)), None);
+        let unknown = ResolutionStrategy::LatestBy("modified".to_string());
+        let msg = unknown.undecided_reason(Some(&one), Some(&one)).unwrap();
+        assert!(msg.contains("no column \"modified\""), "{msg}");
+        let msg = reason(Some(&one), Some(&null)).unwrap();
+        assert!(
+            msg.contains("updated_at") && msg.contains("NULL on target"),
+            "{msg}"
+        );
+        let msg = reason(None, Some(&one)).unwrap();
+        assert!(msg.contains("no source row"), "{msg}");
+        assert!(reason(Some(&one), Some(&one))
+            .unwrap()
+            .contains("both sides"));
+        assert_eq!(ResolutionStrategy::Fail.undecided_reason(None, None), None);
+    }
+}
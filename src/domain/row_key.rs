@@ -0,0 +1,196 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::domain::decimal::Decimal;
+use crate::domain::table_diff::RowMap;
+use crate::domain::value_objects::ColumnName;
+
+/// Composite primary key of a row: one value per key column, in key order.
+///
+/// The values keep their JSON type instead of being flattened into a string,
+/// so keys containing separators (`("a|b", "c")` vs `("a", "b|c")`), the
+/// text `"NULL"` vs an actual NULL, or the string `"1"` vs the number `1`
+/// never collide. Keys are ordered column by column like streamed rows (see
+/// [`compare_keys`]), which makes `RowKey` usable as a `BTreeMap` key.
+#[derive(Debug, Clone)]
+pub struct RowKey(Vec<Value>);
+
+impl RowKey {
+    /// The key of `row`; a key column missing from the row reads as NULL.
+    pub fn from_row(row: &RowMap, pk_cols: &[ColumnName]) -> Self {
+        Self(
+            pk_cols
+                .iter()
+                .map(|c| row.get(&c.0).cloned().unwrap_or(Value::Null))
+                .collect(),
+        )
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.0
+    }
+
+    pub fn into_values(self) -> Vec<Value> {
+        self.0
+    }
+
+    /// `true` when a key column is NULL: such a key matches no row in SQL.
+    pub fn has_null(&self) -> bool {
+        self.0.iter().any(Value::is_null)
+    }
+}
+
+impl From<Vec<Value>> for RowKey {
+    fn from(values: Vec<Value>) -> Self {
+        Self(values)
+    }
+}
+
+impl PartialEq for RowKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RowKey {}
+
+impl PartialOrd for RowKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RowKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_keys(&self.0, &other.0)
+    }
+}
+
+/// Compare two composite keys column by column (see `StreamDiffer`).
+pub fn compare_keys(a: &[Value], b: &[Value]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| compare_key_values(x, y))
+        .find(|o| o.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// Numbers compare by exact decimal value and strings bytewise; values of different
+/// kinds order NULL < bool < number < string < anything else.
+fn compare_key_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) | Value::Object(_) => 4,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => {
+            if let (Some(x), Some(y)) = (x.as_i64(), y.as_i64()) {
+                x.cmp(&y)
+            } else if let (Some(x), Some(y)) = (x.as_u64(), y.as_u64()) {
+                x.cmp(&y)
+            } else {
+                match (
+                    Decimal::parse(&x.to_string()),
+                    Decimal::parse(&y.to_string()),
+                ) {
+                    (Some(x), Some(y)) => x.cmp(&y),
+                    _ => x.to_string().cmp(&y.to_string()),
+                }
+            }
+        }
+        (Value::String(x), Value::String(y)) => x.as_bytes().cmp(y.as_bytes()),
+        _ => rank(a)
+            .cmp(&rank(b))
+            .then_with(|| a.to_string().cmp(&b.to_string())),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    fn key(values: Value) -> RowKey {
+        match values {
+            Value::Array(v) => RowKey::from(v),
+            other => panic!("expected an array, got {}", other),
+        }
+    }
+
+    #[test]
+    fn separators_inside_values_do_not_collide() {
+        assert_ne!(key(json!(["a|b", "c"])), key(json!(["a", "b|c"])));
+        assert_ne!(key(json!(["a,b", "c"])), key(json!(["a", "b,c"])));
+    }
+
+    #[test]
+    fn values_of_different_types_do_not_collide() {
+        assert_ne!(key(json!(["1"])), key(json!([1])));
+        assert_ne!(key(json!(["true"])), key(json!([true])));
+        assert_ne!(key(json!(["NULL"])), key(json!([null])));
+        assert_ne!(key(json!(["null"])), key(json!([null])));
+        assert_eq!(key(json!([1, "x"])), key(json!([1, "x"])));
+    }
+
+    #[test]
+    fn decimal_keys_compare_exactly() {
+        let n = |s: &str| Value::Number(s.parse().unwrap());
+        let big = key(Value::Array(vec![n("18446744073709551615")]));
+        let bigger = key(Value::Array(vec![n("18446744073709551616.5")]));
+        assert!(big < bigger);
+        assert!(
+            key(Value::Array(vec![n("0.1000000000000000001")])) > key(Value::Array(vec![n("0.1")]))
+        );
+        assert_eq!(
+            key(Value::Array(vec![n("1.50")])),
+            key(Value::Array(vec![n("1.5")]))
+        );
+    }
+
+    #[test]
+    fn missing_key_column_reads_as_null() {
+        let pk = [ColumnName("a".into()), ColumnName("b".into())];
+        let row: RowMap = [("a".to_string(), json!("NULL"))].into_iter().collect();
+        assert_eq!(
+            RowKey::from_row(&row, &pk).values(),
+            &[json!("NULL"), Value::Null]
+        );
+    }
+
+    #[test]
+    fn keys_order_column_by_column() {
+        let keys: BTreeSet<RowKey> = [
+            json!(["b", 1]),
+            json!([null, 9]),
+            json!(["a", 2]),
+            json!(["a", 10]),
+            json!([5, 0]),
+        ]
+        .into_iter()
+        .map(key)
+        .collect();
+        let ordered: Vec<Value> = keys.into_iter().map(|k| json!(k.into_values())).collect();
+        assert_eq!(
+            ordered,
+            vec![
+                json!([null, 9]),
+                json!([5, 0]),
+                json!(["a", 2]),
+                json!(["a", 10]),
+                json!(["b", 1]),
+            ]
+        );
+    }
+}
@@ -1,26 +1,50 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::domain::json_patch::{self, PatchOperation};
+use crate::domain::row_key::RowKey;
+use crate::domain::value_objects::ColumnName;
 
 /// Type alias for a database row represented as a sorted map of column name → JSON value.
 pub type RowMap = BTreeMap<String, Value>;
 
-#[derive(Debug, Serialize, Clone)]
+/// Column name → SQL type as the target database spells it in a cast
+/// (`uuid`, `integer[]`, `order_status`, `datetime(6)`).
+pub type ColumnTypes = BTreeMap<String, String>;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TableDiff {
     pub table_name: String,
+    /// Empty for a keyless table: only inserts and deletes of whole rows.
     pub primary_key: Vec<String>,
     pub inserts: Vec<RowChange>,
     pub updates: Vec<RowUpdate>,
     pub deletes: Vec<RowChange>,
+    /// Rows violating the configured primary key (duplicate or NULL key
+    /// values). They are left out of `inserts` / `updates` / `deletes`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_quality: Vec<DataQualityFinding>,
+    /// SQL types of the target table's columns, so generated statements can
+    /// cast their literals. Empty when the driver has no catalog to ask
+    /// (SQLite): literals are then left to the database's implicit casts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_types: ColumnTypes,
+    /// The table's `excluded_columns`: no row carries them, so a deleted row
+    /// cannot be inserted back whole.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_columns: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RowChange {
+    /// Empty for a row of a keyless table: `data` is all there is to match on.
     pub pk: BTreeMap<String, Value>,
     pub data: RowMap,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RowUpdate {
     pub pk: BTreeMap<String, Value>,
     pub before: RowMap,
@@ -28,15 +52,130 @@ pub struct RowUpdate {
     pub changed_columns: Vec<ColumnDiff>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ColumnDiff {
     pub column: String,
     pub before: Value,
     pub after: Value,
+    /// For a JSON document: the RFC 6902 patch turning `before` into
+    /// `after`, leaving out the column's ignored paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Vec<PatchOperation>>,
+}
+
+/// The configured primary key does not identify the rows of one side: no
+/// statement could address them unambiguously, so they are reported instead
+/// of diffed. Typical for logical (unenforced) keys on legacy tables.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DataQualityFinding {
+    pub kind: DataQualityKind,
+    pub side: Side,
+    /// The offending key (NULL components included).
+    pub pk: BTreeMap<String, Value>,
+    /// Every row of `side` holding that key.
+    pub rows: Vec<RowMap>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataQualityKind {
+    /// Several rows share the same key.
+    DuplicateKey,
+    /// At least one key column is NULL.
+    NullKey,
+}
+
+/// The database a row was read from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Source,
+    Target,
+}
+
+impl DataQualityFinding {
+    pub fn new(
+        kind: DataQualityKind,
+        side: Side,
+        pk_cols: &[ColumnName],
+        key: &RowKey,
+        rows: Vec<RowMap>,
+    ) -> Self {
+        Self {
+            kind,
+            side,
+            pk: pk_cols
+                .iter()
+                .zip(key.values())
+                .map(|(c, v)| (c.0.clone(), v.clone()))
+                .collect(),
+            rows,
+        }
+    }
+}
+
+impl fmt::Display for DataQualityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DataQualityKind::DuplicateKey => "duplicate key",
+            DataQualityKind::NullKey => "NULL key",
+        })
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Source => "source",
+            Side::Target => "target",
+        })
+    }
 }
 
 impl TableDiff {
+    /// `true` when the table has no row change (data-quality findings aside).
     pub fn is_empty(&self) -> bool {
         self.inserts.is_empty() && self.updates.is_empty() && self.deletes.is_empty()
     }
+
+    /// The changes undoing this diff once applied: deleted rows are inserted
+    /// back, inserted rows deleted, and updated columns restored to their
+    /// `before` values.
+    pub fn invert(&self) -> TableDiff {
+        TableDiff {
+            table_name: self.table_name.clone(),
+            primary_key: self.primary_key.clone(),
+            inserts: self.deletes.clone(),
+            updates: self.updates.iter().map(RowUpdate::invert).collect(),
+            deletes: self.inserts.clone(),
+            data_quality: self.data_quality.clone(),
+            column_types: self.column_types.clone(),
+            excluded_columns: self.excluded_columns.clone(),
+        }
+    }
+}
+
+impl RowUpdate {
+    /// The update from `after` back to `before`. JSON patches are computed
+    /// anew over the whole document.
+    pub fn invert(&self) -> RowUpdate {
+        RowUpdate {
+            pk: self.pk.clone(),
+            before: self.after.clone(),
+            after: self.before.clone(),
+            changed_columns: self
+                .changed_columns
+                .iter()
+                .map(|c| ColumnDiff {
+                    column: c.column.clone(),
+                    before: c.after.clone(),
+                    after: c.before.clone(),
+                    patch: c
+                        .patch
+                        .as_ref()
+                        .map(|_| json_patch::diff(&c.after, &c.before)),
+                })
+                .collect(),
+        }
+    }
 }
@@ -1,7 +1,11 @@
 use anyhow::{Context, Result};
 use config::{Config, Environment, File, FileFormat, Map};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
+use crate::domain::compare_rules::{CompareRules, TableRules};
+use crate::domain::discovery::SkippedTable;
+use crate::domain::resolution::{ResolutionPolicy, ResolutionStrategy};
 use crate::domain::value_objects::ExcludedColumns;
 
 // ─── Structs ──────────────────────────────────────────────────────────────────
@@ -25,29 +29,199 @@ pub struct DbConfig {
     pub user: String,
     pub password: String,
     pub schema: String,
+    /// Read every table of this database inside a single read-only snapshot,
+    /// so rows related across tables (e.g. by foreign keys) are seen at the
+    /// same point in time. Off by default: it keeps a transaction open for the
+    /// whole run, and on MySQL / MariaDB / SQLite serialises reads onto one
+    /// connection, which rules out [`DiffStrategy::Streaming`].
+    #[serde(default)]
+    pub consistent_read: bool,
 }
 
 fn default_driver() -> String {
     "postgres".to_string()
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize)]
 pub struct DiffConfig {
     #[serde(default)]
     pub tables: Vec<TableConfig>,
+    /// Bisection stops splitting a key range once neither side holds more
+    /// rows than this, and fetches the range for a row-by-row diff.
+    #[serde(default = "default_bisect_leaf_rows")]
+    pub bisect_leaf_rows: u64,
+    /// When set, every table matching the patterns is diffed without being
+    /// listed in `tables`; entries in `tables` override discovered ones.
+    #[serde(default)]
+    pub discover: Option<DiscoverConfig>,
+    /// Exit with code 3 (and refuse to `apply`) when a table has duplicate or
+    /// NULL primary keys. They are reported either way.
+    #[serde(default)]
+    pub fail_on_data_quality: bool,
+    /// Comparison rules for every table (`[diff.compare]`), refined by each
+    /// table's `compare` section.
+    #[serde(default)]
+    pub compare: CompareRules,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            tables: Vec::new(),
+            bisect_leaf_rows: default_bisect_leaf_rows(),
+            discover: None,
+            fail_on_data_quality: false,
+            compare: CompareRules::default(),
+        }
+    }
+}
+
+impl DiffConfig {
+    /// The comparison rules of every table: `[diff.compare]` refined by the
+    /// `compare` section of each `[[diff.tables]]` entry.
+    pub fn compare_rules(&self) -> TableRules {
+        TableRules::new(
+            &self.compare,
+            self.tables.iter().map(|t| (t.name.as_str(), &t.compare)),
+        )
+    }
+
+    /// The `on_conflict` section of every `[[diff.tables]]` entry that has
+    /// one, by table name.
+    pub fn resolution_policies(&self) -> BTreeMap<String, ResolutionPolicy> {
+        self.tables
+            .iter()
+            .filter(|t| !t.on_conflict.is_empty())
+            .map(|t| (t.name.clone(), t.on_conflict.clone()))
+            .collect()
+    }
+}
+
+fn default_bisect_leaf_rows() -> u64 {
+    1000
+}
+
+/// Table auto-discovery (`[diff.discover]`).
+///
+/// Tables and primary keys are read from the database catalog. Patterns are
+/// globs on the table name (`*` matches any run of characters, `?` a single
+/// one); a table is diffed when it matches an `include` pattern and no
+/// `exclude` pattern.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiscoverConfig {
+    #[serde(default = "default_discover_include")]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Default for DiscoverConfig {
+    fn default() -> Self {
+        Self {
+            include: default_discover_include(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+fn default_discover_include() -> Vec<String> {
+    vec!["*".to_string()]
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct TableConfig {
     pub name: String,
+    /// Empty (or omitted) for a table without a key: rows are then compared
+    /// as whole values, see [`DiffStrategy::InMemory`].
+    #[serde(default)]
     pub primary_key: Vec<String>,
     #[serde(default)]
     pub excluded_columns: ExcludedColumns,
+    /// How rows are compared: "in_memory" (default), "streaming" or "bisect".
+    #[serde(default)]
+    pub strategy: DiffStrategy,
+    /// When two values count as equal, on top of `[diff.compare]`.
+    #[serde(default)]
+    pub compare: CompareRules,
+    /// How `check-conflicts` settles this table's conflicts without asking.
+    #[serde(default)]
+    pub on_conflict: ResolutionPolicy,
+}
+
+/// Per-table diff algorithm.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffStrategy {
+    /// Load both sides fully, then index them by primary key.
+    /// Fastest for small and medium tables, and the only strategy for
+    /// keyless tables, which are compared as multisets of full rows.
+    #[default]
+    InMemory,
+    /// Walk both sides as PK-ordered streams in lockstep (merge join).
+    /// Memory stays bounded by the number of changes, not the table size.
+    /// Not available with `consistent_read` on MySQL, MariaDB or SQLite,
+    /// whose single pinned connection cannot be held for a whole table.
+    /// Rows with a NULL key sort anywhere and are held until the end.
+    /// Streamed tables do not contribute to the changeset fingerprints.
+    Streaming,
+    /// Compare server-side checksums of key ranges, split mismatching ranges
+    /// and only fetch the small ranges that differ. Falls back to fetching
+    /// all rows on SQLite and when source and target use different drivers.
+    /// Bisected tables do not contribute to the changeset fingerprints.
+    Bisect,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OutputConfig {
     pub dir: String,
+    /// `[output.sql]`: how the SQL script and the JSON `sql` fields are written.
+    #[serde(default)]
+    pub sql: SqlOutputConfig,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct SqlOutputConfig {
+    /// Every UPDATE / DELETE also asserts the values the diff read, and the
+    /// script fails when one matches no row: a row changed on target since
+    /// the diff is never overwritten.
+    #[serde(default)]
+    pub guarded: bool,
+    /// How inserted rows are written: "plain" (default), "upsert", "merge"
+    /// or "copy".
+    #[serde(default)]
+    pub style: SqlStyle,
+    /// Rows per multi-row `INSERT`, grouped `DELETE … WHERE pk IN (…)` or
+    /// `COPY` section. 0 or 1 (default): one row per statement, one `COPY`
+    /// per table.
+    #[serde(default)]
+    pub batch_size: usize,
+    /// Statements per transaction: the script commits every
+    /// `transaction_size` statements. 0 (default): a single transaction,
+    /// which a changeset needing deferred foreign-key checks must fit in.
+    #[serde(default)]
+    pub transaction_size: usize,
+    /// Write each transaction to its own numbered file.
+    #[serde(default)]
+    pub split_files: bool,
+}
+
+/// Statement style of the inserts of the SQL script.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlStyle {
+    /// `INSERT`: fails on a row that already exists.
+    #[default]
+    Plain,
+    /// `INSERT … ON CONFLICT (pk) DO UPDATE` (PostgreSQL, SQLite) or
+    /// `INSERT … ON DUPLICATE KEY UPDATE` (MySQL, MariaDB): a row inserted
+    /// by an earlier run is overwritten, so the script can be re-run.
+    Upsert,
+    /// `MERGE INTO … WHEN MATCHED … WHEN NOT MATCHED …`, the standard
+    /// equivalent of `Upsert` (PostgreSQL 15+ only).
+    Merge,
+    /// `COPY … FROM STDIN` sections, the fastest bulk load (PostgreSQL only,
+    /// run through psql). Like `Plain`, fails on a row that already exists.
+    Copy,
 }
 
 // ─── URL builder ─────────────────────────────────────────────────────────────
@@ -88,6 +262,92 @@ impl DbConfig {
             ),
         }
     }
+
+    /// Decode the `%XX` escapes of a connection URL component.
+    fn decode(s: &str) -> Result<String> {
+        let bytes = s.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                let hex = s
+                    .get(i + 1..i + 3)
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .with_context(|| format!("Invalid percent-encoding in {:?}", s))?;
+                decoded.push(hex);
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(decoded).with_context(|| format!("Invalid UTF-8 in {:?}", s))
+    }
+
+    /// Parse a connection URL — the inverse of [`DbConfig::url`].
+    ///
+    /// Accepts `postgres://` (or `postgresql://`), `mysql://`, `mariadb://`
+    /// and `sqlite://<path>`. A missing port falls back to the driver default;
+    /// query parameters are ignored. The schema defaults to `public` on
+    /// PostgreSQL and to the database name on MySQL / MariaDB.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .with_context(|| format!("Not a connection URL: {}", url))?;
+        let driver = match scheme {
+            "postgres" | "postgresql" => "postgres",
+            "mysql" => "mysql",
+            "mariadb" => "mariadb",
+            "sqlite" => "sqlite",
+            other => anyhow::bail!("Unsupported URL scheme: {}", other),
+        };
+        let rest = rest.split_once('?').map_or(rest, |(r, _)| r);
+
+        if driver == "sqlite" {
+            anyhow::ensure!(!rest.is_empty(), "Missing database path in {}", url);
+            return Ok(DbConfig {
+                driver: driver.to_string(),
+                host: String::new(),
+                port: 0,
+                dbname: rest.to_string(),
+                user: String::new(),
+                password: String::new(),
+                schema: "main".to_string(),
+                consistent_read: false,
+            });
+        }
+
+        let (authority, dbname) = rest.split_once('/').unwrap_or((rest, ""));
+        let dbname = Self::decode(dbname)?;
+        anyhow::ensure!(!dbname.is_empty(), "Missing database name in {}", url);
+
+        let (userinfo, hostport) = authority.rsplit_once('@').unwrap_or(("", authority));
+        let (user, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+        let default_port = if driver == "postgres" { 5432 } else { 3306 };
+        let (host, port) = match hostport.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse()
+                    .with_context(|| format!("Invalid port in {}", url))?,
+            ),
+            _ => (hostport, default_port),
+        };
+
+        Ok(DbConfig {
+            driver: driver.to_string(),
+            host: host.to_string(),
+            port,
+            user: Self::decode(user)?,
+            password: Self::decode(password)?,
+            schema: if driver == "postgres" {
+                "public".to_string()
+            } else {
+                dbname.clone()
+            },
+            dbname,
+            consistent_read: false,
+        })
+    }
 }
 
 // ─── Layered loading (Viper-style) ───────────────────────────────────────────
@@ -184,8 +444,156 @@ impl AppConfig {
             .try_deserialize::<AppConfig>()
             .context("Failed to deserialize configuration")?;
 
+        cfg.validate()?;
         Ok(cfg)
     }
+
+    /// Reject settings that cannot work together.
+    fn validate(&self) -> Result<()> {
+        // A pinned session reads every table through one connection, which
+        // cannot be held open for a whole merge join: streaming would fall
+        // back to loading the table in memory.
+        for (side, db) in [("source", &self.source), ("target", &self.target)] {
+            if !db.consistent_read || db.driver == "postgres" {
+                continue;
+            }
+            if let Some(table) = self
+                .diff
+                .tables
+                .iter()
+                .find(|t| t.strategy == DiffStrategy::Streaming)
+            {
+                anyhow::bail!(
+                    "Table '{}' uses strategy = \"streaming\", which {} consistent_read \
+                     does not support on {}: use \"in_memory\" or \"bisect\", or turn \
+                     consistent_read off",
+                    table.name,
+                    side,
+                    db.driver
+                );
+            }
+        }
+
+        // An excluded column is never read: `latest_by` could not decide.
+        for table in &self.diff.tables {
+            let policy = &table.on_conflict;
+            for strategy in policy.strategy.iter().chain(policy.columns.values()) {
+                if let ResolutionStrategy::LatestBy(column) = strategy {
+                    if table.excluded_columns.contains(column) {
+                        anyhow::bail!(
+                            "Table '{}': on_conflict \"{}\" names an excluded column",
+                            table.name,
+                            strategy
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `true` when source and target use different drivers. Rows are then
+    /// decoded into a driver-neutral form so equal values compare equal.
+    pub fn is_cross_driver(&self) -> bool {
+        self.source.driver != self.target.driver
+    }
+}
+
+// ─── Config generation (`diffly init`) ───────────────────────────────────────
+
+/// Render a commented `diffly.toml` for `source` → `target` with one
+/// `[[diff.tables]]` block per table. `keyless` tables are listed as
+/// commented-out blocks for the user to complete with a key.
+pub fn render_config(
+    source: &DbConfig,
+    target: &DbConfig,
+    tables: &[TableConfig],
+    keyless: &[SkippedTable],
+) -> String {
+    let mut out = String::new();
+    out.push_str("# diffly configuration — generated by `diffly init`.\n");
+    out.push_str("#\n");
+    out.push_str("# Every value can be overridden by an environment variable, e.g.\n");
+    out.push_str("# DIFFLY_SOURCE__PASSWORD or DIFFLY_TARGET__HOST: prefer them for secrets.\n");
+
+    render_db(
+        &mut out,
+        "source",
+        "Database holding the changes to ship",
+        source,
+    );
+    render_db(
+        &mut out,
+        "target",
+        "Database the changeset is applied to",
+        target,
+    );
+
+    out.push_str("\n[output]\n");
+    out.push_str("dir = \"./output\"\n");
+    out.push_str("# Make every UPDATE / DELETE of the generated SQL fail on a row changed\n");
+    out.push_str("# on target since the diff:\n");
+    out.push_str("# sql = { guarded = true }\n");
+    out.push_str(
+        "# Re-runnable inserts: sql = { style = \"upsert\" } (or \"merge\", PostgreSQL 15+)\n",
+    );
+    out.push_str("# Large syncs: sql = { batch_size = 500, transaction_size = 1000 }\n");
+
+    out.push_str("\n[diff]\n");
+    out.push_str("# Tables are listed one by one below. To diff every table of the schema\n");
+    out.push_str("# instead, replace the blocks with:\n");
+    out.push_str("#   [diff.discover]\n");
+    out.push_str("#   include = [\"*\"]\n");
+    out.push_str("#   exclude = [\"tmp_*\"]\n");
+
+    for table in tables {
+        out.push_str("\n[[diff.tables]]\n");
+        out.push_str(&format!("name = {}\n", toml_string(&table.name)));
+        out.push_str(&format!(
+            "primary_key = {}\n",
+            toml_list(&table.primary_key)
+        ));
+        if !table.excluded_columns.0.is_empty() {
+            out.push_str(&format!(
+                "excluded_columns = {}  # set by the database on write\n",
+                toml_list(&table.excluded_columns.0)
+            ));
+        }
+    }
+
+    for table in keyless {
+        out.push_str(&format!(
+            "\n# {}: {} — set `primary_key`, or leave it empty to compare whole rows.\n",
+            table.table_name, table.reason
+        ));
+        out.push_str("# [[diff.tables]]\n");
+        out.push_str(&format!("# name = {}\n", toml_string(&table.table_name)));
+        out.push_str("# primary_key = []\n");
+    }
+
+    out
+}
+
+fn render_db(out: &mut String, section: &str, comment: &str, db: &DbConfig) {
+    out.push_str(&format!("\n# {}\n[{}]\n", comment, section));
+    out.push_str(&format!("driver = {}\n", toml_string(&db.driver)));
+    out.push_str(&format!("host = {}\n", toml_string(&db.host)));
+    out.push_str(&format!("port = {}\n", db.port));
+    out.push_str(&format!("dbname = {}\n", toml_string(&db.dbname)));
+    out.push_str(&format!("user = {}\n", toml_string(&db.user)));
+    out.push_str(&format!("password = {}\n", toml_string(&db.password)));
+    out.push_str(&format!("schema = {}\n", toml_string(&db.schema)));
+    out.push_str("# consistent_read = true  # read all tables at the same point in time\n");
+}
+
+/// A TOML basic string. JSON string escapes are a subset of TOML's.
+fn toml_string(s: &str) -> String {
+    serde_json::to_string(s).expect("strings always serialise")
+}
+
+fn toml_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|i| toml_string(i)).collect();
+    format!("[{}]", items.join(", "))
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -193,6 +601,8 @@ impl AppConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::compare_rules::TimestampPrecision;
+    use crate::domain::resolution::ResolutionStrategy;
     use std::collections::HashMap;
     use std::io::Write;
     use tempfile::NamedTempFile;
@@ -294,6 +704,11 @@ dir = "./output"
         assert_eq!(cfg.target.driver, "postgres");
         assert_eq!(cfg.target.schema, "public");
         assert_eq!(cfg.output.dir, "./output");
+        assert!(!cfg.output.sql.guarded);
+        assert_eq!(cfg.output.sql.style, SqlStyle::Plain);
+        assert_eq!(cfg.output.sql.batch_size, 0);
+        assert_eq!(cfg.output.sql.transaction_size, 0);
+        assert!(!cfg.is_cross_driver());
     }
 
     #[test]
@@ -320,6 +735,13 @@ tables = []
 
 [output]
 dir = "/var/output"
+
+[output.sql]
+guarded = true
+style = "upsert"
+batch_size = 500
+transaction_size = 1000
+split_files = true
 "#;
         let f = write_toml(toml);
         let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
@@ -329,6 +751,12 @@ dir = "/var/output"
         assert_eq!(cfg.source.port, 5433);
         assert_eq!(cfg.source.schema, "myschema");
         assert_eq!(cfg.output.dir, "/var/output");
+        assert!(cfg.output.sql.guarded);
+        assert_eq!(cfg.output.sql.style, SqlStyle::Upsert);
+        assert_eq!(cfg.output.sql.batch_size, 500);
+        assert_eq!(cfg.output.sql.transaction_size, 1000);
+        assert!(cfg.output.sql.split_files);
+        assert!(cfg.is_cross_driver());
     }
 
     #[test]
@@ -466,6 +894,67 @@ dir = "/var/output"
         drop(f);
     }
 
+    #[test]
+    fn load_consistent_read_defaults_to_false() {
+        let toml = r#"
+[source]
+host = "localhost"
+port = 5432
+dbname = "src"
+user = "u"
+password = "p"
+consistent_read = true
+
+[target]
+host = "localhost"
+port = 5432
+dbname = "tgt"
+user = "u"
+password = "p"
+"#;
+        let f = write_toml(toml);
+        let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
+
+        assert!(cfg.source.consistent_read);
+        assert!(!cfg.target.consistent_read);
+    }
+
+    #[test]
+    fn load_rejects_streaming_over_pinned_consistent_read() {
+        let base = r#"
+[source]
+driver = "DRIVER"
+host = "localhost"
+port = 3306
+dbname = "src"
+user = "u"
+password = "p"
+consistent_read = true
+
+[target]
+driver = "DRIVER"
+host = "localhost"
+port = 3306
+dbname = "tgt"
+user = "u"
+password = "p"
+
+[[diff.tables]]
+name = "orders"
+primary_key = ["id"]
+strategy = "streaming"
+"#;
+        let f = write_toml(&base.replace("DRIVER", "mysql"));
+        let err = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("orders"), "{msg}");
+        assert!(msg.contains("consistent_read"), "{msg}");
+
+        // PostgreSQL shares its snapshot across connections: streaming works.
+        let f = write_toml(&base.replace("DRIVER", "postgres"));
+        assert!(AppConfig::load(Some(f.path().to_str().unwrap())).is_ok());
+    }
+
     #[test]
     fn load_table_config_parsed() {
         let toml = r#"
@@ -494,14 +983,22 @@ primary_key = ["id"]
 name = "orders"
 primary_key = ["order_id", "user_id"]
 excluded_columns = ["created_at", "updated_at"]
+strategy = "streaming"
+
+[[diff.tables]]
+name = "events"
+primary_key = ["id"]
+strategy = "bisect"
 "#;
         let f = write_toml(toml);
         let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
 
-        assert_eq!(cfg.diff.tables.len(), 2);
+        assert_eq!(cfg.diff.tables.len(), 3);
+        assert_eq!(cfg.diff.bisect_leaf_rows, 1000);
         assert_eq!(cfg.diff.tables[0].name, "users");
         assert_eq!(cfg.diff.tables[0].primary_key, vec!["id"]);
         assert!(cfg.diff.tables[0].excluded_columns.0.is_empty());
+        assert_eq!(cfg.diff.tables[0].strategy, DiffStrategy::InMemory);
 
         assert_eq!(cfg.diff.tables[1].name, "orders");
         assert_eq!(cfg.diff.tables[1].primary_key, vec!["order_id", "user_id"]);
@@ -509,6 +1006,198 @@ excluded_columns = ["created_at", "updated_at"]
             cfg.diff.tables[1].excluded_columns.0,
             vec!["created_at", "updated_at"]
         );
+        assert_eq!(cfg.diff.tables[1].strategy, DiffStrategy::Streaming);
+        assert_eq!(cfg.diff.tables[2].strategy, DiffStrategy::Bisect);
+        assert!(cfg.diff.discover.is_none());
+        assert!(!cfg.diff.fail_on_data_quality);
+    }
+
+    #[test]
+    fn load_discover_config() {
+        let toml = r#"
+[source]
+host = "localhost"
+port = 5432
+dbname = "src"
+user = "u"
+password = "p"
+
+[target]
+host = "localhost"
+port = 5432
+dbname = "tgt"
+user = "u"
+password = "p"
+
+[diff]
+fail_on_data_quality = true
+
+[diff.discover]
+exclude = ["tmp_*", "*_backup"]
+"#;
+        let f = write_toml(toml);
+        let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
+
+        let discover = cfg.diff.discover.expect("discover section parsed");
+        assert_eq!(discover.include, vec!["*"]);
+        assert_eq!(discover.exclude, vec!["tmp_*", "*_backup"]);
+        assert!(cfg.diff.tables.is_empty());
+        assert!(cfg.diff.fail_on_data_quality);
+    }
+
+    #[test]
+    fn load_compare_rules() {
+        let toml = r#"
+[source]
+host = "localhost"
+port = 5432
+dbname = "src"
+user = "u"
+password = "p"
+
+[target]
+host = "localhost"
+port = 5432
+dbname = "tgt"
+user = "u"
+password = "p"
+
+[diff.compare]
+timestamp_precision = "second"
+
+[diff.compare.columns.Email]
+ignore_case = true
+
+[[diff.tables]]
+name = "prices"
+primary_key = ["id"]
+
+[diff.tables.compare]
+rel_tolerance = 0.01
+
+[diff.tables.compare.columns.amount]
+round_decimals = 2
+
+[diff.tables.compare.columns.settings]
+ignore_paths = ["$.meta.last_sync"]
+"#;
+        let f = write_toml(toml);
+        let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
+
+        let rules = cfg.diff.compare_rules();
+        let amount = rules.for_table("prices").for_column("amount");
+        assert_eq!(amount.round_decimals, Some(2));
+        assert_eq!(amount.rel_tolerance, Some(0.01));
+        assert_eq!(amount.timestamp_precision, Some(TimestampPrecision::Second));
+        // Column names keep their case; other tables get the global rules.
+        let email = rules.for_table("users").for_column("Email");
+        assert_eq!(email.ignore_case, Some(true));
+        assert_eq!(rules.for_table("users").all.rel_tolerance, None);
+        let settings = rules.for_table("prices").for_column("settings");
+        assert_eq!(
+            settings.ignore_paths.unwrap()[0].to_string(),
+            "$.meta.last_sync"
+        );
+    }
+
+    #[test]
+    fn load_on_conflict_policies() {
+        let toml = |strategy: &str| {
+            format!(
+                r#"
+[source]
+host = "localhost"
+port = 5432
+dbname = "src"
+user = "u"
+password = "p"
+
+[target]
+host = "localhost"
+port = 5432
+dbname = "tgt"
+user = "u"
+password = "p"
+
+[[diff.tables]]
+name = "prices"
+primary_key = ["id"]
+
+[diff.tables.on_conflict]
+strategy = "{}"
+columns = {{ isActive = "target" }}
+
+[[diff.tables]]
+name = "users"
+primary_key = ["id"]
+"#,
+                strategy
+            )
+        };
+        let f = write_toml(&toml("latest_by:updated_at"));
+        let cfg = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap();
+
+        let policies = cfg.diff.resolution_policies();
+        assert_eq!(policies.len(), 1, "tables without a policy are left out");
+        let prices = &policies["prices"];
+        assert_eq!(
+            prices.for_conflict(Some("isActive")),
+            Some(&ResolutionStrategy::Target)
+        );
+        assert_eq!(
+            prices.for_conflict(None),
+            Some(&ResolutionStrategy::LatestBy("updated_at".to_string()))
+        );
+
+        let f = write_toml(&toml("newest"));
+        let err = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("unknown resolution strategy"),
+            "{:#}",
+            err
+        );
+
+        // `latest_by` cannot read an excluded column.
+        let excluded = toml("latest_by:updated_at").replace(
+            "primary_key = [\"id\"]\n\n[diff.tables.on_conflict]",
+            "primary_key = [\"id\"]\nexcluded_columns = [\"updated_at\"]\n\n[diff.tables.on_conflict]",
+        );
+        let f = write_toml(&excluded);
+        let err = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap_err();
+        assert!(
+            err.to_string().contains("latest_by:updated_at"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn invalid_ignore_path_is_rejected() {
+        let toml = r#"
+[source]
+host = "localhost"
+port = 5432
+dbname = "src"
+user = "u"
+password = "p"
+
+[target]
+host = "localhost"
+port = 5432
+dbname = "tgt"
+user = "u"
+password = "p"
+
+[diff.compare]
+ignore_paths = ["meta.last_sync"]
+"#;
+        let f = write_toml(toml);
+        let err = AppConfig::load(Some(f.path().to_str().unwrap())).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("must start with `$`"),
+            "{:#}",
+            err
+        );
     }
 
     // ── DbConfig::url ─────────────────────────────────────────────────────────
@@ -529,6 +1218,7 @@ excluded_columns = ["created_at", "updated_at"]
             port,
             dbname: dbname.to_string(),
             schema: "public".to_string(),
+            consistent_read: false,
         }
     }
 
@@ -619,4 +1309,81 @@ excluded_columns = ["created_at", "updated_at"]
         // ä = U+00E4 → UTF-8 0xC3 0xA4 → %C3%A4
         assert!(url.contains("%C3%A4"));
     }
+
+    // ── DbConfig::from_url ────────────────────────────────────────────────────
+
+    #[test]
+    fn from_url_round_trips_url() {
+        let db = make_db(
+            "postgres",
+            "user@domain",
+            "p@ss:w/rd",
+            "db.local",
+            5433,
+            "app",
+        );
+        let parsed = DbConfig::from_url(&db.url()).unwrap();
+        assert_eq!(parsed.url(), db.url());
+        assert_eq!(parsed.user, "user@domain");
+        assert_eq!(parsed.password, "p@ss:w/rd");
+        assert_eq!(parsed.port, 5433);
+        assert_eq!(parsed.schema, "public");
+    }
+
+    #[test]
+    fn from_url_applies_driver_defaults() {
+        let my = DbConfig::from_url("mariadb://root@127.0.0.1/shop?ssl-mode=DISABLED").unwrap();
+        assert_eq!(my.driver, "mariadb");
+        assert_eq!(my.port, 3306);
+        assert_eq!(my.dbname, "shop");
+        assert_eq!(my.schema, "shop");
+        assert_eq!(my.password, "");
+
+        let lite = DbConfig::from_url("sqlite:///data/app.db").unwrap();
+        assert_eq!(lite.driver, "sqlite");
+        assert_eq!(lite.dbname, "/data/app.db");
+    }
+
+    #[test]
+    fn from_url_rejects_invalid_urls() {
+        assert!(DbConfig::from_url("localhost:5432/app").is_err());
+        assert!(DbConfig::from_url("oracle://u:p@host/app").is_err());
+        assert!(DbConfig::from_url("postgres://u:p@host:5432").is_err());
+        assert!(DbConfig::from_url("postgres://u:p@host:port/app").is_err());
+    }
+
+    // ── render_config ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn rendered_config_loads_back() {
+        let source = make_db("postgres", "alice", "s3cr\"t", "localhost", 5432, "app");
+        let target = DbConfig {
+            schema: "staging".into(),
+            ..source.clone()
+        };
+        let tables = vec![TableConfig {
+            name: "orders".into(),
+            primary_key: vec!["order_id".into(), "line".into()],
+            excluded_columns: ExcludedColumns(vec!["updated_at".into()]),
+            strategy: DiffStrategy::InMemory,
+            compare: CompareRules::default(),
+            on_conflict: ResolutionPolicy::default(),
+        }];
+        let keyless = vec![SkippedTable {
+            table_name: "audit_log".into(),
+            reason: "no primary key".into(),
+        }];
+
+        let toml = render_config(&source, &target, &tables, &keyless);
+        assert!(toml.contains("# [[diff.tables]]\n# name = \"audit_log\""));
+
+        let f = write_toml(&toml);
+        let cfg = AppConfig::load_inner(Some(f.path().to_str().unwrap()), env(&[])).unwrap();
+        assert_eq!(cfg.source.password, "s3cr\"t");
+        assert_eq!(cfg.target.schema, "staging");
+        assert_eq!(cfg.output.dir, "./output");
+        assert_eq!(cfg.diff.tables.len(), 1);
+        assert_eq!(cfg.diff.tables[0].primary_key, vec!["order_id", "line"]);
+        assert_eq!(cfg.diff.tables[0].excluded_columns.0, vec!["updated_at"]);
+    }
 }
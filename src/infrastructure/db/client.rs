@@ -1,30 +1,63 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use serde_json::Value;
 use sqlx::any::AnyPoolOptions;
-use sqlx::AnyPool;
+use sqlx::{AnyPool, Executor};
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use tracing::debug;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
 
-use crate::domain::ports::RowRepository;
-use crate::domain::table_diff::RowMap;
+use crate::domain::apply_report::{ApplyFailure, ApplyReport, Operation};
+use crate::domain::changeset::Changeset;
+use crate::domain::discovery::{DiscoveredColumn, DiscoveredTable};
+use crate::domain::foreign_key::{ForeignKey, StatementOrder};
+use crate::domain::key_range::{KeyRange, RangeChecksum};
+use crate::domain::ports::{
+    ChangesetApplier, RangeRepository, RowRepository, RowStream, SchemaIntrospector,
+};
+use crate::domain::table_diff::{ColumnTypes, RowMap};
 use crate::domain::value_objects::{ColumnName, ExcludedColumns, Schema, TableName};
 use crate::infrastructure::config::DbConfig;
 use crate::infrastructure::db::dialect::{from_driver, Dialect};
+use crate::infrastructure::db::read_session::{ReadSession, Reader};
 use crate::infrastructure::db::row_mapper::row_to_map;
-use crate::infrastructure::db::sql_utils::{build_select_query, build_typed_select_query};
+use crate::infrastructure::db::sql_utils::{
+    build_key_at_query, build_key_ordered_select_query, build_range_checksum_query,
+    build_range_select_query, build_select_query, build_typed_select_query, delete_statement,
+    insert_statement, update_statement,
+};
+
+/// Rows decoded ahead of the consumer by `stream_rows`. Bounds the memory
+/// held per streamed table while keeping the connection busy.
+const STREAM_BUFFER_ROWS: usize = 1024;
 
 pub struct SqlxRowRepository {
     pool: AnyPool,
     dialect: Arc<dyn Dialect>,
+    /// Set by `begin_consistent_read`: every read then sees the same snapshot.
+    session: Option<ReadSession>,
+    /// Set by `with_normalized_values`: rows are decoded driver-neutrally.
+    normalize: bool,
 }
 
 /// Connect to the database described in `cfg` and return a `SqlxRowRepository`.
 pub async fn connect(cfg: &DbConfig) -> Result<SqlxRowRepository> {
     sqlx::any::install_default_drivers();
 
+    let dialect: Arc<dyn Dialect> = Arc::from(from_driver(&cfg.driver));
+    let session_sql = dialect.session_sql();
     let pool = AnyPoolOptions::new()
         .max_connections(5)
+        .after_connect(move |conn, _| {
+            Box::pin(async move {
+                for stmt in session_sql {
+                    conn.execute(sqlx::raw_sql(stmt)).await?;
+                }
+                Ok(())
+            })
+        })
         .connect(&cfg.url())
         .await
         .with_context(|| {
@@ -41,7 +74,9 @@ pub async fn connect(cfg: &DbConfig) -> Result<SqlxRowRepository> {
 
     Ok(SqlxRowRepository {
         pool,
-        dialect: Arc::from(from_driver(&cfg.driver)),
+        dialect,
+        session: None,
+        normalize: false,
     })
 }
 
@@ -58,20 +93,28 @@ fn blob_or_string(row: &sqlx::any::AnyRow, idx: usize) -> Result<String> {
     }
 }
 
+/// Like `blob_or_string`, for nullable columns.
+fn opt_blob_or_string(row: &sqlx::any::AnyRow, idx: usize) -> Result<Option<String>> {
+    use sqlx::{Row, ValueRef};
+    if row.try_get_raw(idx)?.is_null() {
+        return Ok(None);
+    }
+    blob_or_string(row, idx).map(Some)
+}
+
 /// Query `information_schema.columns` for `(column_name, data_type)` pairs.
 /// The SQL and placeholders are provided by the dialect.
 async fn fetch_column_types(
-    pool: &AnyPool,
+    reader: &mut Reader,
     schema: &Schema,
     table: &TableName,
     dialect: &dyn Dialect,
 ) -> Result<Vec<(String, String)>> {
     let sql = dialect.introspect_sql();
 
-    let rows = sqlx::query(sql)
-        .bind(&schema.0)
-        .bind(&table.0)
-        .fetch_all(pool)
+    let query = sqlx::query(sql).bind(&schema.0).bind(&table.0);
+    let rows = reader
+        .fetch_all(query)
         .await
         .with_context(|| format!("Failed to fetch column types for {}.{}", schema.0, table.0))?;
 
@@ -85,6 +128,67 @@ async fn fetch_column_types(
     Ok(cols)
 }
 
+impl SqlxRowRepository {
+    /// Read every table through one read-only transaction from now on, so all
+    /// reads of this database observe the same point in time (see
+    /// `ReadSession` for how each driver shares the snapshot).
+    pub async fn begin_consistent_read(mut self) -> Result<Self> {
+        self.session = Some(ReadSession::open(&self.pool, self.dialect.as_ref()).await?);
+        Ok(self)
+    }
+
+    /// Decode every value into the driver-neutral form of
+    /// `RowDecoder::normalize`, for diffs against a database of another
+    /// driver. Server-side range checksums are disabled, since they hash the
+    /// raw values: bisected tables are fetched whole instead.
+    pub fn with_normalized_values(mut self) -> Self {
+        self.normalize = true;
+        self
+    }
+
+    /// Connection for one read: inside the consistent-read snapshot if one
+    /// was started, any pool connection otherwise.
+    async fn reader(&self) -> Result<Reader> {
+        match &self.session {
+            Some(session) => session.reader(&self.pool, self.dialect.as_ref()).await,
+            None => Ok(Reader::Pool(self.pool.acquire().await?)),
+        }
+    }
+
+    /// `(column_name, data_type)` pairs for dialects with introspection,
+    /// empty otherwise (SQLite decodes every storage class natively).
+    async fn column_types(
+        &self,
+        reader: &mut Reader,
+        schema: &Schema,
+        table: &TableName,
+    ) -> Result<Vec<(String, String)>> {
+        if self.dialect.needs_introspection() {
+            fetch_column_types(reader, schema, table, self.dialect.as_ref()).await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Decode fetched rows, dropping excluded columns.
+    fn decode_rows(
+        &self,
+        rows: &[sqlx::any::AnyRow],
+        col_types_map: &BTreeMap<String, String>,
+        excluded: &ExcludedColumns,
+    ) -> Result<Vec<RowMap>> {
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut map = row_to_map(row, col_types_map, self.dialect.as_ref(), self.normalize)?;
+            for col in &excluded.0 {
+                map.remove(col);
+            }
+            result.push(map);
+        }
+        Ok(result)
+    }
+}
+
 #[async_trait]
 impl RowRepository for SqlxRowRepository {
     async fn fetch_rows(
@@ -99,9 +203,10 @@ impl RowRepository for SqlxRowRepository {
         // text, and the mapper reconstructs the correct Value variant from the
         // type hint. Dialects without introspection (SQLite) use SELECT * —
         // SQLite's loose affinity means AnyRow decodes all storage classes natively.
+        let mut reader = self.reader().await?;
         let (query, col_types_map) = if self.dialect.needs_introspection() {
             let col_types =
-                fetch_column_types(&self.pool, schema, table, self.dialect.as_ref()).await?;
+                fetch_column_types(&mut reader, schema, table, self.dialect.as_ref()).await?;
             let q =
                 build_typed_select_query(schema, table, pk_cols, &col_types, self.dialect.as_ref());
             let type_map: BTreeMap<String, String> = col_types.into_iter().collect();
@@ -115,19 +220,415 @@ impl RowRepository for SqlxRowRepository {
 
         debug!("Executing: {}", query);
 
-        let rows = sqlx::query(&query)
-            .fetch_all(&self.pool)
+        let rows = reader
+            .fetch_all(sqlx::query(&query))
             .await
             .with_context(|| format!("Failed to query {}.{}", schema.0, table.0))?;
 
-        let mut result = Vec::with_capacity(rows.len());
+        self.decode_rows(&rows, &col_types_map, excluded)
+    }
+
+    async fn stream_rows(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+    ) -> Result<RowStream> {
+        anyhow::ensure!(
+            !pk_cols.is_empty(),
+            "Streaming {}.{} requires a primary key",
+            schema.0,
+            table.0
+        );
+
+        let mut reader = self.reader().await?;
+        let col_types = self.column_types(&mut reader, schema, table).await?;
+        let query = build_key_ordered_select_query(
+            schema,
+            table,
+            pk_cols,
+            &col_types,
+            self.dialect.as_ref(),
+        );
+        let col_types_map: BTreeMap<String, String> = col_types.into_iter().collect();
+
+        if reader.is_pinned() {
+            // One connection serves every table of this database. Holding it
+            // for the whole merge join could deadlock against the other
+            // side's pinned connection, so the table is read in one go.
+            // Configs loaded from a file never get here (see
+            // `AppConfig::load`), only direct callers of the port.
+            warn!(
+                table = %format!("{}.{}", schema.0, table.0),
+                "streaming over a consistent read loads the whole table in memory"
+            );
+            debug!("Executing: {}", query);
+            let rows = reader
+                .fetch_all(sqlx::query(&query))
+                .await
+                .with_context(|| format!("Failed to query {}.{}", schema.0, table.0))?;
+            let rows = self.decode_rows(&rows, &col_types_map, excluded)?;
+            return Ok(Box::pin(stream::iter(rows.into_iter().map(Ok))));
+        }
+
+        debug!("Streaming: {}", query);
+
+        // The query runs on its own task and hands decoded rows over a bounded
+        // channel: the stream is 'static, and the producer pauses whenever the
+        // differ falls behind. Dropping the stream stops the producer.
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER_ROWS);
+        let dialect = Arc::clone(&self.dialect);
+        let normalize = self.normalize;
+        let excluded = excluded.clone();
+        let context = format!("Failed to stream {}.{}", schema.0, table.0);
+
+        tokio::spawn(async move {
+            let mut rows = reader.fetch(sqlx::query(&query));
+            while let Some(row) = rows.next().await {
+                let item = row.context(context.clone()).and_then(|row| {
+                    let mut map = row_to_map(&row, &col_types_map, dialect.as_ref(), normalize)?;
+                    for col in &excluded.0 {
+                        map.remove(col);
+                    }
+                    Ok(map)
+                });
+                let failed = item.is_err();
+                if tx.send(item).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Box::pin(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        })))
+    }
+
+    async fn column_sql_types(&self, schema: &Schema, table: &TableName) -> Result<ColumnTypes> {
+        let Some(sql) = self.dialect.column_types_sql() else {
+            return Ok(ColumnTypes::new());
+        };
+        let mut reader = self.reader().await?;
+        let query = sqlx::query(sql).bind(&schema.0).bind(&table.0);
+        let rows = reader.fetch_all(query).await.with_context(|| {
+            format!("Failed to fetch column types for {}.{}", schema.0, table.0)
+        })?;
+
+        rows.iter()
+            .map(|row| Ok((blob_or_string(row, 0)?, blob_or_string(row, 1)?)))
+            .collect()
+    }
+
+    async fn foreign_keys(&self, schema: &Schema) -> Result<Vec<ForeignKey>> {
+        let query = self.dialect.foreign_keys_sql(&schema.0);
+        debug!("Executing: {}", query);
+
+        let mut reader = self.reader().await?;
+        let rows = reader
+            .fetch_all(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to list the foreign keys of schema {}", schema.0))?;
+
+        // One row per key column, grouped by table and constraint.
+        let mut keys: Vec<(String, ForeignKey)> = Vec::new();
         for row in &rows {
-            let mut map = row_to_map(row, &col_types_map, self.dialect.as_ref())?;
-            for col in &excluded.0 {
-                map.remove(col);
+            let table = blob_or_string(row, 0)?;
+            let constraint = blob_or_string(row, 1)?;
+            let column = blob_or_string(row, 2)?;
+            match keys.last_mut() {
+                Some((name, key)) if key.table == table && *name == constraint => {
+                    key.columns.push(column)
+                }
+                _ => keys.push((
+                    constraint,
+                    ForeignKey {
+                        table,
+                        columns: vec![column],
+                        referenced_table: blob_or_string(row, 3)?,
+                    },
+                )),
             }
-            result.push(map);
         }
-        Ok(result)
+        Ok(keys.into_iter().map(|(_, key)| key).collect())
+    }
+}
+
+#[async_trait]
+impl RangeRepository for SqlxRowRepository {
+    async fn checksum_range(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+        range: &KeyRange,
+    ) -> Result<Option<RangeChecksum>> {
+        if self.normalize {
+            return Ok(None);
+        }
+        let mut reader = self.reader().await?;
+        let col_types = self.column_types(&mut reader, schema, table).await?;
+        let compared: Vec<String> = col_types
+            .iter()
+            .filter(|(name, _)| !excluded.0.contains(name))
+            .map(|(name, _)| self.dialect.quote_ident(name))
+            .collect();
+        let Some(checksum_expr) = self.dialect.checksum_expr(&compared) else {
+            return Ok(None);
+        };
+
+        let query = build_range_checksum_query(
+            schema,
+            table,
+            pk_cols,
+            &col_types,
+            &checksum_expr,
+            range,
+            self.dialect.as_ref(),
+        );
+        debug!("Executing: {}", query);
+
+        let row = reader
+            .fetch_one(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to checksum {}.{}", schema.0, table.0))?;
+
+        use sqlx::Row;
+        let rows: i64 = row.try_get(0)?;
+        Ok(Some(RangeChecksum {
+            rows: rows as u64,
+            checksum: blob_or_string(&row, 1)?,
+        }))
+    }
+
+    async fn key_at(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        range: &KeyRange,
+        offset: u64,
+    ) -> Result<Option<Vec<Value>>> {
+        let mut reader = self.reader().await?;
+        let col_types = self.column_types(&mut reader, schema, table).await?;
+        let query = build_key_at_query(
+            schema,
+            table,
+            pk_cols,
+            &col_types,
+            range,
+            offset,
+            self.dialect.as_ref(),
+        );
+        debug!("Executing: {}", query);
+
+        let row = reader
+            .fetch_optional(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to split {}.{}", schema.0, table.0))?;
+
+        let col_types_map: BTreeMap<String, String> = col_types.into_iter().collect();
+        row.map(|row| {
+            // Raw values: the key is sent back as a range bound.
+            let map = row_to_map(&row, &col_types_map, self.dialect.as_ref(), false)?;
+            Ok(pk_cols
+                .iter()
+                .map(|c| map.get(&c.0).cloned().unwrap_or(Value::Null))
+                .collect())
+        })
+        .transpose()
+    }
+
+    async fn fetch_range(
+        &self,
+        schema: &Schema,
+        table: &TableName,
+        pk_cols: &[ColumnName],
+        excluded: &ExcludedColumns,
+        range: &KeyRange,
+    ) -> Result<Vec<RowMap>> {
+        let mut reader = self.reader().await?;
+        let col_types = self.column_types(&mut reader, schema, table).await?;
+        let query = build_range_select_query(
+            schema,
+            table,
+            pk_cols,
+            &col_types,
+            range,
+            self.dialect.as_ref(),
+        );
+        debug!("Executing: {}", query);
+
+        let rows = reader
+            .fetch_all(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to query {}.{}", schema.0, table.0))?;
+
+        let col_types_map: BTreeMap<String, String> = col_types.into_iter().collect();
+        self.decode_rows(&rows, &col_types_map, excluded)
+    }
+}
+
+#[async_trait]
+impl SchemaIntrospector for SqlxRowRepository {
+    async fn discover_tables(&self, schema: &Schema) -> Result<Vec<DiscoveredTable>> {
+        let query = self.dialect.discover_tables_sql(&schema.0);
+        debug!("Executing: {}", query);
+
+        let mut reader = self.reader().await?;
+        let rows = reader
+            .fetch_all(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to list the tables of schema {}", schema.0))?;
+
+        // One row per key column, grouped by table (the query orders by name).
+        let mut tables: Vec<DiscoveredTable> = Vec::new();
+        for row in &rows {
+            let name = blob_or_string(row, 0)?;
+            let column = opt_blob_or_string(row, 1)?;
+            match tables.last_mut() {
+                Some(table) if table.name == name => table.primary_key.extend(column),
+                _ => tables.push(DiscoveredTable {
+                    name,
+                    primary_key: column.into_iter().collect(),
+                }),
+            }
+        }
+        Ok(tables)
+    }
+
+    async fn describe_columns(&self, schema: &Schema) -> Result<Vec<DiscoveredColumn>> {
+        let query = self.dialect.describe_columns_sql(&schema.0);
+        debug!("Executing: {}", query);
+
+        let mut reader = self.reader().await?;
+        let rows = reader
+            .fetch_all(sqlx::query(&query))
+            .await
+            .with_context(|| format!("Failed to list the columns of schema {}", schema.0))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DiscoveredColumn {
+                    table_name: blob_or_string(row, 0)?,
+                    name: blob_or_string(row, 1)?,
+                    default: opt_blob_or_string(row, 2)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ChangesetApplier for SqlxRowRepository {
+    async fn apply(
+        &self,
+        schema: &Schema,
+        changeset: &Changeset,
+        dry_run: bool,
+    ) -> Result<ApplyReport> {
+        let dialect = self.dialect.as_ref();
+
+        // Same order as the SQL script: all deletes (children first), then
+        // updates and inserts (parents first), so a PK that is deleted and
+        // re-inserted never collides and foreign keys hold after every
+        // statement.
+        let order = StatementOrder::plan(&changeset.tables, &changeset.foreign_keys);
+        let mut statements = Vec::with_capacity(changeset.summary.total_changes);
+        for table in order.deletes().map(|i| &changeset.tables[i]) {
+            for row in &table.deletes {
+                let sql = delete_statement(&schema.0, table, row, false, dialect);
+                statements.push((&table.table_name, Operation::Delete, &row.pk, sql));
+            }
+        }
+        for table in order.tables.iter().map(|i| &changeset.tables[*i]) {
+            for row in &table.updates {
+                let sql = update_statement(&schema.0, table, row, false, dialect);
+                statements.push((&table.table_name, Operation::Update, &row.pk, sql));
+            }
+        }
+        for table in order.tables.iter().map(|i| &changeset.tables[*i]) {
+            for row in &table.inserts {
+                let sql = insert_statement(&schema.0, table, row, dialect);
+                statements.push((&table.table_name, Operation::Insert, &row.pk, sql));
+            }
+        }
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to open the apply transaction")?;
+        if order.defer_constraints {
+            tx.execute(dialect.defer_foreign_keys_sql())
+                .await
+                .context("Failed to defer foreign-key checks")?;
+        }
+
+        let mut report = ApplyReport {
+            changeset_id: changeset.changeset_id.clone(),
+            dry_run,
+            committed: false,
+            statements_executed: 0,
+            rows_affected: 0,
+            failures: Vec::new(),
+        };
+
+        for (table_name, operation, pk, sql) in statements {
+            debug!("Executing: {}", sql);
+            report.statements_executed += 1;
+
+            let failure = |reason: String| ApplyFailure {
+                table_name: table_name.clone(),
+                operation,
+                pk: pk.clone(),
+                sql: sql.clone(),
+                reason,
+            };
+
+            match sqlx::query(&sql).execute(&mut *tx).await {
+                Ok(result) => {
+                    let affected = result.rows_affected();
+                    report.rows_affected += affected;
+                    if affected != 1 {
+                        report.failures.push(failure(format!(
+                            "expected exactly 1 row affected, got {}",
+                            affected
+                        )));
+                    }
+                }
+                Err(e) => {
+                    // A failed statement aborts the transaction on most
+                    // drivers — stop here, nothing after it would run anyway.
+                    report.failures.push(failure(e.to_string()));
+                    break;
+                }
+            }
+        }
+
+        if let Some(restore) = dialect
+            .restore_foreign_keys_sql()
+            .filter(|_| order.defer_constraints)
+        {
+            // Session-wide on MySQL: restored before the connection goes
+            // back to the pool, also after a failed statement.
+            tx.execute(restore)
+                .await
+                .context("Failed to restore foreign-key checks")?;
+        }
+
+        if report.failures.is_empty() && !dry_run {
+            tx.commit()
+                .await
+                .context("Failed to commit the apply transaction")?;
+            report.committed = true;
+        } else {
+            tx.rollback()
+                .await
+                .context("Failed to roll back the apply transaction")?;
+        }
+
+        Ok(report)
     }
 }
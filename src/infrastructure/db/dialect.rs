@@ -3,6 +3,9 @@ use serde_json::{json, Value};
 use sqlx::any::AnyRow;
 use sqlx::{Column, Row, TypeInfo};
 
+use crate::domain::binary;
+use crate::infrastructure::db::{normalize, pg_types, temporal};
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Traits
 // ─────────────────────────────────────────────────────────────────────────────
@@ -46,37 +49,209 @@ pub trait QueryDialect: Send + Sync {
     /// is natively decodable by `sqlx::AnyRow` without any explicit cast.
     fn is_native_type(&self, data_type: &str) -> bool;
 
+    /// ORDER BY expression for a key column when rows are streamed to the
+    /// merge-join differ. The database must sort exactly the way diffly
+    /// compares decoded keys: numbers numerically, dates and times
+    /// chronologically (their canonical text sorts that way), everything else
+    /// by the raw UTF-8 bytes of its text form, regardless of the column
+    /// collation.
+    /// `data_type` is the `information_schema` type (empty when unknown).
+    fn key_order_expr(&self, col_quoted: &str, data_type: &str) -> String;
+
+    /// Aggregate expression hashing every row of a `SELECT … FROM … WHERE …`
+    /// into a single text value, independent of row order (used by the
+    /// bisection strategy). `cols_quoted` are the compared columns.
+    /// Returns `None` when the database has no hash function (SQLite).
+    fn checksum_expr(&self, _cols_quoted: &[String]) -> Option<String> {
+        None
+    }
+
+    /// Statements opening a read-only transaction that sees one consistent
+    /// snapshot of the whole database (`consistent_read = true`).
+    fn consistent_read_sql(&self) -> &'static [&'static str];
+
+    /// Statements run on every new connection, before any read or write.
+    fn session_sql(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Query returning an identifier other connections can import to share
+    /// the snapshot opened by `consistent_read_sql` (PostgreSQL only).
+    /// `None` means every read must go through the connection that opened it.
+    fn export_snapshot_sql(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Statements run right after `BEGIN` on another connection to join an
+    /// exported snapshot.
+    fn import_snapshot_sql(&self, _snapshot_id: &str) -> Vec<String> {
+        Vec::new()
+    }
+
     /// The SQL to introspect column types from `information_schema.columns`.
     /// Uses driver-appropriate placeholders ($1/$2 vs ?/?)
     /// and driver-appropriate casts (::TEXT vs nothing).
     fn introspect_sql(&self) -> &'static str;
 
+    /// The SQL listing every base table of `schema` with its primary-key
+    /// columns: one `(table_name, column_name)` row per key column, in key
+    /// order, and a single `(table_name, NULL)` row for tables without a
+    /// primary key. Ordered by table name.
+    fn discover_tables_sql(&self, schema: &str) -> String;
+
+    /// The SQL listing every column of every base table of `schema`:
+    /// `(table_name, column_name, column_default)` rows, ordered by table
+    /// name then column position. `column_default` is NULL without default.
+    fn describe_columns_sql(&self, schema: &str) -> String;
+
+    /// The SQL listing every foreign key between two tables of `schema`:
+    /// `(table_name, constraint_name, column_name, referenced_table_name)`
+    /// rows, one per key column, ordered by table, constraint, then column
+    /// position within the key.
+    fn foreign_keys_sql(&self, schema: &str) -> String;
+
+    /// Statement postponing foreign-key checks to the commit of the current
+    /// transaction, for changesets no statement order satisfies. No
+    /// trailing semicolon.
+    fn defer_foreign_keys_sql(&self) -> &'static str;
+
+    /// Statement undoing `defer_foreign_keys_sql` before the transaction
+    /// ends, when the deferral would outlive it. `None` by default.
+    fn restore_foreign_keys_sql(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// `DELETE` removing a single row of `table` (already qualified) that
+    /// matches `predicate`, for tables without a key where several identical
+    /// rows may match. No trailing semicolon.
+    fn delete_one_sql(&self, table: &str, predicate: &str) -> String;
+
+    /// `col_quoted = literal`, a predicate matching a value of `column_type`.
+    /// Override where a type has no usable `=` (PostgreSQL `json`) or stores
+    /// a rounded value (single-precision floats).
+    fn equals_sql(&self, col_quoted: &str, literal: &str, _column_type: Option<&str>) -> String {
+        format!("{} = {}", col_quoted, literal)
+    }
+
+    /// Statement run before the transaction of a guarded script, creating
+    /// what `guarded_sql` needs; `None` when nothing is needed.
+    ///
+    /// Without procedural SQL outside stored programs, a failed guard
+    /// inserts a NULL into a `NOT NULL` column: the error names
+    /// `unchanged_on_target` and the line of the check.
+    fn guard_setup_sql(&self) -> Option<&'static str> {
+        Some("CREATE TEMPORARY TABLE IF NOT EXISTS diffly_guard (unchanged_on_target INTEGER NOT NULL);")
+    }
+
+    /// `statement` (terminated by `;`) followed by a check failing the script
+    /// unless it changed exactly one row. `row` names the row in the error,
+    /// where the dialect can raise a message of its own.
+    fn guarded_sql(&self, statement: &str, _row: &str) -> String {
+        format!(
+            "{}\nINSERT INTO diffly_guard (unchanged_on_target) VALUES (CASE WHEN {} = 1 THEN 1 END);",
+            statement,
+            self.row_count_sql()
+        )
+    }
+
+    /// Tail of an `INSERT` overwriting the row when `key_cols` (quoted)
+    /// already exist: `update_cols` take the inserted values. Defaults to
+    /// `ON CONFLICT (…) DO UPDATE` (PostgreSQL, SQLite 3.24+).
+    fn upsert_clause(&self, key_cols: &[String], update_cols: &[String]) -> String {
+        if update_cols.is_empty() {
+            return format!("ON CONFLICT ({}) DO NOTHING", key_cols.join(", "));
+        }
+        let set: Vec<String> = update_cols
+            .iter()
+            .map(|c| format!("{} = EXCLUDED.{}", c, c))
+            .collect();
+        format!(
+            "ON CONFLICT ({}) DO UPDATE SET {}",
+            key_cols.join(", "),
+            set.join(", ")
+        )
+    }
+
+    /// `true` when the database runs `MERGE` statements. PostgreSQL does
+    /// from version 15 on; the server version is not checked, since a script
+    /// is written without connecting to the target.
+    fn supports_merge(&self) -> bool {
+        false
+    }
+
+    /// `true` when a script can load rows with `COPY … FROM STDIN`.
+    fn supports_copy(&self) -> bool {
+        false
+    }
+
+    /// `val` as a field of a `COPY … FROM STDIN` data line, in PostgreSQL's
+    /// text format. Only used where `supports_copy`.
+    fn copy_field(&self, val: &Value, column_type: Option<&str>) -> String {
+        pg_types::copy_text(val, column_type)
+    }
+
+    /// Number of rows changed by the previous statement, for `guarded_sql`.
+    /// MySQL counts rows actually changed, not rows matched.
+    fn row_count_sql(&self) -> &'static str {
+        "ROW_COUNT()"
+    }
+
+    /// The SQL listing the type of every column of one table, spelled as a
+    /// cast target: `(column_name, sql_type)` rows in ordinal order, bound
+    /// like `introspect_sql`. `None` when literals never need a cast (SQLite).
+    fn column_types_sql(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Format a JSON `Value` as an SQL literal for this dialect.
     /// - NULL          → `NULL`
     /// - Bool          → `TRUE` / `FALSE`
-    /// - Number        → bare number
+    /// - Number        → bare number, digits as decoded (no float rounding)
     /// - String        → `'escaped'`
+    /// - `{"$bytes"}`  → the dialect's binary literal (see `bytes_literal`)
     /// - Object/Array  → `'json'` with `::jsonb` cast on PostgreSQL only
-    fn sql_literal(&self, val: &Value) -> String {
+    ///
+    /// `column_type` is the SQL type of the column receiving the value (see
+    /// `column_types_sql`), when known: `typed_literal` may then cast it.
+    fn sql_literal(&self, val: &Value, column_type: Option<&str>) -> String {
+        let typed = column_type
+            .filter(|_| !val.is_null())
+            .and_then(|t| self.typed_literal(val, t));
+        if let Some(literal) = typed {
+            return literal;
+        }
         match val {
             Value::Null => "NULL".to_string(),
             Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
             Value::Number(n) => n.to_string(),
-            Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+            Value::String(s) => quoted(s),
             Value::Array(_) | Value::Object(_) => {
-                let json_str = serde_json::to_string(val)
-                    .unwrap_or_default()
-                    .replace('\'', "''");
-                self.json_literal(&json_str)
+                if let Some(hex) = binary::bytes_hex(val) {
+                    return self.bytes_literal(hex);
+                }
+                self.json_literal(&json_text(val))
             }
         }
     }
 
+    /// Literal for a non-NULL `val` going to a column of type `column_type`,
+    /// for values the database would not convert implicitly, or `None` for
+    /// the untyped literal of `sql_literal`. Defaults to `None`.
+    fn typed_literal(&self, _val: &Value, _column_type: &str) -> Option<String> {
+        None
+    }
+
     /// Render a pre-serialised JSON string as a dialect-appropriate literal.
     /// Override in PostgreSQL to append `::jsonb`.
     fn json_literal(&self, json_str: &str) -> String {
         format!("'{}'", json_str)
     }
+
+    /// Render binary data, given as lowercase hex digits, as a literal.
+    /// Defaults to the standard `X'..'`.
+    fn bytes_literal(&self, hex: &str) -> String {
+        format!("X'{}'", hex)
+    }
 }
 
 /// Row decoder: read a single `AnyRow` column into a `serde_json::Value`.
@@ -87,6 +262,13 @@ pub trait RowDecoder: Send + Sync {
     /// Decode the column at `idx` using `type_hint` (an `information_schema`
     /// `data_type` string) to reconstruct the correct `Value` variant.
     fn decode_column(&self, row: &AnyRow, idx: usize, type_hint: &str) -> Result<Value>;
+
+    /// Rewrite a value returned by `decode_column` into the driver-neutral
+    /// form used when source and target use different drivers, so that e.g.
+    /// a MySQL `TINYINT(1)` and a PostgreSQL `boolean` compare equal.
+    fn normalize(&self, value: Value, type_hint: &str) -> Value {
+        normalize::portable_value(value, type_hint)
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -115,21 +297,251 @@ impl QueryDialect for PostgresDialect {
         )
     }
 
+    fn key_order_expr(&self, col_quoted: &str, data_type: &str) -> String {
+        // NUMERIC is cast to TEXT in the SELECT but decoded back to a number,
+        // temporal types to their canonical text.
+        if self.is_native_type(data_type)
+            || data_type.eq_ignore_ascii_case("numeric")
+            || temporal::sorts_by_value(data_type)
+        {
+            col_quoted.to_string()
+        } else {
+            format!("{}::TEXT COLLATE \"C\"", col_quoted)
+        }
+    }
+
+    fn checksum_expr(&self, cols_quoted: &[String]) -> Option<String> {
+        // Sum of the first 60 bits of each row's md5: order-independent and
+        // constant memory. SUM(bigint) is NUMERIC, so it cannot overflow.
+        Some(format!(
+            "COALESCE(SUM(('x' || SUBSTR(MD5(ROW({})::TEXT), 1, 15))::BIT(60)::BIGINT), 0)::TEXT",
+            cols_quoted.join(", ")
+        ))
+    }
+
+    fn consistent_read_sql(&self) -> &'static [&'static str] {
+        &["BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY"]
+    }
+
+    fn export_snapshot_sql(&self) -> Option<&'static str> {
+        Some("SELECT pg_export_snapshot()")
+    }
+
+    fn import_snapshot_sql(&self, snapshot_id: &str) -> Vec<String> {
+        vec![
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY".to_string(),
+            format!(
+                "SET TRANSACTION SNAPSHOT '{}'",
+                snapshot_id.replace('\'', "''")
+            ),
+        ]
+    }
+
     fn introspect_sql(&self) -> &'static str {
-        "SELECT column_name::TEXT, data_type::TEXT \
-         FROM information_schema.columns \
-         WHERE table_schema = $1 AND table_name = $2 \
-         ORDER BY ordinal_position"
+        // Arrays, ranges, hstore and composites are decoded structurally by
+        // `pg_types`, which needs more than `information_schema` tells
+        // (`ARRAY`, `USER-DEFINED`): `array:<element hint>`,
+        // `range:<subtype>`, `hstore` and `composite`.
+        "SELECT c.column_name::TEXT, \
+           CASE WHEN r.rngsubtype IS NOT NULL THEN 'range:' || format_type(r.rngsubtype, NULL) \
+                WHEN c.data_type = 'ARRAY' THEN 'array:' || \
+                  CASE WHEN er.rngsubtype IS NOT NULL THEN 'range:' || format_type(er.rngsubtype, NULL) \
+                       WHEN e.typtype = 'c' THEN 'composite' \
+                       WHEN e.typname = 'hstore' THEN 'hstore' \
+                       ELSE format_type(e.oid, NULL) END \
+                WHEN t.typtype = 'c' THEN 'composite' \
+                WHEN t.typname = 'hstore' THEN 'hstore' \
+                ELSE c.data_type END::TEXT \
+         FROM information_schema.columns c \
+         LEFT JOIN pg_catalog.pg_namespace n ON n.nspname = c.udt_schema \
+         LEFT JOIN pg_catalog.pg_type t ON t.typnamespace = n.oid AND t.typname = c.udt_name \
+         LEFT JOIN pg_catalog.pg_range r ON r.rngtypid = t.oid \
+         LEFT JOIN pg_catalog.pg_type e ON e.oid = t.typelem AND c.data_type = 'ARRAY' \
+         LEFT JOIN pg_catalog.pg_range er ON er.rngtypid = e.oid \
+         WHERE c.table_schema = $1 AND c.table_name = $2 \
+         ORDER BY c.ordinal_position"
+    }
+
+    fn column_types_sql(&self) -> Option<&'static str> {
+        // `format_type` spells arrays, enums and modifiers the way a cast
+        // expects them (`integer[]`, `order_status`, `numeric(10,2)`), where
+        // `information_schema` only says `ARRAY` / `USER-DEFINED`.
+        Some(
+            "SELECT a.attname::TEXT, format_type(a.atttypid, a.atttypmod) \
+             FROM pg_catalog.pg_attribute a \
+             JOIN pg_catalog.pg_class c ON c.oid = a.attrelid \
+             JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+             WHERE n.nspname = $1 AND c.relname = $2 \
+               AND a.attnum > 0 AND NOT a.attisdropped \
+             ORDER BY a.attnum",
+        )
+    }
+
+    fn discover_tables_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT t.table_name::TEXT, k.column_name::TEXT \
+             FROM information_schema.tables t \
+             LEFT JOIN information_schema.table_constraints c \
+               ON c.table_schema = t.table_schema AND c.table_name = t.table_name \
+              AND c.constraint_type = 'PRIMARY KEY' \
+             LEFT JOIN information_schema.key_column_usage k \
+               ON k.table_schema = c.table_schema AND k.table_name = c.table_name \
+              AND k.constraint_name = c.constraint_name \
+             WHERE t.table_schema = {} AND t.table_type = 'BASE TABLE' \
+             ORDER BY t.table_name, k.ordinal_position",
+            self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn describe_columns_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT c.table_name::TEXT, c.column_name::TEXT, c.column_default::TEXT \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t \
+               ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE c.table_schema = {} AND t.table_type = 'BASE TABLE' \
+             ORDER BY c.table_name, c.ordinal_position",
+            self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn foreign_keys_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT t.relname::TEXT, c.conname::TEXT, a.attname::TEXT, r.relname::TEXT \
+             FROM pg_catalog.pg_constraint c \
+             JOIN pg_catalog.pg_class t ON t.oid = c.conrelid \
+             JOIN pg_catalog.pg_class r ON r.oid = c.confrelid \
+             JOIN pg_catalog.pg_namespace n ON n.oid = t.relnamespace \
+             JOIN pg_catalog.pg_namespace rn ON rn.oid = r.relnamespace \
+             CROSS JOIN LATERAL unnest(c.conkey) WITH ORDINALITY AS k(attnum, position) \
+             JOIN pg_catalog.pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum \
+             WHERE c.contype = 'f' AND n.nspname = {schema} AND rn.nspname = {schema} \
+             ORDER BY t.relname, c.conname, k.position",
+            schema = self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn defer_foreign_keys_sql(&self) -> &'static str {
+        // Only affects constraints declared DEFERRABLE.
+        "SET CONSTRAINTS ALL DEFERRED"
+    }
+
+    fn delete_one_sql(&self, table: &str, predicate: &str) -> String {
+        format!(
+            "DELETE FROM {} WHERE ctid = (SELECT ctid FROM {} WHERE {} LIMIT 1)",
+            table, table, predicate
+        )
+    }
+
+    fn equals_sql(&self, col_quoted: &str, literal: &str, column_type: Option<&str>) -> String {
+        match column_type {
+            // `json` has no equality operator: compare as documents.
+            Some("json") => format!("{}::jsonb = {}::jsonb", col_quoted, literal),
+            Some("real") => format!("{} = {}::real", col_quoted, literal),
+            _ => format!("{} = {}", col_quoted, literal),
+        }
+    }
+
+    fn guard_setup_sql(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn supports_merge(&self) -> bool {
+        // PostgreSQL 15+.
+        true
+    }
+
+    fn supports_copy(&self) -> bool {
+        true
+    }
+
+    fn guarded_sql(&self, statement: &str, row: &str) -> String {
+        // A dollar-quote tag the statement cannot close early.
+        let mut tag = "$diffly$".to_string();
+        while statement.contains(&tag) {
+            tag.insert(tag.len() - 1, '_');
+        }
+        // The statement is kept verbatim: re-indenting it would change the
+        // multi-line text literals it may hold. The variable is named so no
+        // column of the statement makes a reference to it ambiguous.
+        format!(
+            "DO {tag}\nDECLARE\n  diffly_affected_rows bigint;\nBEGIN\n{}\n  \
+             GET DIAGNOSTICS diffly_affected_rows = ROW_COUNT;\n  \
+             IF diffly_affected_rows <> 1 THEN\n    RAISE EXCEPTION 'diffly guard: % changed on target since the diff', {};\n  \
+             END IF;\nEND\n{tag};",
+            statement,
+            quoted(row),
+        )
     }
 
     fn json_literal(&self, json_str: &str) -> String {
         format!("'{}'::jsonb", json_str)
     }
+
+    fn bytes_literal(&self, hex: &str) -> String {
+        format!("'\\x{}'::bytea", hex)
+    }
+
+    fn typed_literal(&self, val: &Value, column_type: &str) -> Option<String> {
+        if matches!(column_type, "json" | "jsonb") && binary::bytes_hex(val).is_none() {
+            // Any JSON value, scalars included, is written as a document.
+            return Some(format!("'{}'::{}", json_text(val), column_type));
+        }
+        match val {
+            Value::Array(items) if column_type.ends_with("[]") => {
+                let element_type = &column_type[..column_type.len() - 2];
+                Some(format!(
+                    "{}::{}",
+                    self.array_literal(items, element_type),
+                    column_type
+                ))
+            }
+            // Any other array is a composite, any other object a range or an
+            // hstore: written in their text form, cast to the column type.
+            Value::Array(fields) => Some(format!(
+                "{}::{}",
+                quoted(&pg_types::composite_text(fields)),
+                column_type
+            )),
+            Value::Object(_) if binary::bytes_hex(val).is_none() => {
+                let text = if column_type.ends_with("hstore") {
+                    pg_types::hstore_text(val)
+                } else {
+                    pg_types::range_text(val)
+                };
+                text.map(|t| format!("{}::{}", quoted(&t), column_type))
+            }
+            // Text-like columns take a quoted literal as is; anything else
+            // (uuid, enums, ranges, intervals, inet, arrays decoded as text, …)
+            // gets an explicit cast.
+            Value::String(s) if !is_pg_text_type(column_type) => {
+                Some(format!("{}::{}", quoted(s), column_type))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl PostgresDialect {
+    /// `ARRAY[…]` constructor for a decoded array, nested arrays included,
+    /// elements typed as `element_type`. The caller casts the outermost one
+    /// to the column type, which also types an empty `ARRAY[]`.
+    fn array_literal(&self, items: &[Value], element_type: &str) -> String {
+        let elements: Vec<String> = items
+            .iter()
+            .map(|v| match v {
+                Value::Array(inner) => self.array_literal(inner, element_type),
+                other => self.sql_literal(other, Some(element_type)),
+            })
+            .collect();
+        format!("ARRAY[{}]", elements.join(", "))
+    }
 }
 
 impl RowDecoder for PostgresDialect {
     fn decode_column(&self, row: &AnyRow, idx: usize, type_hint: &str) -> Result<Value> {
-        col_to_json(row, idx, type_hint)
+        let value = col_to_json(row, idx, type_hint)?;
+        Ok(temporal::canonical_value(value, type_hint))
     }
 }
 
@@ -157,17 +569,196 @@ impl QueryDialect for MysqlDialect {
     fn is_native_type(&self, data_type: &str) -> bool {
         matches!(
             data_type.to_lowercase().as_str(),
-            "int" | "mediumint" | "bigint" | "float" | "double"
+            "int"
+                | "mediumint"
+                | "bigint"
+                | "float"
+                | "double"
+                // Binary: read as raw bytes, never through a charset conversion.
+                | "binary"
+                | "varbinary"
+                | "tinyblob"
+                | "blob"
+                | "mediumblob"
+                | "longblob"
         )
     }
 
+    fn key_order_expr(&self, col_quoted: &str, data_type: &str) -> String {
+        // DECIMAL comes back as BLOB but is decoded to a number, temporal
+        // types to their canonical text; every other non-native type is
+        // decoded as a UTF-8 string.
+        let numeric = matches!(
+            data_type.to_lowercase().as_str(),
+            "decimal" | "numeric" | "bigint unsigned"
+        );
+        if self.is_native_type(data_type) || numeric || temporal::sorts_by_value(data_type) {
+            col_quoted.to_string()
+        } else {
+            format!("CAST(CONVERT({} USING utf8mb4) AS BINARY)", col_quoted)
+        }
+    }
+
+    fn checksum_expr(&self, cols_quoted: &[String]) -> Option<String> {
+        // HEX keeps the separator out of the values and 'n' (not a hex
+        // digit) tells NULL apart from an empty string.
+        let parts: Vec<String> = cols_quoted
+            .iter()
+            .map(|c| format!("COALESCE(HEX(CAST({} AS CHAR)), 'n')", c))
+            .collect();
+        Some(format!(
+            "CAST(COALESCE(SUM(CAST(CONV(SUBSTRING(MD5(CONCAT_WS('|', {})), 1, 15), 16, 10) AS UNSIGNED)), 0) AS CHAR)",
+            parts.join(", ")
+        ))
+    }
+
+    fn consistent_read_sql(&self) -> &'static [&'static str] {
+        &[
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+            "START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY",
+        ]
+    }
+
+    fn session_sql(&self) -> &'static [&'static str] {
+        // TIMESTAMP values are read and written in the session time zone:
+        // UTC makes them instants, whatever the server's zone.
+        &["SET time_zone = '+00:00'"]
+    }
+
     fn introspect_sql(&self) -> &'static str {
-        "SELECT column_name, data_type \
+        // TINYINT(1) is MySQL's boolean: keep the display width so the
+        // decoder can tell flags from small integers. BIGINT UNSIGNED does
+        // not fit the native i64 and is read as text instead.
+        "SELECT column_name, \
+         CASE WHEN column_type LIKE 'tinyint(1)%' THEN 'tinyint(1)' \
+              WHEN column_type LIKE 'bigint%unsigned%' THEN 'bigint unsigned' \
+              ELSE data_type END AS data_type \
          FROM information_schema.columns \
          WHERE table_schema = ? AND table_name = ? \
          ORDER BY ordinal_position"
     }
+
+    fn column_types_sql(&self) -> Option<&'static str> {
+        Some(
+            "SELECT column_name, column_type \
+             FROM information_schema.columns \
+             WHERE table_schema = ? AND table_name = ? \
+             ORDER BY ordinal_position",
+        )
+    }
+
+    fn discover_tables_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT t.table_name, k.column_name \
+             FROM information_schema.tables t \
+             LEFT JOIN information_schema.table_constraints c \
+               ON c.table_schema = t.table_schema AND c.table_name = t.table_name \
+              AND c.constraint_type = 'PRIMARY KEY' \
+             LEFT JOIN information_schema.key_column_usage k \
+               ON k.table_schema = c.table_schema AND k.table_name = c.table_name \
+              AND k.constraint_name = c.constraint_name \
+             WHERE t.table_schema = {} AND t.table_type = 'BASE TABLE' \
+             ORDER BY t.table_name, k.ordinal_position",
+            self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn describe_columns_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT c.table_name, c.column_name, c.column_default \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t \
+               ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE c.table_schema = {} AND t.table_type = 'BASE TABLE' \
+             ORDER BY c.table_name, c.ordinal_position",
+            self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn foreign_keys_sql(&self, schema: &str) -> String {
+        format!(
+            "SELECT table_name, constraint_name, column_name, referenced_table_name \
+             FROM information_schema.key_column_usage \
+             WHERE table_schema = {schema} AND referenced_table_schema = {schema} \
+             ORDER BY table_name, constraint_name, ordinal_position",
+            schema = self.sql_literal(&Value::String(schema.to_string()), None)
+        )
+    }
+
+    fn defer_foreign_keys_sql(&self) -> &'static str {
+        // MySQL cannot defer: checks are off until restored, and rows
+        // written meanwhile are never checked.
+        "SET FOREIGN_KEY_CHECKS = 0"
+    }
+
+    fn restore_foreign_keys_sql(&self) -> Option<&'static str> {
+        Some("SET FOREIGN_KEY_CHECKS = 1")
+    }
+
+    fn delete_one_sql(&self, table: &str, predicate: &str) -> String {
+        format!("DELETE FROM {} WHERE {} LIMIT 1", table, predicate)
+    }
+
+    fn equals_sql(&self, col_quoted: &str, literal: &str, column_type: Option<&str>) -> String {
+        match column_type {
+            Some(t) if t.to_lowercase().starts_with("float") => {
+                format!("{} = CAST({} AS FLOAT)", col_quoted, literal)
+            }
+            _ => format!("{} = {}", col_quoted, literal),
+        }
+    }
+
+    fn upsert_clause(&self, key_cols: &[String], update_cols: &[String]) -> String {
+        // The inserted row is referred to through a row alias (MySQL
+        // 8.0.19+): `VALUES(col)` is deprecated since 8.0.20.
+        let set = duplicate_key_assignments(key_cols, update_cols, |c| format!("new.{}", c));
+        format!("AS new ON DUPLICATE KEY UPDATE {}", set)
+    }
     // json_literal: default (no ::jsonb cast)
+
+    fn bytes_literal(&self, hex: &str) -> String {
+        // `0x` needs at least one digit.
+        if hex.is_empty() {
+            "X''".to_string()
+        } else {
+            format!("0x{}", hex)
+        }
+    }
+
+    fn typed_literal(&self, val: &Value, column_type: &str) -> Option<String> {
+        let column_type = column_type.to_lowercase();
+        if column_type == "json" && binary::bytes_hex(val).is_none() {
+            return Some(format!("CAST('{}' AS JSON)", json_text(val)));
+        }
+        let s = val.as_str()?;
+        // Fractional seconds are dropped by a cast without precision: carry
+        // the column's over (`datetime(6)` → `DATETIME(6)`), or the value's
+        // when the type has none (a range bound typed by `DATA_TYPE`).
+        let precision = match (column_type.find('('), s.rsplit_once('.')) {
+            (Some(i), _) => column_type[i..].to_string(),
+            (None, Some((_, fraction))) => format!("({})", fraction.len()),
+            (None, None) => String::new(),
+        };
+        let target = if column_type.starts_with("datetime") || column_type.starts_with("timestamp")
+        {
+            // An instant is written in UTC and converted to the zone of the
+            // session running the script.
+            if let Some(utc) = temporal::parse_canonical_instant(s) {
+                return Some(format!(
+                    "CONVERT_TZ({}, '+00:00', @@session.time_zone)",
+                    quoted(&temporal::format_naive(&utc))
+                ));
+            }
+            format!("DATETIME{}", precision)
+        } else if column_type.starts_with("time") {
+            format!("TIME{}", precision)
+        } else if column_type == "date" {
+            "DATE".to_string()
+        } else {
+            return None;
+        };
+        Some(format!("CAST({} AS {})", quoted(s), target))
+    }
 }
 
 impl RowDecoder for MysqlDialect {
@@ -175,11 +766,12 @@ impl RowDecoder for MysqlDialect {
         // MySQL returns non-native columns as BLOB regardless of any SQL cast.
         // Detect at runtime and read raw bytes, then reinterpret using the type hint.
         let anyrow_type = row.column(idx).type_info().name();
-        if anyrow_type == "BLOB" {
-            blob_to_json(row, idx, type_hint)
+        let value = if anyrow_type == "BLOB" {
+            blob_to_json(row, idx, type_hint)?
         } else {
-            col_to_json(row, idx, type_hint)
-        }
+            col_to_json(row, idx, type_hint)?
+        };
+        Ok(temporal::canonical_value(value, type_hint))
     }
 }
 
@@ -203,9 +795,71 @@ impl QueryDialect for MariadbDialect {
         MysqlDialect.is_native_type(data_type)
     }
 
+    fn key_order_expr(&self, col_quoted: &str, data_type: &str) -> String {
+        MysqlDialect.key_order_expr(col_quoted, data_type)
+    }
+
+    fn checksum_expr(&self, cols_quoted: &[String]) -> Option<String> {
+        MysqlDialect.checksum_expr(cols_quoted)
+    }
+
+    fn consistent_read_sql(&self) -> &'static [&'static str] {
+        MysqlDialect.consistent_read_sql()
+    }
+
+    fn session_sql(&self) -> &'static [&'static str] {
+        MysqlDialect.session_sql()
+    }
+
     fn introspect_sql(&self) -> &'static str {
         MysqlDialect.introspect_sql()
     }
+
+    fn discover_tables_sql(&self, schema: &str) -> String {
+        MysqlDialect.discover_tables_sql(schema)
+    }
+
+    fn column_types_sql(&self) -> Option<&'static str> {
+        MysqlDialect.column_types_sql()
+    }
+
+    fn describe_columns_sql(&self, schema: &str) -> String {
+        MysqlDialect.describe_columns_sql(schema)
+    }
+
+    fn foreign_keys_sql(&self, schema: &str) -> String {
+        MysqlDialect.foreign_keys_sql(schema)
+    }
+
+    fn defer_foreign_keys_sql(&self) -> &'static str {
+        MysqlDialect.defer_foreign_keys_sql()
+    }
+
+    fn restore_foreign_keys_sql(&self) -> Option<&'static str> {
+        MysqlDialect.restore_foreign_keys_sql()
+    }
+
+    fn delete_one_sql(&self, table: &str, predicate: &str) -> String {
+        MysqlDialect.delete_one_sql(table, predicate)
+    }
+
+    fn equals_sql(&self, col_quoted: &str, literal: &str, column_type: Option<&str>) -> String {
+        MysqlDialect.equals_sql(col_quoted, literal, column_type)
+    }
+
+    fn upsert_clause(&self, key_cols: &[String], update_cols: &[String]) -> String {
+        // MariaDB has no row alias on INSERT: `VALUES(col)` is its form.
+        let set = duplicate_key_assignments(key_cols, update_cols, |c| format!("VALUES({})", c));
+        format!("ON DUPLICATE KEY UPDATE {}", set)
+    }
+
+    fn bytes_literal(&self, hex: &str) -> String {
+        MysqlDialect.bytes_literal(hex)
+    }
+
+    fn typed_literal(&self, val: &Value, column_type: &str) -> Option<String> {
+        MysqlDialect.typed_literal(val, column_type)
+    }
 }
 
 impl RowDecoder for MariadbDialect {
@@ -250,11 +904,66 @@ impl QueryDialect for SqliteDialect {
         )
     }
 
+    fn key_order_expr(&self, col_quoted: &str, _data_type: &str) -> String {
+        // Numbers sort before text in SQLite; BINARY overrides a declared
+        // NOCASE/RTRIM collation so text compares bytewise.
+        format!("{} COLLATE BINARY", col_quoted)
+    }
+
+    fn consistent_read_sql(&self) -> &'static [&'static str] {
+        // A deferred transaction only takes its snapshot at the first read.
+        &["BEGIN", "SELECT COUNT(*) FROM sqlite_master"]
+    }
+
     fn introspect_sql(&self) -> &'static str {
         // SQLite does not have information_schema; this path is not used.
         // fetch_column_types is only called for postgres/mysql/mariadb.
         ""
     }
+
+    fn discover_tables_sql(&self, _schema: &str) -> String {
+        // `pk` is the 1-based position in the primary key, 0 for other columns.
+        "SELECT m.name, p.name \
+         FROM sqlite_master m \
+         LEFT JOIN pragma_table_info(m.name) p ON p.pk > 0 \
+         WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+         ORDER BY m.name, p.pk"
+            .to_string()
+    }
+
+    fn describe_columns_sql(&self, _schema: &str) -> String {
+        "SELECT m.name, p.name, p.dflt_value \
+         FROM sqlite_master m \
+         JOIN pragma_table_info(m.name) p \
+         WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+         ORDER BY m.name, p.cid"
+            .to_string()
+    }
+
+    fn foreign_keys_sql(&self, _schema: &str) -> String {
+        // A key has no name: its `id` tells the keys of one table apart.
+        "SELECT m.name, CAST(p.id AS TEXT), p.\"from\", p.\"table\" \
+         FROM sqlite_master m \
+         JOIN pragma_foreign_key_list(m.name) p \
+         WHERE m.type = 'table' \
+         ORDER BY m.name, p.id, p.seq"
+            .to_string()
+    }
+
+    fn defer_foreign_keys_sql(&self) -> &'static str {
+        "PRAGMA defer_foreign_keys = ON"
+    }
+
+    fn delete_one_sql(&self, table: &str, predicate: &str) -> String {
+        format!(
+            "DELETE FROM {} WHERE rowid = (SELECT rowid FROM {} WHERE {} LIMIT 1)",
+            table, table, predicate
+        )
+    }
+
+    fn row_count_sql(&self) -> &'static str {
+        "changes()"
+    }
     // json_literal: default (no ::jsonb cast)
 }
 
@@ -262,6 +971,11 @@ impl RowDecoder for SqliteDialect {
     fn decode_column(&self, row: &AnyRow, idx: usize, type_hint: &str) -> Result<Value> {
         col_to_json(row, idx, type_hint)
     }
+
+    fn normalize(&self, value: Value, type_hint: &str) -> Value {
+        // No temporal types: timestamps are TEXT, recognised by their shape.
+        normalize::portable_text(normalize::portable_value(value, type_hint))
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -290,26 +1004,94 @@ impl Dialect for SqliteDialect {}
 // Shared decoding helpers (private to this module)
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Decode a BLOB column (MySQL/MariaDB non-native types) as raw UTF-8 bytes,
-/// then reinterpret the string using the `information_schema` type hint.
+/// Decode a BLOB column (MySQL/MariaDB non-native types and binary columns)
+/// as raw bytes: binary columns keep them as is, anything else is UTF-8 text
+/// reinterpreted using the `information_schema` type hint.
 fn blob_to_json(row: &AnyRow, idx: usize, type_hint: &str) -> Result<Value> {
     let bytes: Option<Vec<u8>> = row.try_get(idx)?;
     let Some(b) = bytes else {
         return Ok(Value::Null);
     };
-    let s = String::from_utf8(b).unwrap_or_default();
-    Ok(match type_hint.to_uppercase().as_str() {
-        "DECIMAL" | "NUMERIC" => s
-            .parse::<f64>()
-            .ok()
-            .and_then(serde_json::Number::from_f64)
-            .map(Value::Number)
-            .unwrap_or(Value::String(s)),
+    let hint = type_hint.to_uppercase();
+    if is_binary_type(&hint) {
+        return Ok(binary::bytes_value(&b));
+    }
+    // Text that is not UTF-8 is kept as bytes rather than lost.
+    let s = match String::from_utf8(b) {
+        Ok(s) => s,
+        Err(e) => return Ok(binary::bytes_value(e.as_bytes())),
+    };
+    Ok(match hint.as_str() {
+        "DECIMAL" | "NUMERIC" | "BIGINT UNSIGNED" => exact_number(s),
         "JSON" | "JSONB" => serde_json::from_str(&s).unwrap_or(Value::String(s)),
         _ => Value::String(s),
     })
 }
 
+/// Hints given by `PostgresDialect::introspect_sql` to the types decoded by
+/// `pg_types` (upper-cased, as matched in `col_to_json`).
+fn is_pg_structured_type(hint: &str) -> bool {
+    hint.starts_with("ARRAY:")
+        || hint.starts_with("RANGE:")
+        || hint == "HSTORE"
+        || hint == "COMPOSITE"
+}
+
+/// `'…'` with embedded quotes doubled.
+/// `col = <inserted value of col>, …` for `ON DUPLICATE KEY UPDATE`. With
+/// no column to update, a no-op assignment still ignores the duplicate.
+fn duplicate_key_assignments(
+    key_cols: &[String],
+    update_cols: &[String],
+    inserted: impl Fn(&str) -> String,
+) -> String {
+    let set: Vec<String> = if update_cols.is_empty() {
+        key_cols.iter().map(|c| format!("{} = {}", c, c)).collect()
+    } else {
+        update_cols
+            .iter()
+            .map(|c| format!("{} = {}", c, inserted(c)))
+            .collect()
+    };
+    set.join(", ")
+}
+
+fn quoted(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Compact JSON text of `val`, quotes doubled for a `'…'` literal.
+fn json_text(val: &Value) -> String {
+    serde_json::to_string(val)
+        .unwrap_or_default()
+        .replace('\'', "''")
+}
+
+/// PostgreSQL types taking a quoted literal without a cast, as spelled by
+/// `format_type` (`character varying(100)`, `text`, …).
+fn is_pg_text_type(column_type: &str) -> bool {
+    column_type == "text"
+        || column_type == "name"
+        || column_type == "\"char\""
+        || column_type.starts_with("character")
+}
+
+/// Binary column types of every dialect (upper case).
+fn is_binary_type(hint: &str) -> bool {
+    matches!(
+        hint,
+        "BYTEA" | "BLOB" | "BINARY" | "VARBINARY" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB"
+    )
+}
+
+/// A NUMERIC / DECIMAL (or unsigned 64-bit) value rendered as text, kept
+/// digit for digit as a JSON number; `NaN` and `Infinity` stay text.
+pub(crate) fn exact_number(s: String) -> Value {
+    s.trim()
+        .parse::<serde_json::Number>()
+        .map_or(Value::String(s), Value::Number)
+}
+
 /// Decode a column whose AnyRow type is supported natively or has been
 /// cast to TEXT in the SELECT query.
 fn col_to_json(row: &AnyRow, idx: usize, type_name: &str) -> Result<Value> {
@@ -350,15 +1132,9 @@ fn col_to_json(row: &AnyRow, idx: usize, type_name: &str) -> Result<Value> {
             .map_or(Value::Null, |v| json!(v)),
 
         // ── NUMERIC / DECIMAL → cast to TEXT in SELECT, parse back to Number ─
-        "NUMERIC" | "DECIMAL" => match row.try_get::<Option<String>, _>(idx)? {
-            None => Value::Null,
-            Some(s) => s
-                .parse::<f64>()
-                .ok()
-                .and_then(serde_json::Number::from_f64)
-                .map(Value::Number)
-                .unwrap_or(Value::String(s)),
-        },
+        "NUMERIC" | "DECIMAL" | "BIGINT UNSIGNED" => row
+            .try_get::<Option<String>, _>(idx)?
+            .map_or(Value::Null, exact_number),
 
         // ── JSON / JSONB → cast to TEXT in SELECT, parse back to Value ────────
         "JSON" | "JSONB" => match row.try_get::<Option<String>, _>(idx)? {
@@ -366,10 +1142,27 @@ fn col_to_json(row: &AnyRow, idx: usize, type_name: &str) -> Result<Value> {
             Some(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
         },
 
-        // ── ARRAY (PostgreSQL) → stored as Value::String ──────────────────────
-        "ARRAY" => row
+        // ── BYTEA → cast to TEXT in SELECT (`\x` + hex), read back as bytes ──
+        "BYTEA" => match row.try_get::<Option<String>, _>(idx)? {
+            None => Value::Null,
+            Some(s) => match s.strip_prefix("\\x").and_then(binary::decode_hex) {
+                Some(bytes) => binary::bytes_value(&bytes),
+                None => Value::String(s),
+            },
+        },
+
+        // ── Binary columns read natively (SQLite BLOB, MySQL VARBINARY …) ────
+        hint if is_binary_type(hint) => row
+            .try_get::<Option<Vec<u8>>, _>(idx)?
+            .map_or(Value::Null, |b| binary::bytes_value(&b)),
+
+        // ── PostgreSQL arrays, ranges, hstore, composites → cast to TEXT in
+        //    SELECT, parsed into structured values (see `pg_types`) ─────────
+        hint if is_pg_structured_type(hint) => row
             .try_get::<Option<String>, _>(idx)?
-            .map_or(Value::Null, Value::String),
+            .map_or(Value::Null, |s| {
+                pg_types::decode(s, &type_name.to_lowercase())
+            }),
 
         // ── Everything else: TEXT, VARCHAR, CHAR, UUID, TIMESTAMP, DATE …
         _ => row
@@ -444,6 +1237,88 @@ mod tests {
         );
     }
 
+    // ── QueryDialect — key_order_expr ───────────────────────────────────────
+
+    #[test]
+    fn test_key_order_expr_numbers_sort_natively() {
+        assert_eq!(
+            PostgresDialect.key_order_expr(r#""id""#, "bigint"),
+            r#""id""#
+        );
+        assert_eq!(
+            PostgresDialect.key_order_expr(r#""n""#, "numeric"),
+            r#""n""#
+        );
+        assert_eq!(MysqlDialect.key_order_expr("`id`", "int"), "`id`");
+        assert_eq!(MysqlDialect.key_order_expr("`n`", "decimal"), "`n`");
+    }
+
+    #[test]
+    fn test_key_order_expr_dates_and_times_sort_by_value() {
+        let tz = "timestamp with time zone";
+        assert_eq!(PostgresDialect.key_order_expr(r#""ts""#, tz), r#""ts""#);
+        assert_eq!(PostgresDialect.key_order_expr(r#""d""#, "date"), r#""d""#);
+        assert_eq!(
+            PostgresDialect.key_order_expr(r#""t""#, "time with time zone"),
+            r#""t"::TEXT COLLATE "C""#
+        );
+        assert_eq!(MysqlDialect.key_order_expr("`ts`", "timestamp"), "`ts`");
+        assert_eq!(MysqlDialect.key_order_expr("`at`", "datetime"), "`at`");
+    }
+
+    #[test]
+    fn test_key_order_expr_text_sorts_bytewise() {
+        assert_eq!(
+            PostgresDialect.key_order_expr(r#""code""#, "character varying"),
+            r#""code"::TEXT COLLATE "C""#
+        );
+        assert_eq!(
+            MysqlDialect.key_order_expr("`code`", "varchar"),
+            "CAST(CONVERT(`code` USING utf8mb4) AS BINARY)"
+        );
+        assert_eq!(
+            SqliteDialect.key_order_expr(r#""code""#, ""),
+            r#""code" COLLATE BINARY"#
+        );
+    }
+
+    // ── QueryDialect — checksum_expr ────────────────────────────────────────
+
+    #[test]
+    fn test_checksum_expr_hashes_listed_columns() {
+        let cols = vec![r#""id""#.to_string(), r#""name""#.to_string()];
+        let pg = PostgresDialect.checksum_expr(&cols).unwrap();
+        assert!(pg.contains(r#"ROW("id", "name")"#), "got: {pg}");
+
+        let cols = vec!["`id`".to_string(), "`name`".to_string()];
+        let my = MysqlDialect.checksum_expr(&cols).unwrap();
+        assert!(my.contains("HEX(CAST(`name` AS CHAR))"), "got: {my}");
+        assert_eq!(MariadbDialect.checksum_expr(&cols), Some(my));
+    }
+
+    #[test]
+    fn test_checksum_expr_unsupported_on_sqlite() {
+        assert!(SqliteDialect
+            .checksum_expr(&["\"id\"".to_string()])
+            .is_none());
+    }
+
+    // ── QueryDialect — consistent read ──────────────────────────────────────
+
+    #[test]
+    fn test_only_postgres_exports_snapshots() {
+        assert!(PostgresDialect.export_snapshot_sql().is_some());
+        assert!(MysqlDialect.export_snapshot_sql().is_none());
+        assert!(SqliteDialect.export_snapshot_sql().is_none());
+    }
+
+    #[test]
+    fn test_postgres_import_snapshot_escapes_id() {
+        let stmts = PostgresDialect.import_snapshot_sql("0000-1'x");
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(stmts[1], "SET TRANSACTION SNAPSHOT '0000-1''x'");
+    }
+
     // ── QueryDialect — is_native_type ──────────────────────────────────────
 
     #[test]
@@ -473,34 +1348,147 @@ mod tests {
 
     #[test]
     fn test_sql_literal_null() {
-        assert_eq!(PostgresDialect.sql_literal(&Value::Null), "NULL");
-        assert_eq!(MysqlDialect.sql_literal(&Value::Null), "NULL");
+        assert_eq!(PostgresDialect.sql_literal(&Value::Null, None), "NULL");
+        assert_eq!(MysqlDialect.sql_literal(&Value::Null, None), "NULL");
     }
 
     #[test]
     fn test_sql_literal_bool() {
-        assert_eq!(PostgresDialect.sql_literal(&Value::Bool(true)), "TRUE");
-        assert_eq!(MysqlDialect.sql_literal(&Value::Bool(false)), "FALSE");
+        assert_eq!(
+            PostgresDialect.sql_literal(&Value::Bool(true), None),
+            "TRUE"
+        );
+        assert_eq!(MysqlDialect.sql_literal(&Value::Bool(false), None), "FALSE");
+    }
+
+    #[test]
+    fn test_sql_literal_decimal_is_verbatim() {
+        let money = exact_number("12345678901234567890.123456789012".to_string());
+        assert_eq!(
+            PostgresDialect.sql_literal(&money, None),
+            "12345678901234567890.123456789012"
+        );
+        let unsigned = exact_number("18446744073709551615".to_string());
+        assert_eq!(
+            MysqlDialect.sql_literal(&unsigned, None),
+            "18446744073709551615"
+        );
+        assert_eq!(exact_number("NaN".to_string()), Value::String("NaN".into()));
+    }
+
+    #[test]
+    fn test_sql_literal_bytes_per_dialect() {
+        let v = binary::bytes_value(&[0xde, 0xad, 0x00]);
+        assert_eq!(PostgresDialect.sql_literal(&v, None), r"'\xdead00'::bytea");
+        assert_eq!(MysqlDialect.sql_literal(&v, None), "0xdead00");
+        assert_eq!(MariadbDialect.sql_literal(&v, None), "0xdead00");
+        assert_eq!(SqliteDialect.sql_literal(&v, None), "X'dead00'");
+        assert_eq!(
+            MysqlDialect.sql_literal(&binary::bytes_value(&[]), None),
+            "X''"
+        );
+    }
+
+    #[test]
+    fn test_sql_literal_casts_to_column_type() {
+        let pg = |v: Value, t: &str| PostgresDialect.sql_literal(&v, Some(t));
+        assert_eq!(pg(json!("it's"), "text"), "'it''s'");
+        assert_eq!(pg(json!("a"), "character varying(10)"), "'a'");
+        assert_eq!(pg(json!("[1,5)"), "int4range"), "'[1,5)'::int4range");
+        assert_eq!(pg(json!("1 day"), "interval"), "'1 day'::interval");
+        assert_eq!(
+            pg(json!([[1, 2], [3, 4]]), "integer[]"),
+            "ARRAY[ARRAY[1, 2], ARRAY[3, 4]]::integer[]"
+        );
+        assert_eq!(pg(json!([]), "inet[]"), "ARRAY[]::inet[]");
+        assert_eq!(pg(json!("v"), "jsonb"), r#"'"v"'::jsonb"#);
+        assert_eq!(
+            pg(json!("2024-01-02T03:04:05Z"), "timestamp with time zone"),
+            "'2024-01-02T03:04:05Z'::timestamp with time zone"
+        );
+        assert_eq!(pg(json!(42), "bigint"), "42");
+        assert_eq!(pg(Value::Null, "uuid"), "NULL");
+
+        let my = |v: Value, t: &str| MysqlDialect.sql_literal(&v, Some(t));
+        assert_eq!(
+            my(json!("2024-01-02 03:04:05.123456"), "datetime(6)"),
+            "CAST('2024-01-02 03:04:05.123456' AS DATETIME(6))"
+        );
+        assert_eq!(
+            my(json!("2024-01-02 03:04:05"), "timestamp"),
+            "CAST('2024-01-02 03:04:05' AS DATETIME)"
+        );
+        assert_eq!(
+            my(json!("2024-01-02"), "date"),
+            "CAST('2024-01-02' AS DATE)"
+        );
+        assert_eq!(
+            my(json!("2024-01-02T03:04:05.500+00:00"), "timestamp(3)"),
+            "CONVERT_TZ('2024-01-02 03:04:05.500', '+00:00', @@session.time_zone)"
+        );
+        assert_eq!(
+            my(json!("2024-01-02T03:04:05Z"), "varchar(32)"),
+            "'2024-01-02T03:04:05Z'"
+        );
+        assert_eq!(my(json!({"k": 1}), "json"), r#"CAST('{"k":1}' AS JSON)"#);
+        assert_eq!(my(json!("a"), "enum('a','b')"), "'a'");
+        assert_eq!(
+            MariadbDialect.sql_literal(&json!("10:00:00"), Some("time")),
+            "CAST('10:00:00' AS TIME)"
+        );
+
+        assert_eq!(SqliteDialect.sql_literal(&json!("x"), Some("uuid")), "'x'");
+    }
+
+    #[test]
+    fn test_sql_literal_postgres_structured_values() {
+        let pg = |v: Value, t: &str| PostgresDialect.sql_literal(&v, Some(t));
+        assert_eq!(
+            pg(json!(["it's", null]), "text[]"),
+            "ARRAY['it''s', NULL]::text[]"
+        );
+        assert_eq!(
+            pg(
+                json!([{"lower": 1, "upper": 3, "lower_inc": true, "upper_inc": false}]),
+                "int4range[]"
+            ),
+            "ARRAY['[1,3)'::int4range]::int4range[]"
+        );
+        assert_eq!(
+            pg(
+                json!({"lower": null, "upper": "2024-01-01", "lower_inc": false, "upper_inc": false}),
+                "daterange"
+            ),
+            r#"'(,"2024-01-01")'::daterange"#
+        );
+        assert_eq!(
+            pg(json!({"a": "1", "b": null}), "hstore"),
+            r#"'"a"=>"1", "b"=>NULL'::hstore"#
+        );
+        assert_eq!(
+            pg(json!(["O'Neil", null]), "address"),
+            r#"'("O''Neil",)'::address"#
+        );
     }
 
     #[test]
     fn test_sql_literal_string_escapes() {
         let v = Value::String("it's fine".to_string());
-        assert_eq!(PostgresDialect.sql_literal(&v), "'it''s fine'");
-        assert_eq!(MysqlDialect.sql_literal(&v), "'it''s fine'");
+        assert_eq!(PostgresDialect.sql_literal(&v, None), "'it''s fine'");
+        assert_eq!(MysqlDialect.sql_literal(&v, None), "'it''s fine'");
     }
 
     #[test]
     fn test_sql_literal_json_postgres_has_cast() {
         let v = serde_json::json!({"k": "v"});
-        let lit = PostgresDialect.sql_literal(&v);
+        let lit = PostgresDialect.sql_literal(&v, None);
         assert!(lit.ends_with("::jsonb"), "Expected ::jsonb, got: {}", lit);
     }
 
     #[test]
     fn test_sql_literal_json_mysql_no_cast() {
         let v = serde_json::json!({"k": "v"});
-        let lit = MysqlDialect.sql_literal(&v);
+        let lit = MysqlDialect.sql_literal(&v, None);
         assert!(
             !lit.contains("::"),
             "MySQL must not have any cast, got: {}",
@@ -512,7 +1500,7 @@ mod tests {
     #[test]
     fn test_sql_literal_json_sqlite_no_cast() {
         let v = serde_json::json!([1, 2, 3]);
-        let lit = SqliteDialect.sql_literal(&v);
+        let lit = SqliteDialect.sql_literal(&v, None);
         assert!(
             !lit.contains("::"),
             "SQLite must not have any cast, got: {}",
@@ -1,4 +1,8 @@
 pub mod client;
 pub mod dialect;
+pub(crate) mod normalize;
+pub(crate) mod pg_types;
+pub(crate) mod read_session;
 pub mod row_mapper;
 pub mod sql_utils;
+pub(crate) mod temporal;
@@ -0,0 +1,218 @@
+use serde_json::{Number, Value};
+
+use crate::domain::decimal::Decimal;
+use crate::infrastructure::db::temporal;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Cross-driver value normalisation
+// ─────────────────────────────────────────────────────────────────────────────
+//
+// Each driver decodes the same logical value differently: MySQL reads a
+// TINYINT(1) flag as "1" where PostgreSQL reads `true`, SQLite stores
+// timestamps as free-form TEXT where PostgreSQL renders `timestamptz` with an
+// offset, DECIMAL 5.00 decodes to a float while an INTEGER decodes to an int.
+// When source and target use different drivers, decoded values are rewritten
+// into one driver-neutral form so equal values compare equal:
+//
+// - boolean columns (incl. MySQL `tinyint(1)`)   → `true` / `false`
+// - integer and decimal columns read as text      → JSON numbers
+// - numbers                                       → without trailing fraction
+//   zeros (`5.0` → `5`, `1.50` → `1.5`), digits otherwise kept exactly
+// - timestamps                                    → `YYYY-MM-DD HH:MM:SS[.fff]`
+//   (`temporal::canonical_utc`), converted to UTC when the value carries an
+//   offset (naive values are assumed to already be UTC)
+// - SQLite TEXT holding a JSON object / array     → the parsed document
+//
+// SQLite has no boolean type either, and its 0/1 integers carry no hint that
+// they are flags: the differ treats them as equal to `false` / `true`.
+//
+// Same-driver diffs never go through this module: their values (and the SQL
+// literals rendered from them) stay exactly as the database returned them.
+
+/// Rewrite `value`, decoded from a column of type `type_hint`, into the
+/// driver-neutral form described above.
+pub(crate) fn portable_value(value: Value, type_hint: &str) -> Value {
+    let hint = type_hint.to_lowercase();
+    match value {
+        Value::Number(n) if is_boolean_type(&hint) => match n.as_i64() {
+            Some(0) => Value::Bool(false),
+            Some(1) => Value::Bool(true),
+            _ => Value::Number(n),
+        },
+        Value::Number(n) => Value::Number(canonical_number(n)),
+        Value::String(s) if is_boolean_type(&hint) => {
+            parse_bool(&s).map_or(Value::String(s), Value::Bool)
+        }
+        Value::String(s) if is_numeric_type(&hint) => {
+            parse_number(&s).map_or(Value::String(s), Value::Number)
+        }
+        Value::String(s) if is_timestamp_type(&hint) => {
+            Value::String(canonical_timestamp(&s).unwrap_or(s))
+        }
+        other => other,
+    }
+}
+
+/// Rewrite a string that looks like a timestamp or a JSON document into the
+/// normalised form, regardless of the column type. For databases without
+/// temporal or JSON types (SQLite), where both are conventionally TEXT.
+pub(crate) fn portable_text(value: Value) -> Value {
+    match value {
+        Value::String(s) if s.starts_with('{') || s.starts_with('[') => {
+            match serde_json::from_str::<Value>(&s) {
+                Ok(doc @ (Value::Object(_) | Value::Array(_))) => doc,
+                _ => Value::String(s),
+            }
+        }
+        Value::String(s) => Value::String(canonical_timestamp(&s).unwrap_or(s)),
+        other => other,
+    }
+}
+
+fn is_boolean_type(hint: &str) -> bool {
+    matches!(hint, "boolean" | "bool" | "tinyint(1)" | "bit(1)")
+}
+
+fn is_numeric_type(hint: &str) -> bool {
+    matches!(
+        hint,
+        "tinyint"
+            | "smallint"
+            | "mediumint"
+            | "int"
+            | "integer"
+            | "bigint"
+            | "bigint unsigned"
+            | "int2"
+            | "int4"
+            | "int8"
+            | "year"
+            | "decimal"
+            | "numeric"
+            | "real"
+            | "float"
+            | "double"
+            | "double precision"
+    )
+}
+
+fn is_timestamp_type(hint: &str) -> bool {
+    hint.starts_with("timestamp") || hint == "datetime"
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_lowercase().as_str() {
+        "1" | "t" | "true" => Some(true),
+        "0" | "f" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_number(s: &str) -> Option<Number> {
+    s.trim().parse::<Number>().ok().map(canonical_number)
+}
+
+/// `5.0` → `5`, `1.50` → `1.5`: a DECIMAL read on one side equals an
+/// INTEGER or a differently scaled DECIMAL read on the other. Exact: the
+/// digits are rewritten, never rounded through `f64`.
+fn canonical_number(n: Number) -> Number {
+    Decimal::parse(&n.to_string())
+        .and_then(|d| d.normalized().to_string().parse().ok())
+        .unwrap_or(n)
+}
+
+/// Parse the timestamp renderings of PostgreSQL, MySQL and SQLite
+/// (`2024-01-01 10:00:00+02`, `2024-01-01T08:00:00.000Z`, …) into the
+/// canonical naive UTC text of `temporal`. `None` when `s` is not a full
+/// date-and-time.
+fn canonical_timestamp(s: &str) -> Option<String> {
+    let s = s.trim();
+    // Cheap shape check before trying formats: `YYYY-MM-DD` + separator.
+    let b = s.as_bytes();
+    if b.len() < 16 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b' ' | b'T') {
+        return None;
+    }
+    temporal::canonical_utc(s)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn mysql_tinyint_flag_equals_postgres_boolean() {
+        assert_eq!(portable_value(json!("1"), "tinyint(1)"), json!(true));
+        assert_eq!(portable_value(json!("0"), "tinyint(1)"), json!(false));
+        assert_eq!(portable_value(json!(true), "boolean"), json!(true));
+        // Wider TINYINT columns are plain integers.
+        assert_eq!(portable_value(json!("7"), "tinyint"), json!(7));
+    }
+
+    #[test]
+    fn integral_floats_equal_integers() {
+        assert_eq!(portable_value(json!(5.0), "numeric"), json!(5));
+        assert_eq!(portable_value(json!("5.00"), "decimal"), json!(5));
+        assert_eq!(portable_value(json!(0.25), "real"), json!(0.25));
+    }
+
+    #[test]
+    fn decimals_keep_every_digit() {
+        let money = "12345678901234567890.123456789012";
+        assert_eq!(portable_value(json!(money), "numeric").to_string(), money);
+        assert_eq!(portable_value(json!("1.50"), "decimal").to_string(), "1.5");
+        assert_eq!(
+            portable_value(json!("18446744073709551615"), "bigint unsigned").to_string(),
+            "18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn timestamps_with_offsets_are_converted_to_utc() {
+        let pg = portable_value(json!("2024-01-01 12:00:00+02"), "timestamp with time zone");
+        let sqlite = portable_text(json!("2024-01-01T10:00:00.000Z"));
+        assert_eq!(pg, json!("2024-01-01 10:00:00"));
+        assert_eq!(sqlite, pg);
+        // Decoded instants are already canonical; they end up naive too.
+        assert_eq!(
+            portable_value(json!("2024-01-01T10:00:00.500+00:00"), "timestamptz"),
+            portable_value(json!("2024-01-01 10:00:00.5"), "datetime")
+        );
+        assert_eq!(
+            portable_text(json!("2024-01-01 10:00")),
+            json!("2024-01-01 10:00:00")
+        );
+    }
+
+    #[test]
+    fn naive_timestamps_keep_fractional_seconds() {
+        assert_eq!(
+            portable_value(json!("2024-01-01T10:00:00.5"), "datetime"),
+            json!("2024-01-01 10:00:00.500")
+        );
+        assert_eq!(
+            portable_text(json!("2024-01-01 10:00:00.500")),
+            json!("2024-01-01 10:00:00.500")
+        );
+    }
+
+    #[test]
+    fn json_text_is_parsed() {
+        assert_eq!(
+            portable_text(json!(r#"{"a": [1, 2]}"#)),
+            json!({"a": [1, 2]})
+        );
+        assert_eq!(portable_text(json!("[not json")), json!("[not json"));
+    }
+
+    #[test]
+    fn non_temporal_text_is_left_alone() {
+        assert_eq!(portable_text(json!("2024-01-01")), json!("2024-01-01"));
+        assert_eq!(portable_text(json!("hello world")), json!("hello world"));
+        assert_eq!(portable_value(json!("1"), "text"), json!("1"));
+    }
+}
@@ -0,0 +1,493 @@
+//! PostgreSQL arrays, ranges, hstore and composite values: decoded from
+//! their text form (every such column is selected `::TEXT`) into structured
+//! JSON, and written back as text literals.
+//!
+//! | PostgreSQL                 | JSON                                                      |
+//! |----------------------------|-----------------------------------------------------------|
+//! | `{1,NULL,3}`               | `[1, null, 3]` (nested for multi-dimensional arrays)      |
+//! | `[1,5)`                    | `{"lower": 1, "upper": 5, "lower_inc": true, "upper_inc": false}` |
+//! | `empty`                    | `{"empty": true}`                                         |
+//! | `"a"=>"1", "b"=>NULL`      | `{"a": "1", "b": null}`                                   |
+//! | `(1,"Main St",)`           | `["1", "Main St", null]` (fields in declaration order)    |
+//!
+//! Type hints come from `PostgresDialect::introspect_sql`: `array:<element
+//! hint>`, `range:<subtype>`, `hstore` and `composite`.
+
+use serde_json::{Map, Value};
+
+use crate::domain::binary;
+use crate::infrastructure::db::dialect::exact_number;
+use crate::infrastructure::db::temporal;
+
+/// Decode `text`, read from a column whose hint is `hint`, into a value.
+/// Scalars of any other type are returned as strings.
+pub(crate) fn decode(text: String, hint: &str) -> Value {
+    let parsed = if let Some(element) = hint.strip_prefix("array:") {
+        parse_array(&text, element)
+    } else if let Some(subtype) = hint.strip_prefix("range:") {
+        parse_range(&text, subtype)
+    } else {
+        match hint {
+            "hstore" => parse_hstore(&text),
+            "composite" => parse_composite(&text),
+            _ => return scalar(text, hint),
+        }
+    };
+    parsed.unwrap_or(Value::String(text))
+}
+
+/// An array element, a range bound: numbers, booleans, JSON, bytes, dates
+/// and times are decoded like the columns of those types.
+fn scalar(text: String, hint: &str) -> Value {
+    match hint {
+        "smallint" | "integer" | "bigint" | "numeric" | "real" | "double precision" => {
+            exact_number(text)
+        }
+        "boolean" => match text.as_str() {
+            "t" => Value::Bool(true),
+            "f" => Value::Bool(false),
+            _ => Value::String(text),
+        },
+        "json" | "jsonb" => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        "bytea" => match text.strip_prefix("\\x").and_then(binary::decode_hex) {
+            Some(bytes) => binary::bytes_value(&bytes),
+            None => Value::String(text),
+        },
+        _ if hint.starts_with("array:")
+            || hint.starts_with("range:")
+            || hint == "hstore"
+            || hint == "composite" =>
+        {
+            decode(text, hint)
+        }
+        _ => temporal::canonical_value(Value::String(text), hint),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Parsing
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A token of the text forms: quoted (escapes resolved) or bare (trimmed).
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.chars.next();
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        if self.peek() == Some(c) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// A quoted token, or a bare one ending at any of `stops`. Inside quotes,
+    /// `\` escapes the next character and, when `doubled_quotes`, `""`
+    /// stands for `"` (ranges and composites).
+    fn token(&mut self, stops: &[char], doubled_quotes: bool) -> Option<Token> {
+        self.skip_spaces();
+        if self.peek() != Some('"') {
+            let mut text = String::new();
+            while let Some(c) = self.peek().filter(|c| !stops.contains(c)) {
+                self.chars.next();
+                text.push(if c == '\\' { self.chars.next()? } else { c });
+            }
+            return Some(Token {
+                text: text.trim_end().to_string(),
+                quoted: false,
+            });
+        }
+        self.chars.next();
+        let mut text = String::new();
+        loop {
+            match self.chars.next()? {
+                '\\' => text.push(self.chars.next()?),
+                '"' if doubled_quotes && self.peek() == Some('"') => {
+                    self.chars.next();
+                    text.push('"');
+                }
+                '"' => break,
+                c => text.push(c),
+            }
+        }
+        Some(Token { text, quoted: true })
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_spaces();
+        self.peek().is_none()
+    }
+}
+
+/// `{a,"b c",NULL}`, nested for multi-dimensional arrays. A leading
+/// dimension decoration (`[0:1]={…}`) is skipped.
+fn parse_array(text: &str, element: &str) -> Option<Value> {
+    let body = match text.find('=') {
+        Some(i) if text.starts_with('[') => &text[i + 1..],
+        _ => text,
+    };
+    let mut cursor = Cursor::new(body);
+    let value = array_items(&mut cursor, element)?;
+    cursor.at_end().then_some(value)
+}
+
+fn array_items(cursor: &mut Cursor, element: &str) -> Option<Value> {
+    if !cursor.eat('{') {
+        return None;
+    }
+    let mut items = Vec::new();
+    if cursor.eat('}') {
+        return Some(Value::Array(items));
+    }
+    loop {
+        cursor.skip_spaces();
+        if cursor.peek() == Some('{') {
+            items.push(array_items(cursor, element)?);
+        } else {
+            let token = cursor.token(&[',', '}'], false)?;
+            items.push(
+                if !token.quoted && token.text.eq_ignore_ascii_case("NULL") {
+                    Value::Null
+                } else {
+                    scalar(token.text, element)
+                },
+            );
+        }
+        if cursor.eat('}') {
+            return Some(Value::Array(items));
+        }
+        if !cursor.eat(',') {
+            return None;
+        }
+    }
+}
+
+/// `[1,5)`, `(,"2024-01-01")`, `empty`. An omitted bound is `null`.
+fn parse_range(text: &str, subtype: &str) -> Option<Value> {
+    if text.trim().eq_ignore_ascii_case("empty") {
+        return Some(serde_json::json!({"empty": true}));
+    }
+    let mut cursor = Cursor::new(text);
+    let lower_inc = if cursor.eat('[') {
+        true
+    } else if cursor.eat('(') {
+        false
+    } else {
+        return None;
+    };
+    let bound = |token: Token| {
+        if token.text.is_empty() && !token.quoted {
+            Value::Null
+        } else {
+            scalar(token.text, subtype)
+        }
+    };
+    let lower = bound(cursor.token(&[','], true)?);
+    if !cursor.eat(',') {
+        return None;
+    }
+    let upper = bound(cursor.token(&[']', ')'], true)?);
+    let upper_inc = if cursor.eat(']') {
+        true
+    } else if cursor.eat(')') {
+        false
+    } else {
+        return None;
+    };
+    if !cursor.at_end() {
+        return None;
+    }
+
+    let mut range = Map::new();
+    range.insert("lower".into(), lower);
+    range.insert("upper".into(), upper);
+    range.insert("lower_inc".into(), Value::Bool(lower_inc));
+    range.insert("upper_inc".into(), Value::Bool(upper_inc));
+    Some(Value::Object(range))
+}
+
+/// `"a"=>"1", "b"=>NULL`. Values stay strings, as hstore stores them.
+fn parse_hstore(text: &str) -> Option<Value> {
+    let mut cursor = Cursor::new(text);
+    let mut map = Map::new();
+    while !cursor.at_end() {
+        let key = cursor.token(&['='], false)?;
+        if !(cursor.eat('=') && cursor.eat('>')) {
+            return None;
+        }
+        let value = cursor.token(&[','], false)?;
+        let value = if !value.quoted && value.text.eq_ignore_ascii_case("NULL") {
+            Value::Null
+        } else {
+            Value::String(value.text)
+        };
+        map.insert(key.text, value);
+        if !cursor.eat(',') && !cursor.at_end() {
+            return None;
+        }
+    }
+    Some(Value::Object(map))
+}
+
+/// `(1,"Main St",)`: one string per field, `null` for an empty bare field.
+fn parse_composite(text: &str) -> Option<Value> {
+    let mut cursor = Cursor::new(text);
+    if !cursor.eat('(') {
+        return None;
+    }
+    let mut fields = Vec::new();
+    loop {
+        let token = cursor.token(&[',', ')'], true)?;
+        fields.push(if token.text.is_empty() && !token.quoted {
+            Value::Null
+        } else {
+            Value::String(token.text)
+        });
+        if cursor.eat(')') {
+            break;
+        }
+        if !cursor.eat(',') {
+            return None;
+        }
+    }
+    cursor.at_end().then_some(Value::Array(fields))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Rendering
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Text form of a decoded range, `None` if `value` is not one.
+pub(crate) fn range_text(value: &Value) -> Option<String> {
+    let range = value.as_object()?;
+    if range.len() == 1 && range.get("empty") == Some(&Value::Bool(true)) {
+        return Some("empty".to_string());
+    }
+    let keys = ["lower", "upper", "lower_inc", "upper_inc"];
+    if range.len() != keys.len() || !keys.iter().all(|k| range.contains_key(*k)) {
+        return None;
+    }
+    let bound = |v: &Value| match v {
+        Value::Null => Some(String::new()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(quote(s)),
+        _ => None,
+    };
+    Some(format!(
+        "{}{},{}{}",
+        if range["lower_inc"].as_bool()? {
+            '['
+        } else {
+            '('
+        },
+        bound(&range["lower"])?,
+        bound(&range["upper"])?,
+        if range["upper_inc"].as_bool()? {
+            ']'
+        } else {
+            ')'
+        },
+    ))
+}
+
+/// Text form of a decoded hstore, `None` unless every value is a string or
+/// `null`.
+pub(crate) fn hstore_text(value: &Value) -> Option<String> {
+    let pairs = value
+        .as_object()?
+        .iter()
+        .map(|(k, v)| match v {
+            Value::Null => Some(format!("{}=>NULL", quote(k))),
+            Value::String(s) => Some(format!("{}=>{}", quote(k), quote(s))),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(pairs.join(", "))
+}
+
+/// Text form of a decoded composite.
+pub(crate) fn composite_text(fields: &[Value]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|v| match v {
+            Value::Null => String::new(),
+            Value::String(s) => quote(s),
+            other => quote(&other.to_string()),
+        })
+        .collect();
+    format!("({})", fields.join(","))
+}
+
+/// A field of `COPY`'s text format: the value's text form with backslash,
+/// tab and line breaks escaped, `\N` for NULL.
+pub(crate) fn copy_text(value: &Value, column_type: Option<&str>) -> String {
+    match text_form(value, column_type) {
+        None => "\\N".to_string(),
+        Some(text) => text
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r"),
+    }
+}
+
+/// What PostgreSQL reads back as `value` from text, `None` for NULL.
+fn text_form(value: &Value, column_type: Option<&str>) -> Option<String> {
+    if let Some(hex) = binary::bytes_hex(value) {
+        return Some(format!("\\x{}", hex));
+    }
+    let column_type = column_type.unwrap_or_default();
+    Some(match value {
+        Value::Null => return None,
+        _ if matches!(column_type, "json" | "jsonb") => value.to_string(),
+        Value::Bool(b) => if *b { "t" } else { "f" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => match column_type.strip_suffix("[]") {
+            Some(element_type) => array_text(items, element_type),
+            None => composite_text(items),
+        },
+        Value::Object(_) if column_type.ends_with("hstore") => hstore_text(value)?,
+        Value::Object(_) => range_text(value).unwrap_or_else(|| value.to_string()),
+    })
+}
+
+/// Text form of a decoded array, nested arrays included.
+fn array_text(items: &[Value], element_type: &str) -> String {
+    let elements: Vec<String> = items
+        .iter()
+        .map(|v| match v {
+            Value::Array(inner) => array_text(inner, element_type),
+            other => match text_form(other, Some(element_type)) {
+                Some(text) => quote(&text),
+                None => "NULL".to_string(),
+            },
+        })
+        .collect();
+    format!("{{{}}}", elements.join(","))
+}
+
+/// `"…"` with `"` and `\` backslash-escaped, as every text form accepts.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn d(text: &str, hint: &str) -> Value {
+        decode(text.to_string(), hint)
+    }
+
+    #[test]
+    fn arrays_decode_elements_by_type() {
+        assert_eq!(d("{1,NULL,3}", "array:integer"), json!([1, null, 3]));
+        assert_eq!(
+            d(
+                r#"{a,"b c","NULL",NULL,"q\"uote","back\\slash"}"#,
+                "array:text"
+            ),
+            json!(["a", "b c", "NULL", null, "q\"uote", "back\\slash"])
+        );
+        assert_eq!(d("{{1,2},{3,4}}", "array:integer"), json!([[1, 2], [3, 4]]));
+        assert_eq!(d("[0:1]={t,f}", "array:boolean"), json!([true, false]));
+        assert_eq!(d("{}", "array:text"), json!([]));
+        assert_eq!(d(r#"{"{\"a\": 1}"}"#, "array:jsonb"), json!([{"a": 1}]));
+        assert_eq!(
+            d(r#"{"[1,3)",empty}"#, "array:range:integer"),
+            json!([
+                {"lower": 1, "upper": 3, "lower_inc": true, "upper_inc": false},
+                {"empty": true}
+            ])
+        );
+        // Malformed text is kept as is.
+        assert_eq!(d("{1,2", "array:integer"), json!("{1,2"));
+    }
+
+    #[test]
+    fn ranges_hstore_and_composites_round_trip() {
+        let range = d(
+            r#"["2024-01-01 10:00:00",)"#,
+            "range:timestamp without time zone",
+        );
+        assert_eq!(
+            range,
+            json!({"lower": "2024-01-01 10:00:00", "upper": null, "lower_inc": true, "upper_inc": false})
+        );
+        assert_eq!(range_text(&range).unwrap(), r#"["2024-01-01 10:00:00",)"#);
+        assert_eq!(range_text(&d("empty", "range:integer")).unwrap(), "empty");
+        assert_eq!(range_text(&d("[1,5)", "range:integer")).unwrap(), "[1,5)");
+        assert!(range_text(&json!({"lower": 1})).is_none());
+
+        let hstore = d(r#""a"=>"1", "b"=>NULL, "c\"d"=>"x,y""#, "hstore");
+        assert_eq!(hstore, json!({"a": "1", "b": null, "c\"d": "x,y"}));
+        assert_eq!(
+            hstore_text(&hstore).unwrap(),
+            r#""a"=>"1", "b"=>NULL, "c\"d"=>"x,y""#
+        );
+        assert_eq!(d("", "hstore"), json!({}));
+
+        let composite = d(r#"(1,"Main ""St""",)"#, "composite");
+        assert_eq!(composite, json!(["1", "Main \"St\"", null]));
+        assert_eq!(
+            composite_text(composite.as_array().unwrap()),
+            r#"("1","Main \"St\"",)"#
+        );
+    }
+
+    #[test]
+    fn copy_fields_use_the_text_form() {
+        let c = |v: Value, t: &str| copy_text(&v, Some(t));
+        assert_eq!(c(Value::Null, "text"), "\\N");
+        assert_eq!(
+            c(json!("tab\there\nback\\slash"), "text"),
+            "tab\\there\\nback\\\\slash"
+        );
+        assert_eq!(c(json!(true), "boolean"), "t");
+        assert_eq!(c(json!({"a": [1, "x"]}), "jsonb"), r#"{"a":[1,"x"]}"#);
+        assert_eq!(c(json!("42"), "jsonb"), r#""42""#);
+        assert_eq!(
+            c(json!([[1, null], [3, 4]]), "integer[]"),
+            "{{\"1\",NULL},{\"3\",\"4\"}}"
+        );
+        assert_eq!(c(json!(["a b", "q\""]), "text[]"), r#"{"a b","q\\""}"#);
+        assert_eq!(
+            c(
+                json!({"lower": 1, "upper": 5, "lower_inc": true, "upper_inc": false}),
+                "int4range"
+            ),
+            "[1,5)"
+        );
+        assert_eq!(c(json!({"$bytes": "00ff"}), "bytea"), "\\\\x00ff");
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use sqlx::any::{AnyArguments, AnyRow};
+use sqlx::pool::PoolConnection;
+use sqlx::query::Query;
+use sqlx::{Any, AnyConnection, AnyPool, Executor, Row, Transaction};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::debug;
+
+use crate::infrastructure::db::dialect::QueryDialect;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ReadSession
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A read-only transaction shared by every read of one database, so all
+/// tables are seen at the same point in time.
+///
+/// - PostgreSQL exports the snapshot of a leader transaction; each read opens
+///   its own transaction and imports it, so tables are still read in parallel.
+/// - MySQL / MariaDB / SQLite cannot share a snapshot between connections:
+///   every read goes through the single connection holding the transaction.
+///
+/// Session connections are closed (not returned to the pool) when dropped,
+/// which ends the transaction.
+pub(crate) enum ReadSession {
+    Shared {
+        snapshot_id: String,
+        /// Keeps the exporting transaction — and thus the snapshot — alive.
+        _leader: Mutex<PoolConnection<Any>>,
+    },
+    Pinned(Arc<Mutex<PoolConnection<Any>>>),
+}
+
+impl ReadSession {
+    pub(crate) async fn open(pool: &AnyPool, dialect: &dyn QueryDialect) -> Result<Self> {
+        let mut conn = pool
+            .acquire()
+            .await
+            .context("Failed to acquire a connection for the consistent read")?;
+        conn.close_on_drop();
+
+        for stmt in dialect.consistent_read_sql() {
+            execute(&mut conn, stmt)
+                .await
+                .with_context(|| format!("Failed to start the consistent read: {}", stmt))?;
+        }
+
+        let Some(export_sql) = dialect.export_snapshot_sql() else {
+            debug!("Consistent read pinned to a single connection");
+            return Ok(ReadSession::Pinned(Arc::new(Mutex::new(conn))));
+        };
+
+        let row = sqlx::query(export_sql)
+            .fetch_one(&mut *conn)
+            .await
+            .context("Failed to export the read snapshot")?;
+        let snapshot_id: String = row.try_get(0)?;
+        debug!("Consistent read sharing snapshot {}", snapshot_id);
+
+        Ok(ReadSession::Shared {
+            snapshot_id,
+            _leader: Mutex::new(conn),
+        })
+    }
+
+    /// A connection that reads through this session's snapshot.
+    pub(crate) async fn reader(
+        &self,
+        pool: &AnyPool,
+        dialect: &dyn QueryDialect,
+    ) -> Result<Reader> {
+        match self {
+            ReadSession::Shared { snapshot_id, .. } => {
+                let mut tx = pool.begin().await?;
+                for stmt in dialect.import_snapshot_sql(snapshot_id) {
+                    execute(&mut tx, &stmt)
+                        .await
+                        .with_context(|| format!("Failed to import snapshot {}", snapshot_id))?;
+                }
+                Ok(Reader::Snapshot(tx))
+            }
+            ReadSession::Pinned(conn) => Ok(Reader::Pinned(Arc::clone(conn).lock_owned().await)),
+        }
+    }
+}
+
+/// Run a plain statement (see the note on the `Reader` helpers).
+fn execute<'a>(
+    conn: &'a mut AnyConnection,
+    sql: &'a str,
+) -> BoxFuture<'a, sqlx::Result<sqlx::any::AnyQueryResult>> {
+    conn.execute(sqlx::raw_sql(sql))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Reader
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Connection used for a single repository read.
+pub(crate) enum Reader {
+    /// No consistent read: any pool connection.
+    Pool(PoolConnection<Any>),
+    /// Short transaction that imported the shared snapshot (PostgreSQL).
+    /// Rolled back on drop.
+    Snapshot(Transaction<'static, Any>),
+    /// Exclusive use of the session connection until dropped.
+    Pinned(OwnedMutexGuard<PoolConnection<Any>>),
+}
+
+/// A query as built by the repository, ready to run on a `Reader`.
+pub(crate) type AnyQuery<'q> = Query<'q, Any, AnyArguments<'q>>;
+
+// The fetch helpers go through `Executor`'s boxed methods, whose lifetimes
+// are explicit: awaiting `query.fetch_all(&mut AnyConnection)` directly
+// inside an `async_trait` method trips the compiler's higher-ranked
+// `Executor` / `Send` checks.
+impl Reader {
+    fn conn(&mut self) -> &mut AnyConnection {
+        match self {
+            Reader::Pool(conn) => conn,
+            Reader::Snapshot(tx) => tx,
+            Reader::Pinned(conn) => conn,
+        }
+    }
+
+    pub(crate) fn fetch_all<'a>(
+        &'a mut self,
+        query: AnyQuery<'a>,
+    ) -> BoxFuture<'a, sqlx::Result<Vec<AnyRow>>> {
+        self.conn().fetch_all(query)
+    }
+
+    pub(crate) fn fetch_one<'a>(
+        &'a mut self,
+        query: AnyQuery<'a>,
+    ) -> BoxFuture<'a, sqlx::Result<AnyRow>> {
+        self.conn().fetch_one(query)
+    }
+
+    pub(crate) fn fetch_optional<'a>(
+        &'a mut self,
+        query: AnyQuery<'a>,
+    ) -> BoxFuture<'a, sqlx::Result<Option<AnyRow>>> {
+        self.conn().fetch_optional(query)
+    }
+
+    pub(crate) fn fetch<'a>(
+        &'a mut self,
+        query: AnyQuery<'a>,
+    ) -> BoxStream<'a, sqlx::Result<AnyRow>> {
+        self.conn().fetch(query)
+    }
+
+    /// `true` when every read of this database shares one connection.
+    pub(crate) fn is_pinned(&self) -> bool {
+        matches!(self, Reader::Pinned(_))
+    }
+}
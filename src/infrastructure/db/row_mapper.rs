@@ -11,10 +11,13 @@ use crate::infrastructure::db::dialect::RowDecoder;
 /// `col_types` maps column names to their `information_schema.data_type` values.
 /// `decoder` is the dialect-specific `RowDecoder` that knows how to turn an
 /// AnyRow column index + type hint into the correct `serde_json::Value`.
+/// With `normalize`, values are passed through `RowDecoder::normalize` so they
+/// compare equal with rows read from a database of another driver.
 pub fn row_to_map(
     row: &AnyRow,
     col_types: &BTreeMap<String, String>,
     decoder: &dyn RowDecoder,
+    normalize: bool,
 ) -> Result<RowMap> {
     let mut map = BTreeMap::new();
     for col in row.columns() {
@@ -28,7 +31,10 @@ pub fn row_to_map(
             .map(|s| s.as_str())
             .unwrap_or(anyrow_type);
 
-        let value = decoder.decode_column(row, col.ordinal(), type_hint)?;
+        let mut value = decoder.decode_column(row, col.ordinal(), type_hint)?;
+        if normalize {
+            value = decoder.normalize(value, type_hint);
+        }
         map.insert(name, value);
     }
     Ok(map)
@@ -1,6 +1,10 @@
-use crate::domain::table_diff::RowMap;
+use std::collections::BTreeMap;
+
+use crate::domain::key_range::KeyRange;
+use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate, TableDiff};
 use crate::domain::value_objects::{ColumnName, Schema, TableName};
 use crate::infrastructure::db::dialect::QueryDialect;
+use crate::infrastructure::db::temporal;
 use serde_json::Value;
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -46,27 +50,16 @@ pub fn build_typed_select_query(
 ) -> String {
     let prefix = dialect.schema_prefix(&schema.0);
     let table_q = dialect.quote_ident(&table.0);
-
-    let col_exprs: Vec<String> = col_types
-        .iter()
-        .map(|(col_name, data_type)| {
-            let q = dialect.quote_ident(col_name);
-            if dialect.is_native_type(data_type) {
-                q
-            } else {
-                dialect.cast_to_text(&q)
-            }
-        })
-        .collect();
+    let select_list = typed_select_list(col_types, dialect);
 
     let order_cols: Vec<String> = pk_cols.iter().map(|c| dialect.quote_ident(&c.0)).collect();
 
     if order_cols.is_empty() {
-        format!("SELECT {} FROM {}{}", col_exprs.join(", "), prefix, table_q)
+        format!("SELECT {} FROM {}{}", select_list, prefix, table_q)
     } else {
         format!(
             "SELECT {} FROM {}{} ORDER BY {}",
-            col_exprs.join(", "),
+            select_list,
             prefix,
             table_q,
             order_cols.join(", ")
@@ -74,24 +67,398 @@ pub fn build_typed_select_query(
     }
 }
 
+/// Build the SELECT used by the streaming (merge-join) path.
+///
+/// Same column list as [`build_typed_select_query`] (or `*` when `col_types`
+/// is empty, e.g. SQLite), but every key column is ordered through
+/// `QueryDialect::key_order_expr` so the database returns rows in the exact
+/// order the merge join compares keys, whatever the column collation.
+pub fn build_key_ordered_select_query(
+    schema: &Schema,
+    table: &TableName,
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    dialect: &dyn QueryDialect,
+) -> String {
+    build_range_select_query(
+        schema,
+        table,
+        pk_cols,
+        col_types,
+        &KeyRange::full(),
+        dialect,
+    )
+}
+
+/// Key-ordered SELECT restricted to the rows of `range` (bisection leaves).
+pub fn build_range_select_query(
+    schema: &Schema,
+    table: &TableName,
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    range: &KeyRange,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let select_list = if col_types.is_empty() {
+        "*".to_string()
+    } else {
+        typed_select_list(col_types, dialect)
+    };
+
+    format!(
+        "SELECT {} FROM {}{}{} ORDER BY {}",
+        select_list,
+        dialect.schema_prefix(&schema.0),
+        dialect.quote_ident(&table.0),
+        where_clause(pk_cols, col_types, range, dialect),
+        key_order_exprs(pk_cols, col_types, dialect).join(", ")
+    )
+}
+
+/// `SELECT COUNT(*), <checksum>` over the rows of `range`.
+/// `checksum_expr` comes from `QueryDialect::checksum_expr`.
+pub fn build_range_checksum_query(
+    schema: &Schema,
+    table: &TableName,
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    checksum_expr: &str,
+    range: &KeyRange,
+    dialect: &dyn QueryDialect,
+) -> String {
+    format!(
+        "SELECT COUNT(*), {} FROM {}{}{}",
+        checksum_expr,
+        dialect.schema_prefix(&schema.0),
+        dialect.quote_ident(&table.0),
+        where_clause(pk_cols, col_types, range, dialect)
+    )
+}
+
+/// SELECT the key columns of the row at `offset` within `range`
+/// (the split point of a bisection step).
+pub fn build_key_at_query(
+    schema: &Schema,
+    table: &TableName,
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    range: &KeyRange,
+    offset: u64,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let key_types: Vec<(String, String)> = pk_cols
+        .iter()
+        .map(|c| (c.0.clone(), column_type(col_types, &c.0).to_string()))
+        .collect();
+
+    format!(
+        "SELECT {} FROM {}{}{} ORDER BY {} LIMIT 1 OFFSET {}",
+        typed_select_list(&key_types, dialect),
+        dialect.schema_prefix(&schema.0),
+        dialect.quote_ident(&table.0),
+        where_clause(pk_cols, col_types, range, dialect),
+        key_order_exprs(pk_cols, col_types, dialect).join(", "),
+        offset
+    )
+}
+
+/// ` WHERE (k1, k2) > (…) AND (k1, k2) <= (…)` for a bounded range, or an
+/// empty string for the full range. Keys go through `key_order_expr` so the
+/// bounds compare exactly like the ORDER BY of the streaming queries; a
+/// temporal key is compared by value, against a bound typed like the column.
+fn where_clause(
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    range: &KeyRange,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let keys = format!(
+        "({})",
+        key_order_exprs(pk_cols, col_types, dialect).join(", ")
+    );
+    let tuple = |values: &[Value]| {
+        let literals: Vec<String> = values
+            .iter()
+            .zip(pk_cols)
+            .map(|(v, c)| {
+                let data_type = column_type(col_types, &c.0);
+                let typed = temporal::sorts_by_value(data_type).then_some(data_type);
+                dialect.sql_literal(v, typed)
+            })
+            .collect();
+        format!("({})", literals.join(", "))
+    };
+
+    let mut conditions = Vec::new();
+    if let Some(lower) = &range.lower {
+        conditions.push(format!("{} > {}", keys, tuple(lower)));
+    }
+    if let Some(upper) = &range.upper {
+        conditions.push(format!("{} <= {}", keys, tuple(upper)));
+    }
+
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    }
+}
+
+fn key_order_exprs(
+    pk_cols: &[ColumnName],
+    col_types: &[(String, String)],
+    dialect: &dyn QueryDialect,
+) -> Vec<String> {
+    pk_cols
+        .iter()
+        .map(|c| dialect.key_order_expr(&dialect.quote_ident(&c.0), column_type(col_types, &c.0)))
+        .collect()
+}
+
+/// `information_schema` type of `column`, or `""` when unknown (SQLite).
+fn column_type<'a>(col_types: &'a [(String, String)], column: &str) -> &'a str {
+    col_types
+        .iter()
+        .find(|(name, _)| name == column)
+        .map_or("", |(_, t)| t.as_str())
+}
+
+/// Comma-separated column list where non-native types are cast to text.
+fn typed_select_list(col_types: &[(String, String)], dialect: &dyn QueryDialect) -> String {
+    col_types
+        .iter()
+        .map(|(col_name, data_type)| {
+            let q = dialect.quote_ident(col_name);
+            if dialect.is_native_type(data_type) {
+                q
+            } else {
+                dialect.cast_to_text(&q)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
-// Row helpers
+// Statement builders
 // ─────────────────────────────────────────────────────────────────────────────
+//
+// Shared by the SQL/JSON writers (rendered scripts) and the changeset applier
+// (statements executed against the target), so both always agree on the SQL.
 
-/// Build the composite primary key string for a row (used as BTreeMap lookup key).
-pub fn pk_key(row: &RowMap, pk_cols: &[ColumnName]) -> String {
-    pk_cols
+/// Fully qualified, quoted table reference: `"schema"."table"` (or just
+/// `"table"` for dialects without schemas, e.g. SQLite).
+pub fn qualified_table(schema: &str, table: &str, dialect: &dyn QueryDialect) -> String {
+    format!(
+        "{}{}",
+        dialect.schema_prefix(schema),
+        dialect.quote_ident(table)
+    )
+}
+
+/// Single-line `INSERT INTO … (…) VALUES (…);` for an inserted row of `table`.
+pub fn insert_statement(
+    schema: &str,
+    table: &TableDiff,
+    row: &RowChange,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let (cols, vals) = insert_columns_values(&row.data, &table.column_types, dialect);
+    format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        qualified_table(schema, &table.table_name, dialect),
+        cols,
+        vals
+    )
+}
+
+/// Single-line `UPDATE … SET … WHERE <pk>;` touching only the changed columns.
+/// With `guarded`, the row must also still hold the `before` values (see
+/// `update_guard_clause`).
+pub fn update_statement(
+    schema: &str,
+    table: &TableDiff,
+    row: &RowUpdate,
+    guarded: bool,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let types = &table.column_types;
+    let predicate = if guarded {
+        update_guard_clause(row, types, dialect)
+    } else {
+        pk_where_clause(&row.pk, types, dialect)
+    };
+    format!(
+        "UPDATE {} SET {} WHERE {};",
+        qualified_table(schema, &table.table_name, dialect),
+        set_clause(&row.changed_columns, types, dialect),
+        predicate,
+    )
+}
+
+/// Single-line `DELETE FROM … WHERE <pk>;` for a deleted row. A row of a
+/// keyless table (empty `pk`) is matched on all its columns, and the
+/// statement deletes one row only, leaving its duplicates in place. With
+/// `guarded`, a keyed row is matched on all its columns too.
+pub fn delete_statement(
+    schema: &str,
+    table: &TableDiff,
+    row: &RowChange,
+    guarded: bool,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let types = &table.column_types;
+    let table = qualified_table(schema, &table.table_name, dialect);
+    if row.pk.is_empty() {
+        return format!(
+            "{};",
+            dialect.delete_one_sql(&table, &pk_where_clause(&row.data, types, dialect))
+        );
+    }
+    let predicate = if guarded {
+        pk_where_clause(&row.data, types, dialect)
+    } else {
+        pk_where_clause(&row.pk, types, dialect)
+    };
+    format!("DELETE FROM {} WHERE {};", table, predicate)
+}
+
+/// `col = literal AND …` predicate matching a row by primary key (or by all
+/// its columns). NULL values are rendered as `col IS NULL`.
+pub fn pk_where_clause(
+    pk: &BTreeMap<String, Value>,
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> String {
+    pk.iter()
+        .map(|(col, val)| {
+            let col_q = dialect.quote_ident(col);
+            if val == &Value::Null {
+                format!("{} IS NULL", col_q)
+            } else {
+                dialect.equals_sql(
+                    &col_q,
+                    &literal(col, val, types, dialect),
+                    types.get(col).map(String::as_str),
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Predicate of a guarded update: the primary key plus the `before` value of
+/// every changed column, so the update matches no row once target changed
+/// one of them since the diff. Columns the update leaves alone are not
+/// asserted: a concurrent change to them is kept, not overwritten.
+pub fn update_guard_clause(
+    row: &RowUpdate,
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> String {
+    let mut expected = row.pk.clone();
+    for c in &row.changed_columns {
+        expected.insert(c.column.clone(), c.before.clone());
+    }
+    pk_where_clause(&expected, types, dialect)
+}
+
+/// `table (id=1, …)`, naming a row in the error of a failed guard.
+pub fn row_label(table: &str, pk: &BTreeMap<String, Value>) -> String {
+    let key = pk
         .iter()
-        .map(|col| {
-            row.get(&col.0)
-                .map(|v| match v {
-                    Value::String(s) => s.clone(),
-                    other => other.to_string(),
-                })
-                .unwrap_or_else(|| "NULL".to_string())
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} ({})", table, key)
+}
+
+/// `col = literal, …` assignment list for the changed columns of an update.
+pub fn set_clause(
+    columns: &[ColumnDiff],
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> String {
+    columns
+        .iter()
+        .map(|c| {
+            format!(
+                "{} = {}",
+                dialect.quote_ident(&c.column),
+                literal(&c.column, &c.after, types, dialect)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `col = literal, …` assignment list setting every column of `values`.
+pub fn set_values_clause(
+    values: &BTreeMap<String, Value>,
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> String {
+    values
+        .iter()
+        .map(|(col, val)| {
+            format!(
+                "{} = {}",
+                dialect.quote_ident(col),
+                literal(col, val, types, dialect)
+            )
         })
         .collect::<Vec<_>>()
-        .join("|")
+        .join(", ")
+}
+
+/// Quoted column list and matching literal list for an `INSERT`.
+pub fn insert_columns_values(
+    data: &BTreeMap<String, Value>,
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> (String, String) {
+    let cols: Vec<String> = data.keys().map(|k| dialect.quote_ident(k)).collect();
+    let vals: Vec<String> = data
+        .iter()
+        .map(|(col, v)| literal(col, v, types, dialect))
+        .collect();
+    (cols.join(", "), vals.join(", "))
+}
+
+/// `WHERE` predicate matching any of `pks`, all over the same key columns:
+/// `"id" IN (1, 2)`, `("a", "b") IN ((1, 'x'), (2, 'y'))` for a composite
+/// key, `pk_where_clause` for a single row.
+pub fn pk_in_clause(
+    pks: &[&BTreeMap<String, Value>],
+    types: &ColumnTypes,
+    dialect: &dyn QueryDialect,
+) -> String {
+    match pks {
+        [pk] => pk_where_clause(pk, types, dialect),
+        _ => {
+            let tuple = |items: Vec<String>| match items.len() {
+                1 => items.concat(),
+                _ => format!("({})", items.join(", ")),
+            };
+            let cols = tuple(pks[0].keys().map(|c| dialect.quote_ident(c)).collect());
+            let keys: Vec<String> = pks
+                .iter()
+                .map(|pk| {
+                    tuple(
+                        pk.iter()
+                            .map(|(col, val)| literal(col, val, types, dialect))
+                            .collect(),
+                    )
+                })
+                .collect();
+            format!("{} IN ({})", cols, keys.join(", "))
+        }
+    }
+}
+
+/// Literal for `val` in column `col`, cast to the column type when known.
+fn literal(col: &str, val: &Value, types: &ColumnTypes, dialect: &dyn QueryDialect) -> String {
+    dialect.sql_literal(val, types.get(col).map(String::as_str))
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -103,6 +470,7 @@ mod tests {
     use super::*;
     use crate::domain::value_objects::{ColumnName, Schema, TableName};
     use crate::infrastructure::db::dialect::{MysqlDialect, PostgresDialect, SqliteDialect};
+    use serde_json::json;
 
     fn pg() -> PostgresDialect {
         PostgresDialect
@@ -114,6 +482,19 @@ mod tests {
         SqliteDialect
     }
 
+    fn table(name: &str) -> TableDiff {
+        TableDiff {
+            table_name: name.to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![],
+            updates: vec![],
+            deletes: vec![],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        }
+    }
+
     #[test]
     fn test_build_select_query_postgres() {
         let schema = Schema("sandbox".into());
@@ -225,4 +606,328 @@ mod tests {
         let q = build_typed_select_query(&schema, &table, &pks, &col_types, &pg());
         assert!(!q.contains("ORDER BY"));
     }
+
+    // ── Statement builders ──
+
+    #[test]
+    fn test_build_key_ordered_select_query_postgres() {
+        let schema = Schema("public".into());
+        let table = TableName("ledger".into());
+        let pks = vec![ColumnName("account".into()), ColumnName("seq".into())];
+        let col_types = vec![
+            ("account".to_string(), "text".to_string()),
+            ("seq".to_string(), "bigint".to_string()),
+            ("amount".to_string(), "numeric".to_string()),
+        ];
+        let q = build_key_ordered_select_query(&schema, &table, &pks, &col_types, &pg());
+        assert_eq!(
+            q,
+            r#"SELECT "account"::TEXT AS "account", "seq", "amount"::TEXT AS "amount" FROM "public"."ledger" ORDER BY "account"::TEXT COLLATE "C", "seq""#
+        );
+    }
+
+    #[test]
+    fn test_build_key_ordered_select_query_sqlite_selects_star() {
+        let schema = Schema("ignored".into());
+        let table = TableName("events".into());
+        let pks = vec![ColumnName("id".into())];
+        let q = build_key_ordered_select_query(&schema, &table, &pks, &[], &sq());
+        assert_eq!(q, r#"SELECT * FROM "events" ORDER BY "id" COLLATE BINARY"#);
+    }
+
+    #[test]
+    fn test_build_range_select_query_bounds_both_sides() {
+        let schema = Schema("mydb".into());
+        let table = TableName("events".into());
+        let pks = vec![ColumnName("id".into())];
+        let col_types = vec![("id".to_string(), "bigint".to_string())];
+        let range = KeyRange {
+            lower: Some(vec![json!(10)]),
+            upper: Some(vec![json!(20)]),
+        };
+        let q = build_range_select_query(&schema, &table, &pks, &col_types, &range, &my());
+        assert_eq!(
+            q,
+            "SELECT `id` FROM `mydb`.`events` WHERE (`id`) > (10) AND (`id`) <= (20) ORDER BY `id`"
+        );
+    }
+
+    #[test]
+    fn test_build_range_checksum_query_full_range_has_no_where() {
+        let schema = Schema("public".into());
+        let table = TableName("events".into());
+        let pks = vec![ColumnName("id".into())];
+        let q = build_range_checksum_query(
+            &schema,
+            &table,
+            &pks,
+            &[],
+            "CHECKSUM",
+            &KeyRange::full(),
+            &pg(),
+        );
+        assert_eq!(q, r#"SELECT COUNT(*), CHECKSUM FROM "public"."events""#);
+    }
+
+    #[test]
+    fn test_build_key_at_query_composite_text_key() {
+        let schema = Schema("public".into());
+        let table = TableName("tax_rules".into());
+        let pks = vec![ColumnName("region".into()), ColumnName("seq".into())];
+        let col_types = vec![
+            ("region".to_string(), "text".to_string()),
+            ("seq".to_string(), "integer".to_string()),
+            ("rate".to_string(), "numeric".to_string()),
+        ];
+        let range = KeyRange {
+            lower: Some(vec![json!("it's"), json!(3)]),
+            upper: None,
+        };
+        let q = build_key_at_query(&schema, &table, &pks, &col_types, &range, 41, &pg());
+        assert_eq!(
+            q,
+            r#"SELECT "region"::TEXT AS "region", "seq" FROM "public"."tax_rules" WHERE ("region"::TEXT COLLATE "C", "seq") > ('it''s', 3) ORDER BY "region"::TEXT COLLATE "C", "seq" LIMIT 1 OFFSET 41"#
+        );
+    }
+
+    #[test]
+    fn test_range_bounds_compare_temporal_keys_by_value() {
+        let schema = Schema("public".into());
+        let table = TableName("events".into());
+        let pks = vec![ColumnName("at".into()), ColumnName("seq".into())];
+        let col_types = vec![
+            ("at".to_string(), "timestamp with time zone".to_string()),
+            ("seq".to_string(), "integer".to_string()),
+        ];
+        let range = KeyRange {
+            lower: Some(vec![json!("2024-01-01T10:00:00.500+00:00"), json!(1)]),
+            upper: None,
+        };
+        let q = build_key_at_query(&schema, &table, &pks, &col_types, &range, 7, &pg());
+        assert_eq!(
+            q,
+            r#"SELECT "at"::TEXT AS "at", "seq" FROM "public"."events" WHERE ("at", "seq") > ('2024-01-01T10:00:00.500+00:00'::timestamp with time zone, 1) ORDER BY "at", "seq" LIMIT 1 OFFSET 7"#
+        );
+
+        let col_types = vec![
+            ("at".to_string(), "timestamp".to_string()),
+            ("seq".to_string(), "datetime".to_string()),
+        ];
+        let range = KeyRange {
+            lower: None,
+            upper: Some(vec![
+                json!("2024-01-01T10:00:00.500+00:00"),
+                json!("2024-01-01 10:00:00.25"),
+            ]),
+        };
+        let q = build_range_select_query(&schema, &table, &pks, &col_types, &range, &my());
+        assert!(
+            q.contains(
+                "WHERE (`at`, `seq`) <= (CONVERT_TZ('2024-01-01 10:00:00.500', '+00:00', @@session.time_zone), CAST('2024-01-01 10:00:00.25' AS DATETIME(2)))"
+            ),
+            "{q}"
+        );
+    }
+
+    #[test]
+    fn test_pk_where_clause_null_is_null() {
+        let mut pk = BTreeMap::new();
+        pk.insert("id".to_string(), Value::Null);
+        assert_eq!(
+            pk_where_clause(&pk, &ColumnTypes::new(), &pg()),
+            r#""id" IS NULL"#
+        );
+    }
+
+    #[test]
+    fn test_pk_where_clause_value() {
+        let mut pk = BTreeMap::new();
+        pk.insert("id".to_string(), json!(42));
+        assert_eq!(
+            pk_where_clause(&pk, &ColumnTypes::new(), &pg()),
+            r#""id" = 42"#
+        );
+    }
+
+    #[test]
+    fn test_pk_where_clause_mysql_backticks() {
+        let mut pk = BTreeMap::new();
+        pk.insert("id".to_string(), json!(1));
+        assert_eq!(pk_where_clause(&pk, &ColumnTypes::new(), &my()), "`id` = 1");
+    }
+
+    #[test]
+    fn test_pk_in_clause_single_and_composite_keys() {
+        let types = ColumnTypes::new();
+        let id = |v: i64| BTreeMap::from([("id".to_string(), json!(v))]);
+        let (one, two) = (id(1), id(2));
+        assert_eq!(pk_in_clause(&[&one], &types, &pg()), r#""id" = 1"#);
+        assert_eq!(pk_in_clause(&[&one, &two], &types, &my()), "`id` IN (1, 2)");
+        let key = |r: &str, c: &str| {
+            BTreeMap::from([
+                ("product_category".to_string(), json!(c)),
+                ("region_code".to_string(), json!(r)),
+            ])
+        };
+        let (a, b) = (key("FR", "food"), key("DE", "books"));
+        assert_eq!(
+            pk_in_clause(&[&a, &b], &types, &sq()),
+            r#"("product_category", "region_code") IN (('food', 'FR'), ('books', 'DE'))"#
+        );
+    }
+
+    #[test]
+    fn test_qualified_table_per_dialect() {
+        assert_eq!(qualified_table("s", "t", &pg()), r#""s"."t""#);
+        assert_eq!(qualified_table("s", "t", &my()), "`s`.`t`");
+        assert_eq!(qualified_table("ignored", "t", &sq()), r#""t""#);
+    }
+
+    #[test]
+    fn test_statements_are_single_line() {
+        let row = RowChange {
+            pk: [("id".to_string(), json!(1))].into(),
+            data: [
+                ("id".to_string(), json!(1)),
+                ("name".to_string(), json!("a")),
+            ]
+            .into(),
+        };
+        assert_eq!(
+            insert_statement("s", &table("t"), &row, &pg()),
+            r#"INSERT INTO "s"."t" ("id", "name") VALUES (1, 'a');"#
+        );
+        assert_eq!(
+            delete_statement("s", &table("t"), &row, false, &pg()),
+            r#"DELETE FROM "s"."t" WHERE "id" = 1;"#
+        );
+
+        let upd = RowUpdate {
+            pk: [("id".to_string(), json!(1))].into(),
+            before: [("name".to_string(), json!("a"))].into(),
+            after: [("name".to_string(), json!("b"))].into(),
+            changed_columns: vec![ColumnDiff {
+                column: "name".to_string(),
+                before: json!("a"),
+                after: json!("b"),
+                patch: None,
+            }],
+        };
+        assert_eq!(
+            update_statement("s", &table("t"), &upd, false, &sq()),
+            r#"UPDATE "t" SET "name" = 'b' WHERE "id" = 1;"#
+        );
+    }
+
+    #[test]
+    fn test_keyless_delete_removes_a_single_matching_row() {
+        let row = RowChange {
+            pk: BTreeMap::new(),
+            data: [
+                ("msg".to_string(), json!("login")),
+                ("user_id".to_string(), Value::Null),
+            ]
+            .into(),
+        };
+        assert_eq!(
+            delete_statement("s", &table("t"), &row, false, &pg()),
+            r#"DELETE FROM "s"."t" WHERE ctid = (SELECT ctid FROM "s"."t" WHERE "msg" = 'login' AND "user_id" IS NULL LIMIT 1);"#
+        );
+        assert_eq!(
+            delete_statement("s", &table("t"), &row, false, &my()),
+            "DELETE FROM `s`.`t` WHERE `msg` = 'login' AND `user_id` IS NULL LIMIT 1;"
+        );
+        assert_eq!(
+            delete_statement("s", &table("t"), &row, false, &sq()),
+            r#"DELETE FROM "t" WHERE rowid = (SELECT rowid FROM "t" WHERE "msg" = 'login' AND "user_id" IS NULL LIMIT 1);"#
+        );
+    }
+
+    #[test]
+    fn test_statements_cast_literals_to_column_types() {
+        let mut t = table("t");
+        t.column_types = [
+            ("id".to_string(), "uuid".to_string()),
+            ("status".to_string(), "order_status".to_string()),
+            ("tags".to_string(), "text[]".to_string()),
+            ("note".to_string(), "character varying(20)".to_string()),
+        ]
+        .into();
+        let id = "6f1c0d7e-9a43-4c4e-8a55-0d7f3b2f9e11";
+        let row = RowChange {
+            pk: [("id".to_string(), json!(id))].into(),
+            data: [
+                ("id".to_string(), json!(id)),
+                ("status".to_string(), json!("paid")),
+                ("tags".to_string(), json!(["a", "b"])),
+                ("note".to_string(), json!("x")),
+            ]
+            .into(),
+        };
+        assert_eq!(
+            insert_statement("s", &t, &row, &pg()),
+            format!(
+                r#"INSERT INTO "s"."t" ("id", "note", "status", "tags") VALUES ('{id}'::uuid, 'x', 'paid'::order_status, ARRAY['a', 'b']::text[]);"#
+            )
+        );
+        assert_eq!(
+            delete_statement("s", &t, &row, false, &pg()),
+            format!(r#"DELETE FROM "s"."t" WHERE "id" = '{id}'::uuid;"#)
+        );
+    }
+
+    #[test]
+    fn test_guarded_statements_assert_the_diffed_values() {
+        let upd = RowUpdate {
+            pk: [("id".to_string(), json!(1))].into(),
+            before: [("name".to_string(), Value::Null)].into(),
+            after: [("name".to_string(), json!("b"))].into(),
+            changed_columns: vec![ColumnDiff {
+                column: "name".to_string(),
+                before: Value::Null,
+                after: json!("b"),
+                patch: None,
+            }],
+        };
+        assert_eq!(
+            update_statement("s", &table("t"), &upd, true, &pg()),
+            r#"UPDATE "s"."t" SET "name" = 'b' WHERE "id" = 1 AND "name" IS NULL;"#
+        );
+        let del = RowChange {
+            pk: [("id".to_string(), json!(2))].into(),
+            data: [
+                ("id".to_string(), json!(2)),
+                ("name".to_string(), json!("c")),
+            ]
+            .into(),
+        };
+        assert_eq!(
+            delete_statement("s", &table("t"), &del, true, &my()),
+            "DELETE FROM `s`.`t` WHERE `id` = 2 AND `name` = 'c';"
+        );
+    }
+
+    #[test]
+    fn test_guards_compare_json_and_floats_by_value() {
+        let types: ColumnTypes = [
+            ("doc".to_string(), "json".to_string()),
+            ("ratio".to_string(), "real".to_string()),
+        ]
+        .into();
+        let row = [
+            ("doc".to_string(), json!({"a": 1})),
+            ("ratio".to_string(), json!(0.1)),
+        ]
+        .into();
+        assert_eq!(
+            pk_where_clause(&row, &types, &pg()),
+            r#""doc"::jsonb = '{"a":1}'::json::jsonb AND "ratio" = 0.1::real"#
+        );
+        let types = [("ratio".to_string(), "float".to_string())].into();
+        let row = [("ratio".to_string(), json!(0.1))].into();
+        assert_eq!(
+            pk_where_clause(&row, &types, &my()),
+            "`ratio` = CAST(0.1 AS FLOAT)"
+        );
+    }
 }
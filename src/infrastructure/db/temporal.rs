@@ -0,0 +1,227 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde_json::Value;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Canonical temporal values
+// ─────────────────────────────────────────────────────────────────────────────
+//
+// Every driver renders dates and times its own way (`2024-01-01 12:00:00+02`
+// for a PostgreSQL `timestamptz` read in a UTC+2 session, `2024-01-01
+// 10:00:00.500000` for a MySQL `DATETIME(6)`, …). Decoded values are
+// rewritten into one canonical text per kind, so equal values compare equal
+// as plain strings:
+//
+// - instant (`timestamptz`, MySQL `TIMESTAMP`) → `2024-01-01T10:00:00+00:00`, in UTC
+// - naive date and time                        → `2024-01-01 10:00:00`
+// - date                                       → `2024-01-01`
+// - time                                       → `10:00:00`
+// - time with time zone                        → `08:00:00+00:00`, in UTC
+//
+// Fractional seconds are written with 3, 6 or 9 digits, and only when
+// non-zero (`10:00:00.500`). The text of each kind sorts chronologically
+// (`+` sorts before `.`, where a `Z` would not), like the native ORDER BY of
+// a streamed or bisected temporal key. Values that do not parse (`infinity`, MySQL
+// zero dates, …) are kept as read. SQLite has no temporal types: its text is
+// left alone (see `normalize::portable_text` for cross-driver diffs).
+
+/// What a temporal column holds, by its type hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TemporalKind {
+    Instant,
+    DateTime,
+    Date,
+    Time,
+    TimeTz,
+}
+
+impl TemporalKind {
+    /// The kind of a column whose `information_schema` type is `hint`;
+    /// `None` for non-temporal types.
+    pub(crate) fn from_hint(hint: &str) -> Option<Self> {
+        match hint.to_lowercase().as_str() {
+            // MySQL TIMESTAMP is stored in UTC and read in the session time
+            // zone, pinned to UTC on every connection.
+            "timestamp with time zone" | "timestamptz" | "timestamp" => Some(Self::Instant),
+            "timestamp without time zone" | "datetime" => Some(Self::DateTime),
+            "date" => Some(Self::Date),
+            "time without time zone" | "time" => Some(Self::Time),
+            "time with time zone" | "timetz" => Some(Self::TimeTz),
+            _ => None,
+        }
+    }
+}
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+const INSTANT_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f+00:00";
+const TIME_FORMAT: &str = "%H:%M:%S%.f";
+
+/// `true` for a temporal type whose native order is the byte order of its
+/// canonical text: keys of the type are sorted and bounded by value. Not a
+/// time with time zone, which PostgreSQL orders by UTC time without wrapping
+/// around midnight.
+pub(crate) fn sorts_by_value(hint: &str) -> bool {
+    TemporalKind::from_hint(hint).is_some_and(|kind| kind != TemporalKind::TimeTz)
+}
+
+/// `value`, read from a column of type `hint`, in canonical form: unchanged
+/// when the type is not temporal or the value does not parse.
+pub(crate) fn canonical_value(value: Value, hint: &str) -> Value {
+    match (value, TemporalKind::from_hint(hint)) {
+        (Value::String(s), Some(kind)) => Value::String(canonical(&s, kind).unwrap_or(s)),
+        (other, _) => other,
+    }
+}
+
+/// Canonical text of `s` as a value of `kind`, `None` if it does not parse.
+pub(crate) fn canonical(s: &str, kind: TemporalKind) -> Option<String> {
+    let s = s.trim();
+    Some(match kind {
+        TemporalKind::Instant => parse_instant(s)?.format(INSTANT_FORMAT).to_string(),
+        TemporalKind::DateTime => parse_naive(s)?.format(DATE_TIME_FORMAT).to_string(),
+        TemporalKind::Date => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()?
+            .format("%Y-%m-%d")
+            .to_string(),
+        TemporalKind::Time => parse_time(s)?.format(TIME_FORMAT).to_string(),
+        TemporalKind::TimeTz => {
+            let split = s.rfind(['+', '-', 'Z', 'z'])?;
+            let time = parse_time(&s[..split])?;
+            let offset = match &s[split..] {
+                "Z" | "z" => 0,
+                zone => parse_offset(zone)?.local_minus_utc(),
+            };
+            let utc = time - chrono::TimeDelta::seconds(i64::from(offset));
+            format!("{}+00:00", utc.format(TIME_FORMAT))
+        }
+    })
+}
+
+/// A canonical instant (`…+00:00`) as naive UTC, for dialects writing
+/// instants through a time-zone conversion.
+pub(crate) fn parse_canonical_instant(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s.strip_suffix("+00:00")?, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+/// Format naive UTC the way every dialect accepts a date-and-time literal.
+pub(crate) fn format_naive(dt: &NaiveDateTime) -> String {
+    dt.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// A date and time in any driver's rendering, with or without offset, as
+/// canonical naive UTC (`2024-01-01 10:00:00`): the single form of every
+/// timestamp in cross-driver diffs (see `normalize`).
+pub(crate) fn canonical_utc(s: &str) -> Option<String> {
+    parse_instant(s.trim()).map(|utc| format_naive(&utc))
+}
+
+/// A date and time with an offset (`+02`, `+05:30`, `Z`) converted to UTC;
+/// a value without offset is taken as UTC already.
+fn parse_instant(s: &str) -> Option<NaiveDateTime> {
+    if let Some(naive) = s.strip_suffix('Z').or_else(|| s.strip_suffix('z')) {
+        return parse_naive(naive);
+    }
+    ["%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%dT%H:%M:%S%.f%#z"]
+        .iter()
+        .find_map(|f| DateTime::parse_from_str(s, f).ok())
+        .map(|dt| dt.with_timezone(&Utc).naive_utc())
+        .or_else(|| parse_naive(s))
+}
+
+/// A date and time without offset; SQLite text may stop at the minute.
+fn parse_naive(s: &str) -> Option<NaiveDateTime> {
+    [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok()
+}
+
+/// `+02`, `-03:30`, `+05:30:15` as an offset.
+fn parse_offset(zone: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match zone.as_bytes().first()? {
+        b'+' => (1, &zone[1..]),
+        b'-' => (-1, &zone[1..]),
+        _ => return None,
+    };
+    let mut seconds = 0;
+    for (part, unit) in rest.split(':').zip([3600, 60, 1]) {
+        seconds += part.parse::<i32>().ok()? * unit;
+    }
+    FixedOffset::east_opt(sign * seconds)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(s: &str, hint: &str) -> String {
+        canonical_value(Value::String(s.to_string()), hint)
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn same_instant_in_any_offset_is_one_value() {
+        let tz = "timestamp with time zone";
+        assert_eq!(c("2024-01-01 10:00:00+00", tz), "2024-01-01T10:00:00+00:00");
+        assert_eq!(c("2024-01-01 12:00:00+02", tz), "2024-01-01T10:00:00+00:00");
+        assert_eq!(
+            c("2024-01-01 04:30:00.5-05:30", tz),
+            "2024-01-01T10:00:00.500+00:00"
+        );
+        // MySQL TIMESTAMP, read in a UTC session.
+        assert_eq!(
+            c("2024-01-01 10:00:00", "timestamp"),
+            "2024-01-01T10:00:00+00:00"
+        );
+        assert_eq!(c("infinity", tz), "infinity");
+    }
+
+    #[test]
+    fn naive_values_share_one_format() {
+        let pg = c("2024-01-01 10:00:00.5", "timestamp without time zone");
+        let mysql = c("2024-01-01 10:00:00.500000", "datetime");
+        assert_eq!(pg, "2024-01-01 10:00:00.500");
+        assert_eq!(pg, mysql);
+        assert_eq!(c("2024-01-01T10:00:00", "datetime"), "2024-01-01 10:00:00");
+        assert_eq!(c("2024-01-01", "date"), "2024-01-01");
+        assert_eq!(c("0000-00-00", "date"), "0000-00-00");
+        assert_eq!(c("10:00:00.000000", "time"), "10:00:00");
+        assert_eq!(c("10:00:00+02", "time with time zone"), "08:00:00+00:00");
+        assert_eq!(c("01:00:00+05:30", "time with time zone"), "19:30:00+00:00");
+        assert_eq!(c("10:00", "text"), "10:00");
+    }
+
+    #[test]
+    fn canonical_text_sorts_chronologically() {
+        let tz = "timestamp with time zone";
+        let instants = [
+            c("2024-01-01 10:00:00+00", tz),
+            c("2024-01-01 10:00:00.5+00", tz),
+            c("2024-01-01 10:00:00.500001+00", tz),
+            c("2024-01-01 10:00:00.75+00", tz),
+            c("2024-01-01 10:00:01+00", tz),
+        ];
+        assert!(instants.windows(2).all(|w| w[0] < w[1]), "{instants:?}");
+        let naive = [
+            c("2024-01-01 10:00:00", "timestamp without time zone"),
+            c("2024-01-01 10:00:00.5", "datetime"),
+            c("2024-01-01 10:00:01", "datetime"),
+        ];
+        assert!(naive.windows(2).all(|w| w[0] < w[1]), "{naive:?}");
+        // Canonical text is canonical again.
+        assert_eq!(c(&instants[1], tz), instants[1]);
+    }
+}
@@ -59,22 +59,41 @@ pub fn init_tracing(level: LogLevel) {
 // ─── Public API Facade ───
 
 pub use application::monitoring::PerfReport;
+pub use domain::apply_report::{ApplyFailure, ApplyReport, Operation};
 pub use domain::changeset::{Changeset, Summary};
-pub use domain::conflict::ConflictReport;
+pub use domain::compare_rules::{CompareRule, CompareRules, TableRules, TimestampPrecision};
+pub use domain::conflict::{ConflictKind, ConflictReport};
 pub use domain::diff_result::DiffResult;
+pub use domain::discovery::SkippedTable;
 pub use domain::fingerprint::fingerprint;
+pub use domain::json_patch::{JsonPath, PatchOperation};
 pub use domain::ports::SnapshotProvider;
+pub use domain::resolution::{
+    AutoResolution, Choice, Resolution, ResolutionFile, ResolutionPolicy, ResolutionStrategy,
+};
 pub use domain::snapshot::MapSnapshotProvider;
-pub use domain::table_diff::{ColumnDiff, RowChange, RowMap, RowUpdate, TableDiff};
+pub use domain::table_diff::{
+    ColumnDiff, ColumnTypes, DataQualityFinding, DataQualityKind, RowChange, RowMap, RowUpdate,
+    Side, TableDiff,
+};
 pub use domain::value_objects::{ColumnName, ExcludedColumns, Fingerprint, Schema, TableName};
-pub use infrastructure::config::{AppConfig, DbConfig, DiffConfig, OutputConfig, TableConfig};
+pub use infrastructure::config::{
+    render_config, AppConfig, DbConfig, DiffConfig, DiffStrategy, DiscoverConfig, OutputConfig,
+    SqlOutputConfig, SqlStyle, TableConfig,
+};
 
+use crate::application::bisect::BisectDiffService;
 use crate::application::conflict::ConflictService;
-use crate::application::diff::{DiffService, TableDiffer};
-use crate::application::monitoring::{MonitoringDiffer, MonitoringRowRepository};
+use crate::application::diff::{DiffService, MergeJoinDiffer, TableDiffer};
+use crate::application::discovery::{DiscoverySide, TableDiscovery};
+use crate::application::init::InitService;
+use crate::application::monitoring::{
+    MonitoringDiffer, MonitoringRowRepository, MonitoringStreamDiffer,
+};
+use crate::application::resolution::ResolutionService;
 use crate::application::snapshot::SnapshotService;
-use crate::domain::ports::RowRepository;
-use crate::infrastructure::db::client::connect;
+use crate::domain::ports::{ChangesetApplier, RangeRepository};
+use crate::infrastructure::db::client::{connect, SqlxRowRepository};
 
 // ─── Public entry points ───
 
@@ -95,26 +114,57 @@ pub async fn run(cfg: &AppConfig) -> Result<Changeset> {
 pub async fn run_with_timing(cfg: &AppConfig) -> Result<(Changeset, PerfReport)> {
     let report = PerfReport::new();
 
-    let source_repo = build_repo(&cfg.source, Arc::clone(&report)).await?;
-    let target_repo = build_repo(&cfg.target, Arc::clone(&report)).await?;
+    let normalize = cfg.is_cross_driver();
+    let source =
+        Arc::new(connect_reader(&cfg.source, cfg.source.consistent_read, normalize).await?);
+    let target =
+        Arc::new(connect_reader(&cfg.target, cfg.target.consistent_read, normalize).await?);
+
+    let source_schema = Schema(cfg.source.schema.clone());
+    let target_schema = Schema(cfg.target.schema.clone());
+    let (tables, skipped) = resolve_tables(
+        cfg,
+        &[
+            ("source", source.as_ref(), &source_schema),
+            ("target", target.as_ref(), &target_schema),
+        ],
+    )
+    .await?;
+
+    let rules = cfg.diff.compare_rules();
+    let bisect = BisectDiffService::new(
+        Arc::clone(&source) as Arc<dyn RangeRepository>,
+        Arc::clone(&target) as Arc<dyn RangeRepository>,
+        Arc::new(TableDiffer::new().with_rules(rules.clone())),
+    )
+    .with_leaf_rows(cfg.diff.bisect_leaf_rows);
+
+    let source_repo = Arc::new(MonitoringRowRepository::new(source, Arc::clone(&report)));
+    let target_repo = Arc::new(MonitoringRowRepository::new(target, Arc::clone(&report)));
     let differ = Arc::new(MonitoringDiffer::new(
-        Arc::new(TableDiffer::new()),
+        Arc::new(TableDiffer::new().with_rules(rules.clone())),
         Arc::clone(&report),
     ));
 
-    let service = DiffService::new(source_repo, target_repo, differ);
+    let stream_differ = Arc::new(MonitoringStreamDiffer::new(
+        Arc::new(MergeJoinDiffer::new().with_rules(rules)),
+        Arc::clone(&report),
+    ));
 
-    let source_schema = Schema(cfg.source.schema.clone());
-    let target_schema = Schema(cfg.target.schema.clone());
+    let service = DiffService::new(source_repo, target_repo, differ)
+        .with_stream_differ(stream_differ)
+        .with_bisect(Arc::new(bisect));
 
     let changeset = service
         .run_diff(
             &source_schema,
             &target_schema,
             &cfg.source.driver,
-            &cfg.diff.tables,
+            &cfg.target.driver,
+            &tables,
         )
-        .await?;
+        .await?
+        .with_skipped_tables(skipped);
 
     let perf = report.lock().unwrap().clone();
     // Embed the perf report inside the changeset so JSON/HTML writers include it automatically.
@@ -133,14 +183,25 @@ pub async fn snapshot(cfg: &AppConfig) -> Result<BTreeMap<String, Vec<RowMap>>>
 }
 
 /// Capture a snapshot and return a [`PerfReport`] alongside the rows.
+///
+/// All tables are always read inside one consistent read of the target
+/// (regardless of `consistent_read`): the 3-way merge is only sound if the
+/// base snapshot is internally consistent.
 pub async fn snapshot_with_timing(
     cfg: &AppConfig,
 ) -> Result<(BTreeMap<String, Vec<RowMap>>, PerfReport)> {
     let report = PerfReport::new();
-    let target_repo = build_repo(&cfg.target, Arc::clone(&report)).await?;
-    let svc = SnapshotService::new(target_repo);
+    // Decode like `run_with_timing` does, so the base snapshot compares
+    // equal with the target rows of a cross-driver diff.
+    let target = connect_reader(&cfg.target, true, cfg.is_cross_driver()).await?;
     let target_schema = Schema(cfg.target.schema.clone());
-    let raw = svc.capture(&target_schema, &cfg.diff.tables).await?;
+    let (tables, _) = resolve_tables(cfg, &[("target", &target, &target_schema)]).await?;
+    let target_repo = Arc::new(MonitoringRowRepository::new(
+        Arc::new(target),
+        Arc::clone(&report),
+    ));
+    let svc = SnapshotService::new(target_repo);
+    let raw = svc.capture(&target_schema, &tables).await?;
     let perf = report.lock().unwrap().clone();
     Ok((raw, perf))
 }
@@ -172,8 +233,8 @@ pub async fn run_with_conflicts(
 ) -> Result<DiffResult> {
     let changeset = run(cfg).await?;
 
-    let pk_cols_by_table: BTreeMap<String, Vec<ColumnName>> = cfg
-        .diff
+    // Keys come from the changeset: with discovery they are not in the config.
+    let pk_cols_by_table: BTreeMap<String, Vec<ColumnName>> = changeset
         .tables
         .iter()
         .map(|t| {
@@ -182,30 +243,103 @@ pub async fn run_with_conflicts(
                 .iter()
                 .map(|pk| ColumnName(pk.clone()))
                 .collect();
-            (t.name.clone(), cols)
+            (t.table_name.clone(), cols)
         })
         .collect();
 
-    let conflict_svc = ConflictService::new();
-    Ok(conflict_svc.check(
+    let conflict_svc = ConflictService::new()
+        .with_rules(cfg.diff.compare_rules())
+        .with_policies(cfg.diff.resolution_policies());
+    conflict_svc.check(
         changeset,
         base,
         stored_fps,
         current_target_rows,
         &pk_cols_by_table,
-    ))
+    )
+}
+
+/// Fold the decisions of a resolution file into its changeset.
+///
+/// Fails while any conflict is undecided. Rewritten updates are compared
+/// with the configured comparison rules, like the diff itself. No database
+/// is read: the result can be written or applied like any changeset.
+pub fn resolve(cfg: &AppConfig, file: ResolutionFile) -> Result<Changeset> {
+    ResolutionService::new()
+        .with_rules(cfg.diff.compare_rules())
+        .resolve(file)
+}
+
+/// Apply a changeset to the **target** DB in a single transaction.
+///
+/// Every delete/update/insert must affect exactly one row; otherwise the
+/// whole transaction is rolled back and the offending rows are listed in
+/// [`ApplyReport::failures`]. Check [`ApplyReport::committed`] (or
+/// [`ApplyReport::is_success`]) to know whether the target was modified.
+pub async fn apply(cfg: &AppConfig, changeset: &Changeset) -> Result<ApplyReport> {
+    apply_inner(cfg, changeset, false).await
+}
+
+/// Same as [`apply`], but always rolls back — even when every statement
+/// succeeded. Use it to validate a changeset against a real target (e.g.
+/// staging) without committing anything.
+pub async fn apply_dry_run(cfg: &AppConfig, changeset: &Changeset) -> Result<ApplyReport> {
+    apply_inner(cfg, changeset, true).await
+}
+
+/// Generate the content of a `diffly.toml` for `source` → `target`.
+///
+/// The tables, their primary keys and suggested `excluded_columns` are read
+/// from the **source** schema; tables without a primary key are listed as
+/// commented-out blocks. Nothing is written: the caller saves the returned
+/// TOML where it sees fit.
+pub async fn init_config(source: &DbConfig, target: &DbConfig) -> Result<String> {
+    let repo = Arc::new(connect(source).await?);
+    let (tables, keyless) = InitService::new(repo)
+        .draft_tables(&Schema(source.schema.clone()))
+        .await?;
+    Ok(render_config(source, target, &tables, &keyless))
 }
 
 // ─── Private helpers ───────────────────────────────────────────────────────────
 
-/// Connect to a DB and wrap the repository in the monitoring decorator.
+async fn apply_inner(cfg: &AppConfig, changeset: &Changeset, dry_run: bool) -> Result<ApplyReport> {
+    let target = connect(&cfg.target).await?;
+    let target_schema = Schema(cfg.target.schema.clone());
+    target.apply(&target_schema, changeset, dry_run).await
+}
+
+/// Connect to a DB for reading.
 ///
-/// The shared `report` accumulates timings from all repos created for the
-/// same run, giving a unified view across source and target.
-async fn build_repo(
+/// `consistent_read` reads every table at the same point in time;
+/// `normalize` decodes values driver-neutrally (cross-driver diffs).
+async fn connect_reader(
     cfg: &DbConfig,
-    report: Arc<std::sync::Mutex<PerfReport>>,
-) -> Result<Arc<dyn RowRepository>> {
-    let repo = Arc::new(connect(cfg).await?);
-    Ok(Arc::new(MonitoringRowRepository::new(repo, report)))
+    consistent_read: bool,
+    normalize: bool,
+) -> Result<SqlxRowRepository> {
+    let mut repo = connect(cfg).await?;
+    if normalize {
+        repo = repo.with_normalized_values();
+    }
+    if consistent_read {
+        repo = repo.begin_consistent_read().await?;
+    }
+    Ok(repo)
+}
+
+/// The tables to diff: `cfg.diff.tables`, or — with `[diff.discover]` — the
+/// tables discovered on every side, with explicit entries taking precedence.
+async fn resolve_tables(
+    cfg: &AppConfig,
+    sides: &[DiscoverySide<'_>],
+) -> Result<(Vec<TableConfig>, Vec<SkippedTable>)> {
+    match &cfg.diff.discover {
+        Some(discover) => {
+            TableDiscovery::new(discover)
+                .resolve(sides, &cfg.diff.tables)
+                .await
+        }
+        None => Ok((cfg.diff.tables.clone(), Vec::new())),
+    }
 }
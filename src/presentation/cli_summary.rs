@@ -1,6 +1,8 @@
 use crate::application::monitoring::PerfReport;
+use crate::domain::apply_report::ApplyReport;
 use crate::domain::changeset::Changeset;
 use crate::domain::conflict::ConflictReport;
+use crate::domain::resolution::AutoResolution;
 use colored::*;
 use tabled::settings::{object::Columns, Alignment, Modify, Style};
 use tabled::{Table, Tabled};
@@ -31,6 +33,9 @@ pub fn print_summary(changeset: &Changeset) {
     println!("Changeset: {}", changeset.changeset_id.bright_yellow());
     println!();
 
+    print_skipped_tables(changeset);
+    print_data_quality(changeset);
+
     if changeset.summary.total_changes == 0 {
         println!("{}", "No changes detected.".italic());
         return;
@@ -84,10 +89,78 @@ pub fn print_summary(changeset: &Changeset) {
     println!();
 }
 
+/// Warn about tables that discovery found but could not diff.
+fn print_skipped_tables(changeset: &Changeset) {
+    if changeset.skipped_tables.is_empty() {
+        return;
+    }
+    println!(
+        "{} {} table(s) not diffed:",
+        "⚠".yellow(),
+        changeset.skipped_tables.len().to_string().bold()
+    );
+    for skipped in &changeset.skipped_tables {
+        println!(
+            "  {} — {}",
+            skipped.table_name.bold(),
+            skipped.reason.yellow()
+        );
+    }
+    println!();
+}
+
+#[derive(Tabled)]
+struct DataQualityRow {
+    table: String,
+    side: String,
+    finding: String,
+    key: String,
+    rows: String,
+}
+
+/// Warn about rows the primary key cannot identify (duplicate or NULL keys).
+/// They are left out of the diff.
+pub fn print_data_quality(changeset: &Changeset) {
+    if changeset.summary.data_quality_findings == 0 {
+        return;
+    }
+    println!(
+        "{} {} data-quality finding(s), rows left out of the diff:",
+        "⚠".yellow(),
+        changeset.summary.data_quality_findings.to_string().bold()
+    );
+
+    let rows: Vec<DataQualityRow> = changeset
+        .tables
+        .iter()
+        .flat_map(|t| t.data_quality.iter().map(move |f| (t, f)))
+        .map(|(t, f)| DataQualityRow {
+            table: t.table_name.bold().to_string(),
+            side: f.side.to_string(),
+            finding: f.kind.to_string().yellow().to_string(),
+            key: f
+                .pk
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(", "),
+            rows: f.rows.len().to_string(),
+        })
+        .collect();
+
+    let table = Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(4..=4)).with(Alignment::right()))
+        .to_string();
+    println!("{table}");
+    println!();
+}
+
 // ─── Conflict summary ─────────────────────────────────────────────────────────
 
 #[derive(Tabled)]
 struct ConflictRow {
+    kind: String,
     table: String,
     pk: String,
     column: String,
@@ -98,19 +171,23 @@ struct ConflictRow {
 
 /// Print a coloured table of all detected conflicts to stdout.
 ///
-/// Returns `true` if there are conflicts (so the caller can exit non-zero).
+/// Returns `true` if any conflict needs resolving (so the caller can exit
+/// non-zero); benign delete/delete entries are listed but do not count.
 pub fn print_conflicts(conflicts: &[ConflictReport]) -> bool {
-    if conflicts.is_empty() {
+    let blocking = conflicts.iter().filter(|c| c.is_blocking()).count();
+    if blocking == 0 {
         println!("{}", "✓ No conflicts — changeset is clean.".bold().green());
-        return false;
+        if conflicts.is_empty() {
+            return false;
+        }
+    } else {
+        println!();
+        println!("{}", "CONFLICTS DETECTED".bold().red());
+        println!(
+            "{} conflict(s) must be resolved before deploying.",
+            blocking.to_string().bold()
+        );
     }
-
-    println!();
-    println!("{}", "CONFLICTS DETECTED".bold().red());
-    println!(
-        "{} conflict(s) must be resolved before deploying.",
-        conflicts.len().to_string().bold()
-    );
     println!();
 
     let rows: Vec<ConflictRow> = conflicts
@@ -121,10 +198,17 @@ pub fn print_conflicts(conflicts: &[ConflictReport]) -> bool {
                     .map(|(k, v)| format!("{}={}", k, v))
                     .collect::<Vec<_>>()
                     .join(", ");
+            let kind = c.kind.to_string();
             ConflictRow {
+                kind: if c.is_blocking() {
+                    kind.red().to_string()
+                } else {
+                    kind.dimmed().to_string()
+                },
                 table: c.table_name.bold().to_string(),
                 pk: pk_str,
-                column: c.column.yellow().to_string(),
+                // Row-level conflicts carry whole rows.
+                column: c.column.as_deref().unwrap_or("(row)").yellow().to_string(),
                 base: c.base_value.to_string().dimmed().to_string(),
                 yours: c.source_value.to_string().cyan().to_string(),
                 theirs: c.target_value.to_string().red().to_string(),
@@ -134,7 +218,7 @@ pub fn print_conflicts(conflicts: &[ConflictReport]) -> bool {
 
     let table = Table::new(rows)
         .with(Style::rounded())
-        .with(Modify::new(Columns::new(0..=0)).with(Alignment::left()))
+        .with(Modify::new(Columns::new(0..=1)).with(Alignment::left()))
         .to_string();
 
     println!("{table}");
@@ -142,9 +226,125 @@ pub fn print_conflicts(conflicts: &[ConflictReport]) -> bool {
     println!("  {}  base value at clone time", "base  →".dimmed());
     println!("  {}  your source change", "yours →".cyan());
     println!("  {}  concurrent target change", "theirs→".red());
+    println!(
+        "  {}  the row does not exist on that side",
+        "null  →".dimmed()
+    );
+    println!();
+
+    blocking > 0
+}
+
+#[derive(Tabled)]
+struct AutoResolutionRow {
+    kind: String,
+    table: String,
+    pk: String,
+    column: String,
+    strategy: String,
+    kept: String,
+}
+
+/// Print the conflicts settled by `on_conflict` strategies, if any.
+pub fn print_auto_resolved(resolved: &[AutoResolution]) {
+    if resolved.is_empty() {
+        return;
+    }
+
     println!();
+    println!(
+        "{} conflict(s) settled by on_conflict strategies:",
+        resolved.len().to_string().bold()
+    );
+
+    let rows: Vec<AutoResolutionRow> = resolved
+        .iter()
+        .map(|r| {
+            let c = &r.conflict;
+            AutoResolutionRow {
+                kind: c.kind.to_string(),
+                table: c.table_name.bold().to_string(),
+                pk: c
+                    .pk
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                column: c.column.as_deref().unwrap_or("(row)").to_string(),
+                strategy: r.strategy.to_string(),
+                kept: r.choice.to_string().green().to_string(),
+            }
+        })
+        .collect();
+
+    println!("{}", Table::new(rows).with(Style::rounded()));
+}
 
-    true
+// ─── Apply report ─────────────────────────────────────────────────────────────
+
+#[derive(Tabled)]
+struct ApplyFailureRow {
+    table: String,
+    operation: String,
+    pk: String,
+    reason: String,
+}
+
+/// Print the outcome of `diffly apply`, including one row per failed statement.
+pub fn print_apply_report(report: &ApplyReport) {
+    println!();
+    println!("{}", "DIFFLY APPLY".bold().cyan());
+    println!(
+        "{} statement(s) executed · {} row(s) affected",
+        report.statements_executed.to_string().bold(),
+        report.rows_affected.to_string().bold()
+    );
+
+    if report.is_success() {
+        if report.committed {
+            println!("{}", "✓ Changeset committed.".bold().green());
+        } else {
+            println!(
+                "{}",
+                "✓ Dry run succeeded — transaction rolled back."
+                    .bold()
+                    .green()
+            );
+        }
+        println!();
+        return;
+    }
+
+    println!(
+        "{} {} statement(s) deviated — transaction rolled back.",
+        "✗".bold().red(),
+        report.failures.len().to_string().bold()
+    );
+    println!();
+
+    let rows: Vec<ApplyFailureRow> = report
+        .failures
+        .iter()
+        .map(|f| ApplyFailureRow {
+            table: f.table_name.bold().to_string(),
+            operation: f.operation.to_string().yellow().to_string(),
+            pk: f
+                .pk
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(", "),
+            reason: f.reason.red().to_string(),
+        })
+        .collect();
+
+    let table = Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(0..=0)).with(Alignment::left()))
+        .to_string();
+
+    println!("{table}");
+    println!();
 }
 
 // ─── Performance summary ──────────────────────────────────────────────────────
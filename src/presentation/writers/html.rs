@@ -1,7 +1,9 @@
 use anyhow::Result;
 use sailfish::TemplateOnce;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 
-use crate::domain::{changeset::Changeset, ports::OutputWriter};
+use crate::domain::{binary, changeset::Changeset, ports::OutputWriter};
 
 #[derive(TemplateOnce)]
 #[template(path = "html/changeset.stpl")] // base dir declared inside sailfish.toml
@@ -9,6 +11,19 @@ struct ChangesetTemplate<'a> {
     changeset: &'a Changeset,
 }
 
+/// A value as shown in the report. Binary values show their size and the
+/// start of their SHA-256 instead of the bytes, which are unreadable and
+/// can be large; anything else is its JSON text.
+fn display_value(value: &Value) -> String {
+    match binary::as_bytes(value) {
+        Some(bytes) => {
+            let hash = binary::encode_hex(&Sha256::digest(&bytes));
+            format!("[{} bytes, sha256 {}…]", bytes.len(), &hash[..12])
+        }
+        None => value.to_string(),
+    }
+}
+
 pub struct HtmlWriter;
 
 impl OutputWriter for HtmlWriter {
@@ -20,3 +35,41 @@ impl OutputWriter for HtmlWriter {
         "html"
     }
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::table_diff::{ColumnTypes, RowChange, TableDiff};
+    use serde_json::json;
+
+    #[test]
+    fn binary_values_are_summarised() {
+        let row = RowChange {
+            pk: [("id".to_string(), json!(1))].into(),
+            data: [
+                ("id".to_string(), json!(1)),
+                ("avatar".to_string(), binary::bytes_value(b"\x89PNG")),
+            ]
+            .into(),
+        };
+        let table = TableDiff {
+            table_name: "users".to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![row],
+            updates: vec![],
+            deletes: vec![],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        let cs = Changeset::new("public", "public", "postgres", "postgres", vec![table]);
+
+        let html = HtmlWriter.format(&cs).unwrap();
+        assert!(html.contains("[4 bytes, sha256 "), "{html}");
+        assert!(!html.contains("89504e47"));
+    }
+}
@@ -2,16 +2,17 @@ use anyhow::Result;
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::BTreeMap;
-use std::fmt::Write as FmtWrite;
 
 use crate::application::monitoring::PerfReport;
 use crate::domain::{
     changeset::{Changeset, Summary},
+    discovery::SkippedTable,
+    foreign_key::ForeignKey,
     ports::OutputWriter,
-    table_diff::{ColumnDiff, RowChange, RowUpdate, TableDiff},
+    table_diff::{ColumnDiff, ColumnTypes, DataQualityFinding, TableDiff},
 };
 use crate::infrastructure::db::dialect::{from_driver, QueryDialect};
-use crate::presentation::writers::sql::{insert_columns_values, pk_where_clause, set_clause};
+use crate::infrastructure::db::sql_utils::{delete_statement, insert_statement, update_statement};
 
 // ─── Serialisation view types ─────────────────────────────────────────────────
 //
@@ -23,12 +24,17 @@ struct JsonChangeset<'a> {
     changeset_id: &'a str,
     source_schema: &'a str,
     target_schema: &'a str,
-    driver: &'a str,
+    source_driver: &'a str,
+    target_driver: &'a str,
     created_at: &'a str,
     source_fingerprint: &'a str,
     target_fingerprint: &'a str,
     tables: Vec<JsonTableDiff<'a>>,
     summary: &'a Summary,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    skipped_tables: &'a [SkippedTable],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    foreign_keys: &'a [ForeignKey],
     #[serde(skip_serializing_if = "Option::is_none")]
     perf: Option<&'a PerfReport>,
 }
@@ -40,6 +46,12 @@ struct JsonTableDiff<'a> {
     inserts: Vec<JsonInsert<'a>>,
     updates: Vec<JsonUpdate<'a>>,
     deletes: Vec<JsonDelete<'a>>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    data_quality: &'a [DataQualityFinding],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    column_types: &'a ColumnTypes,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    excluded_columns: &'a [String],
 }
 
 #[derive(Serialize)]
@@ -65,52 +77,12 @@ struct JsonDelete<'a> {
     sql: String,
 }
 
-// ─── SQL generation helpers ───────────────────────────────────────────────────
-
-fn insert_sql(schema: &str, table: &str, row: &RowChange, dialect: &dyn QueryDialect) -> String {
-    let (cols, vals) = insert_columns_values(&row.data, dialect);
-    let mut s = String::new();
-    let _ = write!(
-        s,
-        "INSERT INTO {}.{} ({}) VALUES ({});",
-        dialect.quote_ident(schema),
-        dialect.quote_ident(table),
-        cols,
-        vals
-    );
-    s
-}
-
-fn update_sql(schema: &str, table: &str, row: &RowUpdate, dialect: &dyn QueryDialect) -> String {
-    let mut s = String::new();
-    let _ = write!(
-        s,
-        "UPDATE {}.{} SET {} WHERE {};",
-        dialect.quote_ident(schema),
-        dialect.quote_ident(table),
-        set_clause(&row.changed_columns, dialect),
-        pk_where_clause(&row.pk, dialect),
-    );
-    s
-}
-
-fn delete_sql(schema: &str, table: &str, row: &RowChange, dialect: &dyn QueryDialect) -> String {
-    let mut s = String::new();
-    let _ = write!(
-        s,
-        "DELETE FROM {}.{} WHERE {};",
-        dialect.quote_ident(schema),
-        dialect.quote_ident(table),
-        pk_where_clause(&row.pk, dialect),
-    );
-    s
-}
-
 // ─── View builder ─────────────────────────────────────────────────────────────
 
 fn build_table_diff<'a>(
     table: &'a TableDiff,
     schema: &str,
+    guarded: bool,
     dialect: &dyn QueryDialect,
 ) -> JsonTableDiff<'a> {
     JsonTableDiff {
@@ -122,7 +94,7 @@ fn build_table_diff<'a>(
             .map(|r| JsonInsert {
                 pk: &r.pk,
                 data: &r.data,
-                sql: insert_sql(schema, &table.table_name, r, dialect),
+                sql: insert_statement(schema, table, r, dialect),
             })
             .collect(),
         updates: table
@@ -133,7 +105,7 @@ fn build_table_diff<'a>(
                 before: &r.before,
                 after: &r.after,
                 changed_columns: &r.changed_columns,
-                sql: update_sql(schema, &table.table_name, r, dialect),
+                sql: update_statement(schema, table, r, guarded, dialect),
             })
             .collect(),
         deletes: table
@@ -142,34 +114,53 @@ fn build_table_diff<'a>(
             .map(|r| JsonDelete {
                 pk: &r.pk,
                 data: &r.data,
-                sql: delete_sql(schema, &table.table_name, r, dialect),
+                sql: delete_statement(schema, table, r, guarded, dialect),
             })
             .collect(),
+        data_quality: &table.data_quality,
+        column_types: &table.column_types,
+        excluded_columns: &table.excluded_columns,
     }
 }
 
 // ─── Writer ───────────────────────────────────────────────────────────────────
 
-pub struct JsonWriter;
+#[derive(Default)]
+pub struct JsonWriter {
+    guarded: bool,
+}
+
+impl JsonWriter {
+    /// Assert the diffed values in every UPDATE / DELETE `sql`
+    /// (`[output.sql] guarded`): a statement affecting no row means the row
+    /// changed on target since the diff.
+    pub fn with_guards(mut self, guarded: bool) -> Self {
+        self.guarded = guarded;
+        self
+    }
+}
 
 impl OutputWriter for JsonWriter {
     fn format(&self, cs: &Changeset) -> Result<String> {
-        let dialect = from_driver(&cs.driver);
+        let dialect = from_driver(&cs.target_driver);
 
         let view = JsonChangeset {
             changeset_id: &cs.changeset_id,
             source_schema: &cs.source_schema,
             target_schema: &cs.target_schema,
-            driver: &cs.driver,
+            source_driver: &cs.source_driver,
+            target_driver: &cs.target_driver,
             created_at: &cs.created_at,
             source_fingerprint: &cs.source_fingerprint,
             target_fingerprint: &cs.target_fingerprint,
             tables: cs
                 .tables
                 .iter()
-                .map(|t| build_table_diff(t, &cs.target_schema, dialect.as_ref()))
+                .map(|t| build_table_diff(t, &cs.target_schema, self.guarded, dialect.as_ref()))
                 .collect(),
             summary: &cs.summary,
+            skipped_tables: &cs.skipped_tables,
+            foreign_keys: &cs.foreign_keys,
             perf: cs.perf.as_ref(),
         };
 
@@ -185,7 +176,10 @@ impl OutputWriter for JsonWriter {
 mod tests {
     use super::*;
     use crate::domain::changeset::Changeset;
-    use crate::domain::table_diff::{ColumnDiff, RowChange, RowUpdate, TableDiff};
+    use crate::domain::json_patch::PatchOperation;
+    use crate::domain::table_diff::{
+        ColumnDiff, ColumnTypes, DataQualityKind, RowChange, RowMap, RowUpdate, Side, TableDiff,
+    };
     use serde_json::{json, Value};
 
     fn make_changeset() -> Changeset {
@@ -205,6 +199,7 @@ mod tests {
                 column: "rate".to_string(),
                 before: json!(0.20),
                 after: json!(0.25),
+                patch: None,
             }],
         };
         let delete = RowChange {
@@ -222,15 +217,18 @@ mod tests {
             inserts: vec![insert],
             updates: vec![update],
             deletes: vec![delete],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
         };
 
-        Changeset::new("public", "public", "postgres", vec![table])
+        Changeset::new("public", "public", "postgres", "postgres", vec![table])
     }
 
     #[test]
     fn json_output_contains_sql_field_for_each_change() {
         let cs = make_changeset();
-        let output = JsonWriter.format(&cs).unwrap();
+        let output = JsonWriter::default().format(&cs).unwrap();
         let parsed: Value = serde_json::from_str(&output).unwrap();
         let table = &parsed["tables"][0];
 
@@ -251,8 +249,8 @@ mod tests {
     #[test]
     fn json_output_sql_uses_correct_dialect_quoting() {
         let mut cs = make_changeset();
-        cs.driver = "mysql".to_string();
-        let output = JsonWriter.format(&cs).unwrap();
+        cs.target_driver = "mysql".to_string();
+        let output = JsonWriter::default().format(&cs).unwrap();
         let parsed: Value = serde_json::from_str(&output).unwrap();
         let insert_sql = parsed["tables"][0]["inserts"][0]["sql"].as_str().unwrap();
         // MySQL uses backticks
@@ -261,4 +259,151 @@ mod tests {
             "expected backticks, got: {insert_sql}"
         );
     }
+
+    #[test]
+    fn json_output_round_trips_into_changeset() {
+        // `diffly apply --changeset` reads this file back.
+        let cs = make_changeset();
+        let output = JsonWriter::default().format(&cs).unwrap();
+        let parsed: Changeset = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed.changeset_id, cs.changeset_id);
+        assert_eq!(parsed.target_schema, "public");
+        let table = &parsed.tables[0];
+        assert_eq!(table.primary_key, vec!["id".to_string()]);
+        assert_eq!(table.inserts[0].data, cs.tables[0].inserts[0].data);
+        assert_eq!(
+            table.updates[0].changed_columns[0].after,
+            cs.tables[0].updates[0].changed_columns[0].after
+        );
+        assert_eq!(table.deletes[0].pk, cs.tables[0].deletes[0].pk);
+    }
+
+    #[test]
+    fn json_output_exposes_column_types_and_casts_with_them() {
+        let mut cs = make_changeset();
+        cs.tables[0].column_types = [
+            ("id".to_string(), "integer".to_string()),
+            ("rate".to_string(), "numeric(5,2)".to_string()),
+        ]
+        .into();
+        cs.tables[0].updates[0].changed_columns[0].after = json!("NaN");
+        let output = JsonWriter::default().format(&cs).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let table = &parsed["tables"][0];
+
+        assert_eq!(table["column_types"]["rate"], "numeric(5,2)");
+        let update_sql = table["updates"][0]["sql"].as_str().unwrap();
+        assert!(
+            update_sql.contains(r#""rate" = 'NaN'::numeric(5,2)"#),
+            "got: {update_sql}"
+        );
+
+        let read_back: Changeset = serde_json::from_str(&output).unwrap();
+        assert_eq!(read_back.tables[0].column_types, cs.tables[0].column_types);
+        // Without types (SQLite), the field is left out.
+        let plain = JsonWriter::default().format(&make_changeset()).unwrap();
+        assert!(!plain.contains("column_types"));
+    }
+
+    #[test]
+    fn json_output_includes_patch_of_json_columns() {
+        let mut cs = make_changeset();
+        cs.tables[0].updates[0].changed_columns.push(ColumnDiff {
+            column: "meta".to_string(),
+            before: json!({"tier": "gold"}),
+            after: json!({"tier": "platinum"}),
+            patch: Some(vec![PatchOperation::Replace {
+                path: "/tier".to_string(),
+                value: json!("platinum"),
+            }]),
+        });
+        let output = JsonWriter::default().format(&cs).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let columns = &parsed["tables"][0]["updates"][0]["changed_columns"];
+
+        assert!(columns[0].get("patch").is_none());
+        assert_eq!(
+            columns[1]["patch"],
+            json!([{"op": "replace", "path": "/tier", "value": "platinum"}])
+        );
+        let back: Changeset = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            back.tables[0].updates[0].changed_columns[1].patch,
+            cs.tables[0].updates[0].changed_columns[1].patch
+        );
+    }
+
+    #[test]
+    fn json_output_sql_uses_target_driver() {
+        let mut cs = make_changeset();
+        cs.source_driver = "mysql".to_string();
+        let output = JsonWriter::default().format(&cs).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed["source_driver"], "mysql");
+        assert_eq!(parsed["target_driver"], "postgres");
+        let insert_sql = parsed["tables"][0]["inserts"][0]["sql"].as_str().unwrap();
+        assert!(
+            !insert_sql.contains('`'),
+            "expected postgres quoting, got: {insert_sql}"
+        );
+    }
+
+    #[test]
+    fn legacy_driver_field_reads_as_target_driver() {
+        let cs = make_changeset();
+        let mut json: Value =
+            serde_json::from_str(&JsonWriter::default().format(&cs).unwrap()).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        obj.remove("source_driver");
+        obj.remove("target_driver");
+        obj.insert("driver".to_string(), Value::from("mysql"));
+
+        let parsed: Changeset = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.target_driver, "mysql");
+    }
+
+    #[test]
+    fn json_output_lists_data_quality_findings() {
+        let mut cs = make_changeset();
+        let json: Value =
+            serde_json::from_str(&JsonWriter::default().format(&cs).unwrap()).unwrap();
+        assert!(json["tables"][0].get("data_quality").is_none());
+
+        let row: RowMap = [("id".to_string(), json!(7))].into();
+        cs.tables[0].data_quality.push(DataQualityFinding {
+            kind: DataQualityKind::DuplicateKey,
+            side: Side::Target,
+            pk: row.clone(),
+            rows: vec![row.clone(), row],
+        });
+        let json: Value =
+            serde_json::from_str(&JsonWriter::default().format(&cs).unwrap()).unwrap();
+        let finding = &json["tables"][0]["data_quality"][0];
+        assert_eq!(finding["kind"], "duplicate_key");
+        assert_eq!(finding["side"], "target");
+        assert_eq!(finding["pk"]["id"], 7);
+        assert_eq!(finding["rows"].as_array().unwrap().len(), 2);
+
+        let parsed: Changeset = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.tables[0].data_quality, cs.tables[0].data_quality);
+    }
+
+    #[test]
+    fn json_output_guarded_sql_asserts_before_values() {
+        let cs = make_changeset();
+        let output = JsonWriter::default().with_guards(true).format(&cs).unwrap();
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let table = &parsed["tables"][0];
+
+        assert_eq!(
+            table["updates"][0]["sql"],
+            r#"UPDATE "public"."pricing_rules" SET "rate" = 0.25 WHERE "id" = 2 AND "rate" = 0.2;"#
+        );
+        assert_eq!(
+            table["deletes"][0]["sql"],
+            r#"DELETE FROM "public"."pricing_rules" WHERE "id" = 3 AND "rate" = 0.3;"#
+        );
+    }
 }
@@ -1,38 +1,63 @@
 use crate::domain::{changeset::Changeset, ports::OutputWriter};
+use crate::infrastructure::config::SqlOutputConfig;
 use anyhow::Result;
 use std::fs;
 
-use self::{html::HtmlWriter, json::JsonWriter, sql::SqlWriter};
+use self::{html::HtmlWriter, json::JsonWriter, rollback::RollbackWriter, sql::SqlWriter};
 
 pub mod html;
 pub mod json;
+pub mod rollback;
 pub mod sql;
 
 /// Register available writers - OCP: add new ones without touching main.rs
-pub fn all_writers() -> Vec<Box<dyn OutputWriter>> {
+pub fn all_writers(sql: &SqlOutputConfig) -> Vec<Box<dyn OutputWriter>> {
     vec![
-        Box::new(JsonWriter),
-        Box::new(SqlWriter),
+        Box::new(JsonWriter::default().with_guards(sql.guarded)),
+        Box::new(sql_writer(sql)),
+        Box::new(RollbackWriter::new(sql_writer(sql))),
         Box::new(HtmlWriter),
     ]
 }
 
-pub fn writer_for(format: &str) -> Option<Box<dyn OutputWriter>> {
+pub fn writer_for(format: &str, sql: &SqlOutputConfig) -> Option<Box<dyn OutputWriter>> {
     match format {
-        "json" => Some(Box::new(JsonWriter)),
-        "sql" => Some(Box::new(SqlWriter)),
+        "json" => Some(Box::new(JsonWriter::default().with_guards(sql.guarded))),
+        "sql" => Some(Box::new(sql_writer(sql))),
+        "rollback" => Some(Box::new(RollbackWriter::new(sql_writer(sql)))),
         "html" => Some(Box::new(HtmlWriter)),
         _ => None,
     }
 }
 
+fn sql_writer(sql: &SqlOutputConfig) -> SqlWriter {
+    SqlWriter::default()
+        .with_guards(sql.guarded)
+        .with_style(sql.style)
+        .with_batch_size(sql.batch_size)
+        .with_transactions(sql.transaction_size, sql.split_files)
+}
+
 /// Writes the changeset to disk via the chosen writer
 pub fn write_to_file(writer: &dyn OutputWriter, changeset: &Changeset, dir: &str) -> Result<()> {
     // Ensure the output directory exists
     fs::create_dir_all(dir)?;
 
-    let content = writer.format(changeset)?;
-    let path = format!("{}/{}.{}", dir, changeset.changeset_id, writer.extension());
-    fs::write(&path, &content)?;
+    let parts = writer.format_parts(changeset)?;
+    // Split output: numbered files, `cs_….01.sql`, `cs_….02.sql`, …
+    let width = parts.len().to_string().len().max(2);
+    for (i, content) in parts.iter().enumerate() {
+        let path = match parts.len() {
+            1 => format!("{}/{}.{}", dir, changeset.changeset_id, writer.extension()),
+            _ => format!(
+                "{}/{}.{:0width$}.{}",
+                dir,
+                changeset.changeset_id,
+                i + 1,
+                writer.extension()
+            ),
+        };
+        fs::write(&path, content)?;
+    }
     Ok(())
 }
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use tracing::warn;
+
+use crate::domain::{changeset::Changeset, ports::OutputWriter, table_diff::TableDiff};
+use crate::presentation::writers::sql::SqlWriter;
+
+/// The SQL script undoing a changeset once it has been deployed: the script
+/// of [`Changeset::invert`], written with the forward script's settings.
+///
+/// With guards, every statement asserts the value the deployment wrote, so
+/// the rollback stops on a row changed since.
+///
+/// Rows are never read with their table's `excluded_columns`: a deleted row
+/// comes back with those columns at their defaults (or fails on a NOT NULL
+/// one without). The script says so in its heading, and a warning is logged.
+pub struct RollbackWriter {
+    sql: SqlWriter,
+}
+
+impl RollbackWriter {
+    pub fn new(sql: SqlWriter) -> Self {
+        Self { sql }
+    }
+}
+
+impl OutputWriter for RollbackWriter {
+    fn format(&self, changeset: &Changeset) -> Result<String> {
+        warn_partial_rows(changeset);
+        Ok(heading(changeset) + &self.sql.format(&changeset.invert())?)
+    }
+
+    fn format_parts(&self, changeset: &Changeset) -> Result<Vec<String>> {
+        warn_partial_rows(changeset);
+        let parts = self.sql.format_parts(&changeset.invert())?;
+        Ok(parts.into_iter().map(|p| heading(changeset) + &p).collect())
+    }
+
+    fn extension(&self) -> &'static str {
+        "rollback.sql"
+    }
+}
+
+fn heading(changeset: &Changeset) -> String {
+    let mut heading = format!(
+        "-- Rollback of changeset {}: run once it has been applied\n",
+        changeset.changeset_id
+    );
+    for table in partial_rows(changeset) {
+        heading += &format!(
+            "-- WARNING: rows deleted from {} are inserted back without their excluded \
+             columns ({}), which take their defaults\n",
+            table.table_name,
+            table.excluded_columns.join(", ")
+        );
+    }
+    heading
+}
+
+/// Tables whose deleted rows the rollback cannot insert back whole.
+fn partial_rows(changeset: &Changeset) -> impl Iterator<Item = &TableDiff> {
+    changeset
+        .tables
+        .iter()
+        .filter(|t| !t.deletes.is_empty() && !t.excluded_columns.is_empty())
+}
+
+fn warn_partial_rows(changeset: &Changeset) {
+    for table in partial_rows(changeset) {
+        warn!(
+            table = %table.table_name,
+            excluded = %table.excluded_columns.join(", "),
+            "the rollback inserts deleted rows back without their excluded columns"
+        );
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::foreign_key::ForeignKey;
+    use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate};
+    use serde_json::json;
+
+    fn row(id: i64, name: &str) -> RowChange {
+        RowChange {
+            pk: [("id".to_string(), json!(id))].into(),
+            data: [
+                ("id".to_string(), json!(id)),
+                ("name".to_string(), json!(name)),
+            ]
+            .into(),
+        }
+    }
+
+    fn table(name: &str, inserts: Vec<RowChange>, deletes: Vec<RowChange>) -> TableDiff {
+        TableDiff {
+            table_name: name.to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts,
+            updates: vec![],
+            deletes,
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        }
+    }
+
+    /// The deployment inserted a customer and its order, deleted another
+    /// customer and renamed a third.
+    fn changeset() -> Changeset {
+        let mut customers = table("customers", vec![row(1, "new")], vec![row(2, "gone")]);
+        customers.updates.push(RowUpdate {
+            pk: [("id".to_string(), json!(3))].into(),
+            before: row(3, "old name").data,
+            after: row(3, "new name").data,
+            changed_columns: vec![ColumnDiff {
+                column: "name".to_string(),
+                before: json!("old name"),
+                after: json!("new name"),
+                patch: None,
+            }],
+        });
+        let orders = table("orders", vec![row(10, "first")], vec![]);
+        Changeset::new(
+            "source",
+            "target",
+            "postgres",
+            "postgres",
+            vec![customers, orders],
+        )
+        .with_foreign_keys(vec![ForeignKey {
+            table: "orders".to_string(),
+            columns: vec!["customer_id".to_string()],
+            referenced_table: "customers".to_string(),
+        }])
+    }
+
+    #[test]
+    fn rollback_reverses_every_statement() {
+        let cs = changeset();
+        let sql = RollbackWriter::new(SqlWriter::default())
+            .format(&cs)
+            .unwrap();
+        assert!(sql.starts_with(&format!("-- Rollback of changeset {}", cs.changeset_id)));
+        let at = |s: &str| sql.find(s).unwrap_or_else(|| panic!("{s}: {sql}"));
+        // Inserted rows go, children first; the deleted customer comes back.
+        assert!(
+            at("DELETE FROM \"target\".\"orders\"\n  WHERE \"id\" = 10;")
+                < at("DELETE FROM \"target\".\"customers\"\n  WHERE \"id\" = 1;")
+        );
+        assert!(
+            sql.contains("SET \"name\" = 'old name'\n  WHERE \"id\" = 3;"),
+            "{sql}"
+        );
+        assert!(sql.contains("VALUES (2, 'gone');"), "{sql}");
+        assert!(
+            sql.contains("-- Summary: 1 inserts, 1 updates, 2 deletes"),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn guarded_rollback_asserts_the_deployed_values() {
+        let sql = RollbackWriter::new(SqlWriter::default().with_guards(true))
+            .format(&changeset())
+            .unwrap();
+        assert!(
+            sql.contains("WHERE \"id\" = 3 AND \"name\" = 'new name';"),
+            "{sql}"
+        );
+        assert!(
+            sql.contains("WHERE \"id\" = 10 AND \"name\" = 'first';"),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn rollback_flags_rows_missing_excluded_columns() {
+        let mut cs = changeset();
+        cs.tables[0].excluded_columns = vec!["updated_at".to_string()];
+        // Orders have no delete: nothing of theirs is lost.
+        cs.tables[1].excluded_columns = vec!["created_at".to_string()];
+        let sql = RollbackWriter::new(SqlWriter::default())
+            .format(&cs)
+            .unwrap();
+        assert!(
+            sql.contains(
+                "-- WARNING: rows deleted from customers are inserted back without their \
+                 excluded columns (updated_at)"
+            ),
+            "{sql}"
+        );
+        assert!(!sql.contains("created_at"), "{sql}");
+        // The customer still comes back, from the columns that were read.
+        assert!(sql.contains("VALUES (2, 'gone');"), "{sql}");
+
+        cs.tables[0].deletes.clear();
+        let sql = RollbackWriter::new(SqlWriter::default())
+            .format(&cs)
+            .unwrap();
+        assert!(!sql.contains("WARNING"), "{sql}");
+    }
+}
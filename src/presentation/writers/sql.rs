@@ -1,96 +1,401 @@
-use std::collections::BTreeMap;
 use std::fmt::Write as FmtWrite;
 
-use anyhow::Result;
-use serde_json::Value;
+use anyhow::{bail, Result};
 
-use crate::domain::{changeset::Changeset, ports::OutputWriter, table_diff::ColumnDiff};
-use crate::infrastructure::db::dialect::{from_driver, QueryDialect};
+use crate::domain::{
+    changeset::Changeset,
+    foreign_key::StatementOrder,
+    ports::OutputWriter,
+    table_diff::{RowChange, RowMap, TableDiff},
+};
+use crate::infrastructure::config::SqlStyle;
+use crate::infrastructure::db::dialect::{from_driver, Dialect};
+use crate::infrastructure::db::sql_utils::{
+    delete_statement, insert_columns_values, pk_in_clause, pk_where_clause, qualified_table,
+    row_label, set_clause, set_values_clause, update_guard_clause,
+};
 
-pub struct SqlWriter;
+#[derive(Default)]
+pub struct SqlWriter {
+    guarded: bool,
+    style: SqlStyle,
+    batch_size: usize,
+    transaction_size: usize,
+    split_files: bool,
+}
 
-impl OutputWriter for SqlWriter {
-    fn format(&self, changeset: &Changeset) -> Result<String> {
-        let dialect = from_driver(&changeset.driver);
-        let mut sql = String::new();
-
-        writeln!(sql, "-- Changeset: {}", changeset.changeset_id)?;
-        writeln!(sql, "-- Source: {}", changeset.source_schema)?;
-        writeln!(sql, "-- Target: {}", changeset.target_schema)?;
-        writeln!(sql, "-- Driver: {}", changeset.driver)?;
-        writeln!(sql, "-- Generated: {}", changeset.created_at)?;
-        writeln!(
-            sql,
-            "-- Summary: {} inserts, {} updates, {} deletes",
-            changeset.summary.total_inserts,
-            changeset.summary.total_updates,
-            changeset.summary.total_deletes
-        )?;
-        writeln!(sql)?;
-        writeln!(sql, "BEGIN;")?;
-        writeln!(sql)?;
-
-        for table in &changeset.tables {
-            if table.is_empty() {
-                continue;
+/// One statement of the script, under the banner of its table and pass.
+struct Statement {
+    section: String,
+    sql: String,
+}
+
+impl SqlWriter {
+    /// Assert the diffed values in every UPDATE / DELETE and fail the script
+    /// on one matching no row (`[output.sql] guarded`).
+    pub fn with_guards(mut self, guarded: bool) -> Self {
+        self.guarded = guarded;
+        self
+    }
+
+    /// Write inserted rows as plain inserts, upserts, `MERGE` statements
+    /// (PostgreSQL 15+) or `COPY` sections (`[output.sql] style`). Rows of
+    /// keyless tables are inserted or copied.
+    pub fn with_style(mut self, style: SqlStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Put up to `batch_size` rows in each `INSERT`, `DELETE` and `COPY`
+    /// (`[output.sql] batch_size`). Guarded deletes, updates and `MERGE`
+    /// statements keep one row each.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Commit every `transaction_size` statements instead of once
+    /// (`[output.sql] transaction_size`), each transaction in its own file
+    /// with `split_files`.
+    pub fn with_transactions(mut self, transaction_size: usize, split_files: bool) -> Self {
+        self.transaction_size = transaction_size;
+        self.split_files = split_files;
+        self
+    }
+
+    /// Rows per statement.
+    fn batch(&self) -> usize {
+        self.batch_size.max(1)
+    }
+
+    /// An UPDATE / DELETE, followed by its row-count check when guarded.
+    fn guard(&self, statement: String, table: &str, pk: &RowMap, dialect: &dyn Dialect) -> String {
+        if self.guarded {
+            dialect.guarded_sql(&statement, &row_label(table, pk))
+        } else {
+            statement
+        }
+    }
+
+    fn deletes(&self, schema: &str, table: &TableDiff, dialect: &dyn Dialect) -> Vec<String> {
+        let table_q = qualified_table(schema, &table.table_name, dialect);
+        let types = &table.column_types;
+        // Keyless rows: one statement each, deleting a single row. Guarded,
+        // the row is labelled by its values.
+        if table.primary_key.is_empty() {
+            return table
+                .deletes
+                .iter()
+                .map(|del| {
+                    let statement = delete_statement(schema, table, del, false, dialect);
+                    self.guard(statement, &table.table_name, &del.data, dialect)
+                })
+                .collect();
+        }
+        if self.guarded {
+            return table
+                .deletes
+                .iter()
+                .map(|del| {
+                    let statement = format!(
+                        "DELETE FROM {}\n  WHERE {};",
+                        table_q,
+                        pk_where_clause(&del.data, types, dialect)
+                    );
+                    self.guard(statement, &table.table_name, &del.pk, dialect)
+                })
+                .collect();
+        }
+        table
+            .deletes
+            .chunks(self.batch())
+            .map(|rows| {
+                let pks: Vec<&RowMap> = rows.iter().map(|r| &r.pk).collect();
+                format!(
+                    "DELETE FROM {}\n  WHERE {};",
+                    table_q,
+                    pk_in_clause(&pks, types, dialect)
+                )
+            })
+            .collect()
+    }
+
+    fn updates(&self, schema: &str, table: &TableDiff, dialect: &dyn Dialect) -> Vec<String> {
+        let table_q = qualified_table(schema, &table.table_name, dialect);
+        let types = &table.column_types;
+        table
+            .updates
+            .iter()
+            .map(|upd| {
+                let predicate = if self.guarded {
+                    update_guard_clause(upd, types, dialect)
+                } else {
+                    pk_where_clause(&upd.pk, types, dialect)
+                };
+                let statement = format!(
+                    "UPDATE {}\n  SET {}\n  WHERE {};",
+                    table_q,
+                    set_clause(&upd.changed_columns, types, dialect),
+                    predicate
+                );
+                self.guard(statement, &table.table_name, &upd.pk, dialect)
+            })
+            .collect()
+    }
+
+    fn inserts(&self, schema: &str, table: &TableDiff, dialect: &dyn Dialect) -> Vec<String> {
+        let table_q = qualified_table(schema, &table.table_name, dialect);
+        let types = &table.column_types;
+        let key_cols: Vec<String> = table
+            .primary_key
+            .iter()
+            .map(|c| dialect.quote_ident(c))
+            .collect();
+        match self.style {
+            SqlStyle::Merge if !key_cols.is_empty() => table
+                .inserts
+                .iter()
+                .map(|ins| {
+                    let (cols, vals) = insert_columns_values(&ins.data, types, dialect);
+                    let mut values = ins.data.clone();
+                    values.retain(|col, _| !table.primary_key.contains(col));
+                    let mut sql = format!(
+                        "MERGE INTO {}\n  USING (SELECT 1) AS src ON {}\n",
+                        table_q,
+                        pk_where_clause(&ins.pk, types, dialect)
+                    );
+                    if !values.is_empty() {
+                        sql += &format!(
+                            "  WHEN MATCHED THEN UPDATE SET {}\n",
+                            set_values_clause(&values, types, dialect)
+                        );
+                    }
+                    sql + &format!(
+                        "  WHEN NOT MATCHED THEN INSERT ({}) VALUES ({});",
+                        cols, vals
+                    )
+                })
+                .collect(),
+            // One COPY per table unless batching.
+            SqlStyle::Copy => {
+                let size = match self.batch_size {
+                    0 | 1 => usize::MAX,
+                    n => n,
+                };
+                batches(&table.inserts, size)
+                    .into_iter()
+                    .map(|rows| {
+                        let cols: Vec<String> = rows[0]
+                            .data
+                            .keys()
+                            .map(|c| dialect.quote_ident(c))
+                            .collect();
+                        let mut sql =
+                            format!("COPY {} ({}) FROM STDIN;\n", table_q, cols.join(", "));
+                        for row in rows {
+                            let fields: Vec<String> = row
+                                .data
+                                .iter()
+                                .map(|(col, v)| {
+                                    dialect.copy_field(v, types.get(col).map(String::as_str))
+                                })
+                                .collect();
+                            sql += &fields.join("\t");
+                            sql.push('\n');
+                        }
+                        sql + "\\."
+                    })
+                    .collect()
             }
+            style => batches(&table.inserts, self.batch())
+                .into_iter()
+                .map(|rows| {
+                    let (cols, _) = insert_columns_values(&rows[0].data, types, dialect);
+                    let values: Vec<String> = rows
+                        .iter()
+                        .map(|r| format!("({})", insert_columns_values(&r.data, types, dialect).1))
+                        .collect();
+                    let mut sql = format!(
+                        "INSERT INTO {} ({})\n  VALUES {}",
+                        table_q,
+                        cols,
+                        values.join(",\n         ")
+                    );
+                    if style == SqlStyle::Upsert && !key_cols.is_empty() {
+                        let update_cols: Vec<String> = rows[0]
+                            .data
+                            .keys()
+                            .filter(|c| !table.primary_key.contains(c))
+                            .map(|c| dialect.quote_ident(c))
+                            .collect();
+                        sql += &format!("\n  {}", dialect.upsert_clause(&key_cols, &update_cols));
+                    }
+                    sql + ";"
+                })
+                .collect(),
+        }
+    }
 
-            writeln!(sql, "-- ============================================")?;
-            writeln!(sql, "-- Table: {}", table.table_name)?;
-            writeln!(sql, "-- ============================================")?;
-            writeln!(sql)?;
+    /// The statements of `changeset`: deletes children first, then updates
+    /// and inserts parents first.
+    fn statements(
+        &self,
+        changeset: &Changeset,
+        order: &StatementOrder,
+        dialect: &dyn Dialect,
+    ) -> Vec<Statement> {
+        let schema = &changeset.target_schema;
+        let pass = |table: &TableDiff, pass: &str, sql: Vec<String>| {
+            let section = format!("{} ({})", table.table_name, pass);
+            sql.into_iter().map(move |sql| Statement {
+                section: section.clone(),
+                sql,
+            })
+        };
+        let mut statements = Vec::new();
+        for table in order.deletes().map(|i| &changeset.tables[i]) {
+            statements.extend(pass(table, "deletes", self.deletes(schema, table, dialect)));
+        }
+        for table in order.tables.iter().map(|i| &changeset.tables[*i]) {
+            statements.extend(pass(table, "updates", self.updates(schema, table, dialect)));
+        }
+        for table in order.tables.iter().map(|i| &changeset.tables[*i]) {
+            statements.extend(pass(table, "inserts", self.inserts(schema, table, dialect)));
+        }
+        statements
+    }
 
-            for del in &table.deletes {
-                writeln!(
+    /// The script, or one per transaction when `split`.
+    fn scripts(&self, changeset: &Changeset, split: bool) -> Result<Vec<String>> {
+        let dialect = from_driver(&changeset.target_driver);
+        let dialect = dialect.as_ref();
+        if self.style == SqlStyle::Merge && !dialect.supports_merge() {
+            bail!(
+                "{} has no MERGE statement (PostgreSQL 15+ only): use style = \"upsert\" under [output.sql]",
+                changeset.target_driver
+            );
+        }
+        if self.style == SqlStyle::Copy && !dialect.supports_copy() {
+            bail!(
+                "{} has no COPY statement: use style = \"plain\" with batch_size under [output.sql]",
+                changeset.target_driver
+            );
+        }
+
+        let order = StatementOrder::plan(&changeset.tables, &changeset.foreign_keys);
+        let statements = self.statements(changeset, &order, dialect);
+        let mut transactions: Vec<&[Statement]> = match self.transaction_size {
+            0 => vec![&statements],
+            n => statements.chunks(n).collect(),
+        };
+        if transactions.is_empty() {
+            transactions.push(&[]);
+        }
+        let count = transactions.len();
+        // Deferred or disabled checks only last until the commit: a row
+        // pointing to one written later must be in the same transaction.
+        if order.defer_constraints && count > 1 {
+            bail!(
+                "the changeset needs its foreign keys checked at a single commit \
+                 (a cycle or a moved reference): set transaction_size = 0 under [output.sql]"
+            );
+        }
+
+        let header = |part: Option<usize>| -> Result<String> {
+            let mut sql = String::new();
+            writeln!(sql, "-- Changeset: {}", changeset.changeset_id)?;
+            writeln!(sql, "-- Source: {}", changeset.source_schema)?;
+            writeln!(sql, "-- Target: {}", changeset.target_schema)?;
+            writeln!(sql, "-- Source driver: {}", changeset.source_driver)?;
+            writeln!(sql, "-- Target driver: {}", changeset.target_driver)?;
+            writeln!(sql, "-- Generated: {}", changeset.created_at)?;
+            writeln!(
+                sql,
+                "-- Summary: {} inserts, {} updates, {} deletes",
+                changeset.summary.total_inserts,
+                changeset.summary.total_updates,
+                changeset.summary.total_deletes
+            )?;
+            match part {
+                Some(part) => writeln!(sql, "-- Part: {} of {}", part + 1, count)?,
+                None if count > 1 => writeln!(
                     sql,
-                    "DELETE FROM {}.{}",
-                    dialect.quote_ident(&changeset.target_schema),
-                    dialect.quote_ident(&table.table_name)
-                )?;
+                    "-- Transactions: {}, committed one after the other",
+                    count
+                )?,
+                None => {}
+            }
+            writeln!(sql)?;
+            if self.guarded {
                 writeln!(
                     sql,
-                    "  WHERE {};",
-                    pk_where_clause(&del.pk, dialect.as_ref())
+                    "-- Guarded: every UPDATE / DELETE must change exactly one row"
                 )?;
+                if let Some(setup) = dialect.guard_setup_sql() {
+                    writeln!(sql, "{}", setup)?;
+                }
                 writeln!(sql)?;
             }
-
-            for upd in &table.updates {
-                writeln!(
-                    sql,
-                    "UPDATE {}.{}",
-                    dialect.quote_ident(&changeset.target_schema),
-                    dialect.quote_ident(&table.table_name)
-                )?;
-                writeln!(
-                    sql,
-                    "  SET {}",
-                    set_clause(&upd.changed_columns, dialect.as_ref())
-                )?;
-                writeln!(
-                    sql,
-                    "  WHERE {};",
-                    pk_where_clause(&upd.pk, dialect.as_ref())
-                )?;
+            if self.style == SqlStyle::Merge {
+                writeln!(sql, "-- MERGE statements: requires PostgreSQL 15 or later")?;
+                writeln!(sql)?;
+            }
+            if !order.cyclic.is_empty() {
+                writeln!(sql, "-- Foreign-key cycle: {}", order.cyclic.join(", "))?;
                 writeln!(sql)?;
             }
+            Ok(sql)
+        };
 
-            for ins in &table.inserts {
-                let (cols, vals) = insert_columns_values(&ins.data, dialect.as_ref());
-                writeln!(
-                    sql,
-                    "INSERT INTO {}.{} ({})",
-                    dialect.quote_ident(&changeset.target_schema),
-                    dialect.quote_ident(&table.table_name),
-                    cols
-                )?;
-                writeln!(sql, "  VALUES ({});", vals)?;
+        let transaction = |statements: &[Statement]| -> Result<String> {
+            let mut sql = String::new();
+            writeln!(sql, "BEGIN;")?;
+            if order.defer_constraints {
+                writeln!(sql, "{};", dialect.defer_foreign_keys_sql())?;
+            }
+            writeln!(sql)?;
+            let mut section = None;
+            for statement in statements {
+                if section != Some(&statement.section) {
+                    write_banner(&mut sql, &statement.section)?;
+                    section = Some(&statement.section);
+                }
+                writeln!(sql, "{}", statement.sql)?;
                 writeln!(sql)?;
             }
+            if let Some(restore) = dialect
+                .restore_foreign_keys_sql()
+                .filter(|_| order.defer_constraints)
+            {
+                writeln!(sql, "{};", restore)?;
+            }
+            writeln!(sql, "COMMIT;")?;
+            Ok(sql)
+        };
+
+        if split && count > 1 {
+            transactions
+                .iter()
+                .enumerate()
+                .map(|(part, statements)| Ok(header(Some(part))? + &transaction(statements)?))
+                .collect()
+        } else {
+            let bodies = transactions
+                .iter()
+                .map(|statements| transaction(statements))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(vec![header(None)? + &bodies.join("\n")])
         }
+    }
+}
+
+impl OutputWriter for SqlWriter {
+    fn format(&self, changeset: &Changeset) -> Result<String> {
+        Ok(self.scripts(changeset, false)?.remove(0))
+    }
 
-        writeln!(sql, "COMMIT;")?;
-        Ok(sql)
+    fn format_parts(&self, changeset: &Changeset) -> Result<Vec<String>> {
+        self.scripts(changeset, self.split_files)
     }
 
     fn extension(&self) -> &'static str {
@@ -98,41 +403,29 @@ impl OutputWriter for SqlWriter {
     }
 }
 
-pub(crate) fn pk_where_clause(pk: &BTreeMap<String, Value>, dialect: &dyn QueryDialect) -> String {
-    pk.iter()
-        .map(|(col, val)| {
-            let col_q = dialect.quote_ident(col);
-            if val == &Value::Null {
-                format!("{} IS NULL", col_q)
-            } else {
-                format!("{} = {}", col_q, dialect.sql_literal(val))
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" AND ")
-}
-
-pub(crate) fn set_clause(columns: &[ColumnDiff], dialect: &dyn QueryDialect) -> String {
-    columns
-        .iter()
-        .map(|c| {
-            format!(
-                "{} = {}",
-                dialect.quote_ident(&c.column),
-                dialect.sql_literal(&c.after)
-            )
-        })
-        .collect::<Vec<_>>()
-        .join(", ")
+/// `rows` in statements of up to `size` rows, a statement ending where the
+/// inserted columns change.
+fn batches(rows: &[RowChange], size: usize) -> Vec<&[RowChange]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    for end in 1..=rows.len() {
+        if end == rows.len()
+            || end - start == size
+            || !rows[end].data.keys().eq(rows[start].data.keys())
+        {
+            batches.push(&rows[start..end]);
+            start = end;
+        }
+    }
+    batches
 }
 
-pub(crate) fn insert_columns_values(
-    data: &BTreeMap<String, Value>,
-    dialect: &dyn QueryDialect,
-) -> (String, String) {
-    let cols: Vec<String> = data.keys().map(|k| dialect.quote_ident(k)).collect();
-    let vals: Vec<String> = data.values().map(|v| dialect.sql_literal(v)).collect();
-    (cols.join(", "), vals.join(", "))
+fn write_banner(sql: &mut String, section: &str) -> Result<()> {
+    writeln!(sql, "-- ============================================")?;
+    writeln!(sql, "-- Table: {}", section)?;
+    writeln!(sql, "-- ============================================")?;
+    writeln!(sql)?;
+    Ok(())
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -141,9 +434,16 @@ pub(crate) fn insert_columns_values(
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::infrastructure::db::dialect::{MysqlDialect, PostgresDialect, SqliteDialect};
-    use serde_json::json;
+    use super::SqlWriter;
+    use crate::domain::changeset::Changeset;
+    use crate::domain::foreign_key::ForeignKey;
+    use crate::domain::ports::OutputWriter;
+    use crate::domain::table_diff::{ColumnDiff, ColumnTypes, RowChange, RowUpdate, TableDiff};
+    use crate::infrastructure::config::SqlStyle;
+    use crate::infrastructure::db::dialect::{
+        MysqlDialect, PostgresDialect, QueryDialect, SqliteDialect,
+    };
+    use serde_json::{json, Value};
 
     fn pg() -> PostgresDialect {
         PostgresDialect
@@ -155,55 +455,34 @@ mod tests {
         SqliteDialect
     }
 
-    #[test]
-    fn test_pk_where_clause_null_is_null() {
-        let mut pk = BTreeMap::new();
-        pk.insert("id".to_string(), Value::Null);
-        assert_eq!(pk_where_clause(&pk, &pg()), r#""id" IS NULL"#);
-    }
-
-    #[test]
-    fn test_pk_where_clause_value() {
-        let mut pk = BTreeMap::new();
-        pk.insert("id".to_string(), json!(42));
-        assert_eq!(pk_where_clause(&pk, &pg()), r#""id" = 42"#);
-    }
-
-    #[test]
-    fn test_pk_where_clause_mysql_backticks() {
-        let mut pk = BTreeMap::new();
-        pk.insert("id".to_string(), json!(1));
-        assert_eq!(pk_where_clause(&pk, &my()), "`id` = 1");
-    }
-
     #[test]
     fn test_sql_literal_null() {
-        assert_eq!(pg().sql_literal(&Value::Null), "NULL");
-        assert_eq!(my().sql_literal(&Value::Null), "NULL");
+        assert_eq!(pg().sql_literal(&Value::Null, None), "NULL");
+        assert_eq!(my().sql_literal(&Value::Null, None), "NULL");
     }
 
     #[test]
     fn test_sql_literal_bool() {
-        assert_eq!(pg().sql_literal(&Value::Bool(true)), "TRUE");
-        assert_eq!(my().sql_literal(&Value::Bool(false)), "FALSE");
+        assert_eq!(pg().sql_literal(&Value::Bool(true), None), "TRUE");
+        assert_eq!(my().sql_literal(&Value::Bool(false), None), "FALSE");
     }
 
     #[test]
     fn test_sql_literal_number() {
-        assert_eq!(pg().sql_literal(&json!(19.99)), "19.99");
-        assert_eq!(my().sql_literal(&json!(42)), "42");
+        assert_eq!(pg().sql_literal(&json!(19.99), None), "19.99");
+        assert_eq!(my().sql_literal(&json!(42), None), "42");
     }
 
     #[test]
     fn test_sql_literal_string_escapes_quotes() {
         let v = Value::String("it's fine".to_string());
-        assert_eq!(pg().sql_literal(&v), "'it''s fine'");
+        assert_eq!(pg().sql_literal(&v, None), "'it''s fine'");
     }
 
     #[test]
     fn test_sql_literal_jsonb_postgres() {
         let v = json!({"key": "val"});
-        let lit = pg().sql_literal(&v);
+        let lit = pg().sql_literal(&v, None);
         assert!(
             lit.ends_with("::jsonb"),
             "Expected ::jsonb cast, got: {}",
@@ -215,7 +494,7 @@ mod tests {
     #[test]
     fn test_sql_literal_json_mysql_no_cast() {
         let v = json!({"key": "val"});
-        let lit = my().sql_literal(&v);
+        let lit = my().sql_literal(&v, None);
         assert!(
             !lit.contains("::jsonb"),
             "MySQL must not have ::jsonb, got: {}",
@@ -227,7 +506,7 @@ mod tests {
     #[test]
     fn test_sql_literal_json_sqlite_no_cast() {
         let v = json!([1, 2, 3]);
-        let lit = sq().sql_literal(&v);
+        let lit = sq().sql_literal(&v, None);
         assert!(
             !lit.contains("::"),
             "SQLite must not have any cast, got: {}",
@@ -235,4 +514,378 @@ mod tests {
         );
         assert!(lit.starts_with('\''));
     }
+
+    fn changeset(driver: &str) -> Changeset {
+        let update = RowUpdate {
+            pk: [("id".to_string(), json!(1))].into(),
+            before: [("rate".to_string(), Value::Null)].into(),
+            after: [("rate".to_string(), json!(0.25))].into(),
+            changed_columns: vec![ColumnDiff {
+                column: "rate".to_string(),
+                before: Value::Null,
+                after: json!(0.25),
+                patch: None,
+            }],
+        };
+        let delete = RowChange {
+            pk: [("id".to_string(), json!(2))].into(),
+            data: [
+                ("id".to_string(), json!(2)),
+                ("rate".to_string(), json!(0.30)),
+            ]
+            .into(),
+        };
+        let insert = RowChange {
+            pk: [("id".to_string(), json!(3))].into(),
+            data: [
+                ("id".to_string(), json!(3)),
+                ("rate".to_string(), json!(0.5)),
+            ]
+            .into(),
+        };
+        let table = TableDiff {
+            table_name: "pricing_rules".to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![insert],
+            updates: vec![update],
+            deletes: vec![delete],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        Changeset::new("public", "public", driver, driver, vec![table])
+    }
+
+    #[test]
+    fn test_unguarded_script_matches_on_the_key_only() {
+        let sql = SqlWriter::default().format(&changeset("postgres")).unwrap();
+        assert!(sql.contains("  WHERE \"id\" = 2;"), "{sql}");
+        assert!(sql.contains("  WHERE \"id\" = 1;"), "{sql}");
+        assert!(!sql.contains("DO $diffly$"), "{sql}");
+    }
+
+    #[test]
+    fn test_guarded_postgres_script_raises_on_a_changed_row() {
+        let sql = SqlWriter::default()
+            .with_guards(true)
+            .format(&changeset("postgres"))
+            .unwrap();
+        assert!(
+            sql.contains("BEGIN\nUPDATE \"public\".\"pricing_rules\"\n  SET \"rate\" = 0.25\n  WHERE \"id\" = 1 AND \"rate\" IS NULL;"),
+            "{sql}"
+        );
+        assert!(
+            sql.contains("WHERE \"id\" = 2 AND \"rate\" = 0.3;"),
+            "{sql}"
+        );
+        assert_eq!(
+            sql.matches("GET DIAGNOSTICS diffly_affected_rows = ROW_COUNT;")
+                .count(),
+            2
+        );
+        assert!(sql.contains("'pricing_rules (id=1)'"), "{sql}");
+        assert!(sql.find("DO $diffly$").unwrap() > sql.find("BEGIN;").unwrap());
+    }
+
+    #[test]
+    fn test_guarded_mysql_script_checks_the_row_count() {
+        let sql = SqlWriter::default()
+            .with_guards(true)
+            .format(&changeset("mysql"))
+            .unwrap();
+        let setup = sql
+            .find("CREATE TEMPORARY TABLE IF NOT EXISTS diffly_guard")
+            .unwrap();
+        assert!(setup < sql.find("BEGIN;").unwrap());
+        assert!(
+            sql.contains("  WHERE `id` = 2 AND `rate` = 0.3;\nINSERT INTO diffly_guard (unchanged_on_target) VALUES (CASE WHEN ROW_COUNT() = 1 THEN 1 END);"),
+            "{sql}"
+        );
+        let sql = SqlWriter::default()
+            .with_guards(true)
+            .format(&changeset("sqlite"))
+            .unwrap();
+        assert!(sql.contains("CASE WHEN changes() = 1"), "{sql}");
+    }
+
+    #[test]
+    fn test_guarded_keyless_delete_checks_the_row_count() {
+        let row = RowChange {
+            pk: Default::default(),
+            data: [("event".to_string(), json!("login"))].into(),
+        };
+        let table = TableDiff {
+            table_name: "audit_log".to_string(),
+            primary_key: vec![],
+            inserts: vec![],
+            updates: vec![],
+            deletes: vec![row],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        let cs = Changeset::new("public", "public", "postgres", "postgres", vec![table]);
+        let sql = SqlWriter::default().with_guards(true).format(&cs).unwrap();
+        assert!(
+            sql.contains("BEGIN\nDELETE FROM \"public\".\"audit_log\" WHERE ctid = (SELECT ctid FROM \"public\".\"audit_log\" WHERE \"event\" = 'login' LIMIT 1);\n  GET DIAGNOSTICS"),
+            "{sql}"
+        );
+        assert!(sql.contains("'audit_log (event=\"login\")'"), "{sql}");
+    }
+
+    #[test]
+    fn test_upsert_style_overwrites_an_existing_row() {
+        let upsert = |driver: &str| {
+            SqlWriter::default()
+                .with_style(SqlStyle::Upsert)
+                .format(&changeset(driver))
+                .unwrap()
+        };
+        assert!(
+            upsert("postgres").contains(
+                "  VALUES (3, 0.5)\n  ON CONFLICT (\"id\") DO UPDATE SET \"rate\" = EXCLUDED.\"rate\";"
+            ),
+            "{}",
+            upsert("postgres")
+        );
+        assert!(upsert("sqlite").contains("ON CONFLICT (\"id\") DO UPDATE"));
+        assert!(
+            upsert("mysql").contains(
+                "  VALUES (3, 0.5)\n  AS new ON DUPLICATE KEY UPDATE `rate` = new.`rate`;"
+            ),
+            "{}",
+            upsert("mysql")
+        );
+        assert!(
+            upsert("mariadb")
+                .contains("  VALUES (3, 0.5)\n  ON DUPLICATE KEY UPDATE `rate` = VALUES(`rate`);"),
+            "{}",
+            upsert("mariadb")
+        );
+        // Updates and deletes are re-runnable as they are.
+        assert!(upsert("postgres").contains("  WHERE \"id\" = 1;"));
+    }
+
+    #[test]
+    fn test_merge_style_on_postgres_only() {
+        let sql = SqlWriter::default()
+            .with_style(SqlStyle::Merge)
+            .format(&changeset("postgres"))
+            .unwrap();
+        assert!(
+            sql.contains(
+                "MERGE INTO \"public\".\"pricing_rules\"\n  USING (SELECT 1) AS src ON \"id\" = 3\n  \
+                 WHEN MATCHED THEN UPDATE SET \"rate\" = 0.5\n  \
+                 WHEN NOT MATCHED THEN INSERT (\"id\", \"rate\") VALUES (3, 0.5);"
+            ),
+            "{sql}"
+        );
+        assert!(sql.contains("-- MERGE statements: requires PostgreSQL 15 or later"));
+        let err = SqlWriter::default()
+            .with_style(SqlStyle::Merge)
+            .format(&changeset("mysql"))
+            .unwrap_err();
+        assert!(err.to_string().contains("no MERGE"), "{err}");
+        assert!(err.to_string().contains("PostgreSQL 15+"), "{err}");
+    }
+
+    /// `orders` (listed first) references `customers`: an order and its new
+    /// customer are inserted, another order and its customer deleted.
+    fn orders_and_customers(driver: &str) -> Changeset {
+        let row = |id: i64| RowChange {
+            pk: [("id".to_string(), json!(id))].into(),
+            data: [("id".to_string(), json!(id))].into(),
+        };
+        let table = |name: &str| TableDiff {
+            table_name: name.to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![row(1)],
+            updates: vec![],
+            deletes: vec![row(2)],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        Changeset::new(
+            "public",
+            "public",
+            driver,
+            driver,
+            vec![table("orders"), table("customers")],
+        )
+        .with_foreign_keys(vec![ForeignKey {
+            table: "orders".to_string(),
+            columns: vec!["customer_id".to_string()],
+            referenced_table: "customers".to_string(),
+        }])
+    }
+
+    #[test]
+    fn test_statements_follow_foreign_keys() {
+        let sql = SqlWriter::default()
+            .format(&orders_and_customers("postgres"))
+            .unwrap();
+        let at = |banner: &str| {
+            sql.find(banner)
+                .unwrap_or_else(|| panic!("{banner}: {sql}"))
+        };
+        assert!(at("-- Table: orders (deletes)") < at("-- Table: customers (deletes)"));
+        assert!(at("-- Table: customers (deletes)") < at("-- Table: customers (inserts)"));
+        assert!(at("-- Table: customers (inserts)") < at("-- Table: orders (inserts)"));
+        assert!(!sql.contains("DEFERRED"), "{sql}");
+    }
+
+    /// `orders_and_customers`, customers also referencing their last order.
+    fn cyclic_orders(driver: &str) -> Changeset {
+        let mut cs = orders_and_customers(driver);
+        cs.foreign_keys.push(ForeignKey {
+            table: "customers".to_string(),
+            columns: vec!["last_order_id".to_string()],
+            referenced_table: "orders".to_string(),
+        });
+        cs
+    }
+
+    #[test]
+    fn test_foreign_key_cycles_defer_constraints() {
+        let cyclic = |driver: &str| SqlWriter::default().format(&cyclic_orders(driver)).unwrap();
+        let sql = cyclic("postgres");
+        assert!(
+            sql.contains("-- Foreign-key cycle: orders, customers"),
+            "{sql}"
+        );
+        assert!(
+            sql.contains("BEGIN;\nSET CONSTRAINTS ALL DEFERRED;\n"),
+            "{sql}"
+        );
+        let sql = cyclic("mysql");
+        assert!(
+            sql.contains("BEGIN;\nSET FOREIGN_KEY_CHECKS = 0;\n"),
+            "{sql}"
+        );
+        assert!(
+            sql.ends_with("SET FOREIGN_KEY_CHECKS = 1;\nCOMMIT;\n"),
+            "{sql}"
+        );
+        assert!(cyclic("sqlite").contains("PRAGMA defer_foreign_keys = ON;"));
+    }
+
+    /// Three rows of `items` inserted, three deleted.
+    fn bulk(driver: &str) -> Changeset {
+        let row = |id: i64, name: &str| RowChange {
+            pk: [("id".to_string(), json!(id))].into(),
+            data: [
+                ("id".to_string(), json!(id)),
+                ("name".to_string(), json!(name)),
+            ]
+            .into(),
+        };
+        let table = TableDiff {
+            table_name: "items".to_string(),
+            primary_key: vec!["id".to_string()],
+            inserts: vec![row(1, "a"), row(2, "tab\there"), row(3, "c")],
+            updates: vec![],
+            deletes: vec![row(11, "x"), row(12, "y"), row(13, "z")],
+            data_quality: vec![],
+            column_types: ColumnTypes::new(),
+            excluded_columns: Vec::new(),
+        };
+        Changeset::new("public", "public", driver, driver, vec![table])
+    }
+
+    #[test]
+    fn test_batches_group_rows_per_statement() {
+        let sql = SqlWriter::default()
+            .with_batch_size(2)
+            .format(&bulk("postgres"))
+            .unwrap();
+        assert!(
+            sql.contains("DELETE FROM \"public\".\"items\"\n  WHERE \"id\" IN (11, 12);"),
+            "{sql}"
+        );
+        assert!(sql.contains("  WHERE \"id\" = 13;"), "{sql}");
+        assert!(
+            sql.contains(
+                "INSERT INTO \"public\".\"items\" (\"id\", \"name\")\n  VALUES (1, 'a'),\n         (2, 'tab\there');"
+            ),
+            "{sql}"
+        );
+        assert!(sql.contains("  VALUES (3, 'c');"), "{sql}");
+
+        let sql = SqlWriter::default()
+            .with_batch_size(10)
+            .with_style(SqlStyle::Upsert)
+            .format(&bulk("mysql"))
+            .unwrap();
+        assert!(
+            sql.contains(
+                "         (3, 'c')\n  AS new ON DUPLICATE KEY UPDATE `name` = new.`name`;"
+            ),
+            "{sql}"
+        );
+        // Guarded deletes check one row each.
+        let sql = SqlWriter::default()
+            .with_batch_size(10)
+            .with_guards(true)
+            .format(&bulk("mysql"))
+            .unwrap();
+        assert_eq!(sql.matches("DELETE FROM").count(), 3, "{sql}");
+    }
+
+    #[test]
+    fn test_transactions_commit_every_n_statements() {
+        let writer = SqlWriter::default().with_transactions(4, false);
+        let sql = writer.format(&bulk("postgres")).unwrap();
+        assert!(sql.contains("-- Transactions: 2, committed"), "{sql}");
+        assert_eq!(sql.matches("BEGIN;").count(), 2);
+        assert_eq!(sql.matches("COMMIT;").count(), 2);
+        // The second transaction restates where it is.
+        let second = &sql[sql.rfind("BEGIN;").unwrap()..];
+        assert!(second.contains("-- Table: items (inserts)"), "{second}");
+        assert!(!second.contains("DELETE"), "{second}");
+        assert_eq!(writer.format_parts(&bulk("postgres")).unwrap().len(), 1);
+
+        let parts = SqlWriter::default()
+            .with_transactions(4, true)
+            .format_parts(&bulk("sqlite"))
+            .unwrap();
+        assert_eq!(parts.len(), 2);
+        assert!(parts[1].starts_with("-- Changeset: "));
+        assert!(parts[1].contains("-- Part: 2 of 2"), "{}", parts[1]);
+        assert!(parts[1].ends_with("COMMIT;\n"));
+    }
+
+    #[test]
+    fn test_deferred_foreign_keys_keep_one_transaction() {
+        let err = SqlWriter::default()
+            .with_transactions(1, true)
+            .format_parts(&cyclic_orders("postgres"))
+            .unwrap_err();
+        assert!(err.to_string().contains("transaction_size = 0"), "{err}");
+        // A transaction_size the changeset fits in is fine.
+        let parts = SqlWriter::default()
+            .with_transactions(100, true)
+            .format_parts(&cyclic_orders("postgres"))
+            .unwrap();
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn test_copy_style_loads_rows_on_postgres() {
+        let sql = SqlWriter::default()
+            .with_style(SqlStyle::Copy)
+            .format(&bulk("postgres"))
+            .unwrap();
+        assert!(
+            sql.contains(
+                "COPY \"public\".\"items\" (\"id\", \"name\") FROM STDIN;\n1\ta\n2\ttab\\there\n3\tc\n\\.\n"
+            ),
+            "{sql}"
+        );
+        let err = SqlWriter::default()
+            .with_style(SqlStyle::Copy)
+            .format(&bulk("mysql"))
+            .unwrap_err();
+        assert!(err.to_string().contains("no COPY"), "{err}");
+    }
 }